@@ -1,8 +1,9 @@
 use crate::collection;
-use crate::define::{AstBox, AstVisitor, NestedMap, Operator};
+use crate::define::{AstBox, AstVisitor, NestedMap, Operator, Symbol, Type};
 use crate::define::{FunDefRc, FunctionDef, Inst, ValRc, Value};
 use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::io;
 use std::rc::Rc;
 
@@ -21,8 +22,8 @@ impl Compiler {
       gen: Generator {
         func: None,
         funcs: collection! {
-          "input".to_string() => Rc::new(RefCell::new(FunctionDef::new_lib("input".to_string(), 0))),
-          "print".to_string() => Rc::new(RefCell::new(FunctionDef::new_lib("print".to_string(), 1))),
+          Symbol::new("input") => Rc::new(RefCell::new(FunctionDef::new_lib("input".to_string(), 0))),
+          Symbol::new("print") => Rc::new(RefCell::new(FunctionDef::new_lib("print".to_string(), 1))),
         },
         vars: NestedMap::new(),
         label_id: 0,
@@ -44,14 +45,20 @@ impl Compiler {
   }
 }
 
+impl Default for Compiler {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 /// IR generator.
 struct Generator {
   /// Current function.
   func: Option<FunDefRc>,
   /// All defined functions.
-  funcs: HashMap<String, FunDefRc>,
+  funcs: HashMap<Symbol, FunDefRc>,
   /// All defined variables.
-  vars: NestedMap<String, ValRc>,
+  vars: NestedMap<Symbol, ValRc>,
   /// Current label id.
   label_id: usize,
 }
@@ -71,24 +78,32 @@ impl Generator {
 impl AstVisitor for Generator {
   type Result = Result;
 
-  fn visit_fundef(&mut self, name: &String, args: &[String], body: &AstBox) -> Self::Result {
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
     // check argument count
     (args.len() <= 8)
-      .then(|| ())
+      .then_some(())
       .ok_or("argument count must be less than or equal to 8")?;
     // create function definition IR
-    let func = Rc::new(RefCell::new(FunctionDef::new(name.clone(), args.len())));
+    let func = Rc::new(RefCell::new(FunctionDef::new(name.to_string(), args.len())));
     self.func = Some(func.clone());
     // add to function map
     self
       .funcs
-      .insert(name.clone(), func)
+      .insert(*name, func)
       .map_or(Ok(()), |_| Err("function has already been defined"))?;
     // enter argument environment
     self.vars.push();
     // add definitions of arguments
     for (i, arg) in args.iter().enumerate() {
-      self.vars.add(arg.clone(), Rc::new(Value::ArgRef { id: i }));
+      self.vars.add(*arg, Rc::new(Value::ArgRef { id: i }));
     }
     // generate body
     self.visit(body)?;
@@ -109,15 +124,15 @@ impl AstVisitor for Generator {
     Ok(None)
   }
 
-  fn visit_define(&mut self, name: &String, expr: &AstBox) -> Self::Result {
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
     // generate expression
     let expr = self.visit(expr)?.unwrap();
     // add symbol definition
     let slot = self.func().add_slot();
     self
       .vars
-      .add(name.clone(), slot.clone())
-      .then(|| ())
+      .add(*name, slot.clone())
+      .then_some(())
       .ok_or("symbol has already been defined")?;
     // generate assign instruction
     self.func().push_inst(Box::new(Inst::Assign {
@@ -127,7 +142,7 @@ impl AstVisitor for Generator {
     Ok(None)
   }
 
-  fn visit_assign(&mut self, name: &String, expr: &AstBox) -> Self::Result {
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
     // generate expression
     let expr = self.visit(expr)?.unwrap();
     // get stack slot of the symbol
@@ -140,7 +155,7 @@ impl AstVisitor for Generator {
       dest: slot.clone(),
       val: expr,
     }));
-    Ok(None)
+    Ok(Some(slot.clone()))
   }
 
   fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
@@ -151,7 +166,7 @@ impl AstVisitor for Generator {
     let end_if = else_then.is_some().then(|| self.new_label());
     // generate conditional branch
     let branch = Box::new(Inst::BranchEqz {
-      cond: cond,
+      cond,
       label: false_branch.clone(),
     });
     self.func().push_inst(branch);
@@ -175,9 +190,69 @@ impl AstVisitor for Generator {
     })
   }
 
-  fn visit_return(&mut self, expr: &AstBox) -> Self::Result {
-    // generate return value
-    let expr = self.visit(expr)?.unwrap();
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    // generate scrutinee
+    let val = self.visit(scrutinee)?.unwrap();
+    let end_switch = self.new_label();
+    // compare with values of cases one by one
+    for (case, body) in cases {
+      // the target only supports 32-bit integers
+      let case = i32::try_from(*case).map_err(|_| "integer literal too large")?;
+      let next_case = self.new_label();
+      let cond = self.func().add_slot();
+      self.func().push_inst(Box::new(Inst::Binary {
+        dest: cond.clone(),
+        op: Operator::Eq,
+        lhs: val.clone(),
+        rhs: Rc::new(Value::Integer { val: case }),
+      }));
+      self.func().push_inst(Box::new(Inst::BranchEqz {
+        cond,
+        label: next_case.clone(),
+      }));
+      // generate body of the case
+      self.visit(body)?;
+      self.func().push_inst(Box::new(Inst::Jump {
+        label: end_switch.clone(),
+      }));
+      self.func().push_inst(Box::new(Inst::Label { label: next_case }));
+    }
+    // generate the default case
+    if let Some(default) = default {
+      self.visit(default)?;
+    }
+    self.func().push_inst(Box::new(Inst::Label { label: end_switch }));
+    Ok(None)
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    // generate label of the body
+    let body_label = self.new_label();
+    self.func().push_inst(Box::new(Inst::Label {
+      label: body_label.clone(),
+    }));
+    // generate the body
+    self.visit(body)?;
+    // generate the condition, and branch back to the body
+    let cond = self.visit(cond)?.unwrap();
+    self.func().push_inst(Box::new(Inst::BranchNez {
+      cond,
+      label: body_label,
+    }));
+    Ok(None)
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    // generate return value, functions without return values return 0
+    let expr = match expr {
+      Some(expr) => self.visit(expr)?.unwrap(),
+      None => Rc::new(Value::Integer { val: 0 }),
+    };
     // generate return instruction
     self.func().push_inst(Box::new(Inst::Return { val: expr }));
     Ok(None)
@@ -213,6 +288,8 @@ impl AstVisitor for Generator {
         .func()
         .push_inst(Box::new(Inst::Label { label: end_logic }));
       Ok(Some(lhs))
+    } else if *op == Operator::Pow {
+      Err("power operator is not supported")
     } else {
       // generate lhs & rhs
       let lhs = self.visit(lhs)?.unwrap();
@@ -222,8 +299,8 @@ impl AstVisitor for Generator {
       self.func().push_inst(Box::new(Inst::Binary {
         dest: dest.clone(),
         op: op.clone(),
-        lhs: lhs,
-        rhs: rhs,
+        lhs,
+        rhs,
       }));
       Ok(Some(dest))
     }
@@ -237,17 +314,17 @@ impl AstVisitor for Generator {
     self.func().push_inst(Box::new(Inst::Unary {
       dest: dest.clone(),
       op: op.clone(),
-      opr: opr,
+      opr,
     }));
     Ok(Some(dest))
   }
 
-  fn visit_funcall(&mut self, name: &String, args: &[AstBox]) -> Self::Result {
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
     // get the function definition
     let func = self.funcs.get(name).ok_or("function not found")?.clone();
     // check argument count
     (args.len() == func.borrow().arg_num())
-      .then(|| ())
+      .then_some(())
       .ok_or("argument count mismatch")?;
     // generate arguments
     let args: std::result::Result<Vec<_>, _> = args
@@ -264,11 +341,13 @@ impl AstVisitor for Generator {
     Ok(Some(dest))
   }
 
-  fn visit_int(&mut self, val: &i32) -> Self::Result {
-    Ok(Some(Rc::new(Value::Integer { val: *val })))
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    // the target only supports 32-bit integers
+    let val = i32::try_from(*val).map_err(|_| "integer literal too large")?;
+    Ok(Some(Rc::new(Value::Integer { val })))
   }
 
-  fn visit_id(&mut self, val: &String) -> Self::Result {
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
     // get stack slot of the symbol
     Ok(Some(
       self
@@ -278,4 +357,8 @@ impl AstVisitor for Generator {
         .clone(),
     ))
   }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {
+    Ok(None)
+  }
 }
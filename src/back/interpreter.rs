@@ -1,9 +1,14 @@
-use crate::define::{Ast, AstBox, AstVisitor};
-use crate::define::{NestedMap, Operator};
+use super::prelude;
+use crate::define::{Ast, AstBox, AstVisitor, Type};
+use crate::define::{eval_binary, eval_unary, NestedMap, Operator, OverflowMode, Program, Symbol};
+use crate::pass::purity::PurityAnalyzer;
 use crate::unwrap_struct;
 use lazy_static::lazy_static;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::Arguments;
+use std::io::Write;
 use std::rc::Rc;
 
 /// Interpreter for `first-step` language.
@@ -13,7 +18,20 @@ pub struct Interpreter {
 }
 
 /// `Result` for `Interpreter`.
-pub type Result = std::result::Result<i32, &'static str>;
+pub type Result = std::result::Result<i64, &'static str>;
+
+/// Native function implemented in Rust, takes values of arguments.
+///
+/// Return values are wrapped around to 32-bit integers,
+/// unless the 64-bit mode is enabled.
+pub type NativeFn = Box<dyn Fn(&[i64]) -> Result>;
+
+/// Default maximum depth of function calls.
+///
+/// Calls are evaluated on the Rust stack, each call takes about 4 KiB
+/// in debug builds, so this fits the 2 MiB stack of spawned threads.
+/// Use `set_max_depth` for deeper recursion on larger stacks.
+const DEFAULT_MAX_DEPTH: usize = 256;
 
 impl Interpreter {
   /// Creates a new interpreter.
@@ -21,60 +39,280 @@ impl Interpreter {
     Self {
       intp: InterpreterImpl {
         funcs: Rc::new(RefCell::new(HashMap::new())),
+        natives: HashMap::new(),
         envs: NestedMap::new(),
+        depth: 0,
+        max_depth: DEFAULT_MAX_DEPTH,
+        gas: None,
+        overflow_mode: OverflowMode::default(),
+        int64: false,
+        trace: false,
+        trace_writer: Box::new(std::io::stderr()),
+        memoize: false,
+        memo: HashMap::new(),
+        pure_funcs: None,
+        call_count: 0,
+        flow: Flow::Normal,
       },
     }
   }
 
+  /// Sets the maximum depth of function calls.
+  pub fn set_max_depth(&mut self, max_depth: usize) {
+    self.intp.max_depth = max_depth;
+  }
+
+  /// Sets the overflow handling mode of integer arithmetic.
+  pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+    self.intp.overflow_mode = mode;
+  }
+
+  /// Enables or disables the 64-bit mode (disabled by default),
+  /// in which integers are 64-bit rather than 32-bit.
+  ///
+  /// Integer literals and inputs must fit in the current integer type.
+  pub fn set_int64(&mut self, int64: bool) {
+    self.intp.int64 = int64;
+  }
+
+  /// Sets the gas limit, which is the maximum number of statements
+  /// to be evaluated, `None` for unlimited (by default).
+  ///
+  /// Each evaluated statement consumes one unit of gas, and the
+  /// evaluation is aborted when the gas is used up.
+  pub fn set_gas_limit(&mut self, gas: Option<usize>) {
+    self.intp.gas = gas;
+  }
+
+  /// Enables or disables the trace mode, in which each visited node
+  /// is logged to the trace writer (`stderr` by default) before evaluation.
+  pub fn set_trace(&mut self, trace: bool) {
+    self.intp.trace = trace;
+  }
+
+  /// Sets the writer of the trace mode.
+  pub fn set_trace_writer(&mut self, writer: impl Write + 'static) {
+    self.intp.trace_writer = Box::new(writer);
+  }
+
+  /// Enables or disables memoization of pure functions (disabled by default).
+  ///
+  /// Results of calls to pure functions are cached by their arguments,
+  /// so that each call with the same arguments is evaluated only once.
+  pub fn set_memoize(&mut self, memoize: bool) {
+    self.intp.memoize = memoize;
+  }
+
+  /// Gets the number of user-defined function calls evaluated so far,
+  /// calls whose results are cached are not counted.
+  pub fn call_count(&self) -> usize {
+    self.intp.call_count
+  }
+
+  /// Registers the specific native function, which can be called
+  /// by programs like other functions.
+  ///
+  /// Native functions can not be redefined by programs, registering a
+  /// function whose name has already been defined is an error, and vice versa.
+  pub fn register_native(
+    &mut self,
+    name: &str,
+    f: NativeFn,
+  ) -> std::result::Result<(), &'static str> {
+    let name = Symbol::new(name);
+    if self.intp.is_defined(&name) {
+      Err("function has already been defined")
+    } else {
+      self.intp.natives.insert(name, f);
+      Ok(())
+    }
+  }
+
+  /// Registers all functions in the prelude (`abs`, `min`, `max`, `pow` and `gcd`)
+  /// as native functions.
+  pub fn register_prelude(&mut self) -> std::result::Result<(), &'static str> {
+    for (name, f) in prelude::FUNCTIONS {
+      self.register_native(name, Box::new(f))?;
+    }
+    Ok(())
+  }
+
   /// Adds the specific function definition to interpreter
-  pub fn add_func_def(&mut self, func: AstBox) -> std::result::Result<(), &str> {
+  pub fn add_func_def(&mut self, func: AstBox) -> std::result::Result<(), &'static str> {
     match func.as_ref() {
       // get function name
       Ast::FunDef { name, .. } => {
         // check if is already defined
-        if !self.intp.funcs.borrow().contains_key(name) {
+        if !self.intp.is_defined(name) {
           // add function definition
-          self.intp.funcs.borrow_mut().insert(name.clone(), func);
+          self.intp.funcs.borrow_mut().insert(*name, func);
+          // purity of functions may be changed
+          self.intp.memo.clear();
+          self.intp.pure_funcs = None;
           Ok(())
         } else {
           Err("function has already been defined")
         }
       }
-      _ => panic!("not a function"),
+      // skip top-level comments kept by the lexer
+      Ast::Comment { .. } => Ok(()),
+      _ => Err("expected function definition"),
+    }
+  }
+
+  /// Evaluates the specific function definition, statement or expression
+  /// in the global environment, returns the value if it is an expression.
+  pub fn eval_stmt(&mut self, ast: AstBox) -> std::result::Result<Option<i64>, &'static str> {
+    match ast.as_ref() {
+      Ast::FunDef { .. } => self.add_func_def(ast).map(|_| None),
+      Ast::Block { .. }
+      | Ast::Define { .. }
+      | Ast::Assign { .. }
+      | Ast::If { .. }
+      | Ast::Switch { .. }
+      | Ast::DoWhile { .. }
+      | Ast::Comment { .. } => {
+        self.intp.visit(&ast).map(|_| None)
+      }
+      Ast::Return { .. } => Err("'return' outside of function"),
+      _ => self.intp.visit(&ast).map(Some),
     }
   }
 
   /// Evaluates the current program.
   pub fn eval(&mut self) -> Result {
     // find & evaluate the `main` function
-    match self.intp.funcs.clone().borrow().get("main") {
+    match self.intp.funcs.clone().borrow().get(&Symbol::new("main")) {
       Some(main) => self.intp.visit(main),
       _ => Err("'main' function not found"),
     }
   }
 }
 
+impl Default for Interpreter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 /// Implementation of the interpreter.
 struct InterpreterImpl {
   /// All function definitions.
-  funcs: Rc<RefCell<HashMap<String, AstBox>>>,
+  funcs: Rc<RefCell<HashMap<Symbol, AstBox>>>,
+  /// All native functions.
+  natives: HashMap<Symbol, NativeFn>,
   /// Environments.
-  envs: NestedMap<String, i32>,
+  envs: NestedMap<Symbol, i64>,
+  /// Current depth of function calls.
+  depth: usize,
+  /// Maximum depth of function calls.
+  max_depth: usize,
+  /// Remaining gas, `None` for unlimited.
+  gas: Option<usize>,
+  /// Overflow handling mode of integer arithmetic.
+  overflow_mode: OverflowMode,
+  /// Whether integers are 64-bit.
+  int64: bool,
+  /// Whether the trace mode is enabled.
+  trace: bool,
+  /// Writer of the trace mode.
+  trace_writer: Box<dyn Write>,
+  /// Whether to memoize pure functions.
+  memoize: bool,
+  /// Cached results of calls to pure functions.
+  memo: HashMap<(Symbol, Vec<i64>), i64>,
+  /// Names of all pure functions, `None` if not analyzed yet.
+  pure_funcs: Option<HashSet<Symbol>>,
+  /// Number of evaluated function calls.
+  call_count: usize,
+  /// Control flow state of the statement being evaluated.
+  flow: Flow,
+}
+
+/// Control flow states of the interpreter.
+#[derive(Clone, Copy, PartialEq)]
+enum Flow {
+  /// Evaluates the next statement.
+  Normal,
+  /// Skips the rest statements of the current function,
+  /// the return value has been stored in `$ret`.
+  Return,
 }
 
 lazy_static! {
   /// Name of return value when evaluating.
-  static ref RET_VAL: String = "$ret".to_string();
+  static ref RET_VAL: Symbol = Symbol::new("$ret");
 }
 
 impl InterpreterImpl {
+  /// Logs the specific visited node if the trace mode is enabled,
+  /// indented by the current depth of function calls.
+  fn trace(&mut self, node: Arguments) -> std::result::Result<(), &'static str> {
+    if self.trace {
+      let indent = " ".repeat(self.depth * 2);
+      writeln!(self.trace_writer, "{}{}", indent, node).map_err(|_| "failed to write trace")?;
+    }
+    Ok(())
+  }
+
+  /// Checks if the specific function is pure, analyzes all
+  /// function definitions if they have not been analyzed.
+  fn is_pure(&mut self, name: &Symbol) -> bool {
+    let funcs = &self.funcs;
+    self
+      .pure_funcs
+      .get_or_insert_with(|| {
+        let program: Program = funcs.borrow().values().cloned().collect();
+        PurityAnalyzer::new().analyze(&program)
+      })
+      .contains(name)
+  }
+
+  /// Consumes one unit of gas, returns an error if the gas is used up.
+  fn consume_gas(&mut self) -> std::result::Result<(), &'static str> {
+    match &mut self.gas {
+      Some(0) => Err("out of gas"),
+      Some(gas) => {
+        *gas -= 1;
+        Ok(())
+      }
+      None => Ok(()),
+    }
+  }
+
+  /// Performs the specific binary operation on integers of the current type.
+  fn eval_binary(&self, op: &Operator, lhs: i64, rhs: i64) -> Result {
+    if self.int64 {
+      eval_binary(op, lhs, rhs, self.overflow_mode)
+    } else {
+      eval_binary(op, lhs as i32, rhs as i32, self.overflow_mode).map(i64::from)
+    }
+  }
+
+  /// Performs the specific unary operation on integers of the current type.
+  fn eval_unary(&self, op: &Operator, opr: i64) -> Result {
+    if self.int64 {
+      eval_unary(op, opr, self.overflow_mode)
+    } else {
+      eval_unary(op, opr as i32, self.overflow_mode).map(i64::from)
+    }
+  }
+
+  /// Checks if the specific function has been defined,
+  /// including library functions and native functions.
+  fn is_defined(&self, name: &Symbol) -> bool {
+    matches!(name.as_str(), "input" | "print")
+      || self.natives.contains_key(name)
+      || self.funcs.borrow().contains_key(name)
+  }
+
   /// Performs library function call.
   fn call_lib_func(
     &mut self,
-    name: &str,
+    name: &Symbol,
     args: &[AstBox],
-  ) -> std::result::Result<Option<i32>, &'static str> {
-    match name {
+  ) -> std::result::Result<Option<i64>, &'static str> {
+    match name.as_str() {
       "input" => {
         // check arguments
         if !args.is_empty() {
@@ -85,10 +323,14 @@ impl InterpreterImpl {
           std::io::stdin()
             .read_line(&mut line)
             .expect("failed to read from stdin");
-          match line.trim().parse::<i32>() {
-            Ok(ret) => Ok(Some(ret)),
-            _ => Err("invalid input, expected integer"),
-          }
+          let ret = if self.int64 {
+            line.trim().parse::<i64>()
+          } else {
+            line.trim().parse::<i32>().map(i64::from)
+          };
+          ret
+            .map(Some)
+            .map_err(|_| "invalid input, expected integer")
         }
       }
       "print" => {
@@ -112,71 +354,130 @@ impl InterpreterImpl {
 impl AstVisitor for InterpreterImpl {
   type Result = Result;
 
-  fn visit_fundef(&mut self, _name: &String, _args: &[String], body: &AstBox) -> Self::Result {
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    _args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.trace(format_args!("FunDef {}", name))?;
     // set up the default return value
-    let ret = self.envs.add(RET_VAL.clone(), 0);
+    let ret = self.envs.add(*RET_VAL, 0);
     debug_assert!(ret, "environment corrupted");
     // evaluate function body
-    self.visit(body)?;
+    let ret = self.visit(body);
+    self.flow = Flow::Normal;
+    ret?;
     // get return value
     Ok(*self.envs.get(&RET_VAL, false).unwrap())
   }
 
   fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    self.trace(format_args!("Block"))?;
     // enter a new environment
     self.envs.push();
     // evaluate all statements
+    let mut ret = Ok(0);
     for stmt in stmts {
-      self.visit(stmt)?;
+      ret = self.consume_gas().and_then(|_| self.visit(stmt));
+      // skip the rest statements after errors or returning
+      if ret.is_err() || self.flow != Flow::Normal {
+        break;
+      }
     }
-    // exit the current environment
+    // exit the current environment, even if failed
     self.envs.pop();
-    Ok(0)
+    ret.map(|_| 0)
   }
 
-  fn visit_define(&mut self, name: &String, expr: &AstBox) -> Self::Result {
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    self.trace(format_args!("Define {}", name))?;
     // evaluate the expression
     let expr = self.visit(expr)?;
     // update the current environment
-    if self.envs.add(name.clone(), expr) {
+    if self.envs.add(*name, expr) {
       Ok(0)
     } else {
       Err("symbol has already been defined")
     }
   }
 
-  fn visit_assign(&mut self, name: &String, expr: &AstBox) -> Self::Result {
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    self.trace(format_args!("Assign {}", name))?;
     // evaluate the expression
     let expr = self.visit(expr)?;
-    // update value of the symbol
+    // update value of the symbol, yields the assigned value
     self
       .envs
-      .update_until(name, expr, |map| map.contains_key::<String>(&RET_VAL))
-      .then(|| 0)
+      .update_until(name, expr, |map| map.contains_key(&RET_VAL))
+      .then_some(expr)
       .ok_or("symbol has not been defined")
   }
 
   fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.trace(format_args!("If"))?;
     // evaluate the condition
     let cond = self.visit(cond)?;
     // evaluate true/false branch
     if cond != 0 {
       self.visit(then)
     } else {
-      else_then.as_ref().map_or(Ok(0), |ast| self.visit(&ast))
+      else_then.as_ref().map_or(Ok(0), |ast| self.visit(ast))
     }
   }
 
-  fn visit_return(&mut self, expr: &AstBox) -> Self::Result {
-    // evaluate the return value
-    let expr = self.visit(expr)?;
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.trace(format_args!("Switch"))?;
+    // case values must fit in the current integer type, like literals
+    if !self.int64 && cases.iter().any(|(v, _)| i32::try_from(*v).is_err()) {
+      return Err("integer literal too large");
+    }
+    // evaluate the scrutinee
+    let val = self.visit(scrutinee)?;
+    // evaluate the matched case or the default case
+    match cases.iter().find(|(v, _)| *v == val) {
+      Some((_, body)) => self.visit(body),
+      None => default.as_ref().map_or(Ok(0), |ast| self.visit(ast)),
+    }
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.trace(format_args!("DoWhile"))?;
+    loop {
+      // each iteration consumes gas, even if the body is empty
+      self.consume_gas()?;
+      self.visit(body)?;
+      if self.flow != Flow::Normal || self.visit(cond)? == 0 {
+        break;
+      }
+    }
+    Ok(0)
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    self.trace(format_args!("Return"))?;
+    // evaluate the return value, 0 is the unit value
+    let expr = expr.as_ref().map_or(Ok(0), |expr| self.visit(expr))?;
     // update the current return value
-    let succ = self.envs.update_rec(&RET_VAL, expr);
-    debug_assert!(succ, "environment corrupted");
+    self
+      .envs
+      .update_rec(&RET_VAL, expr)
+      .then_some(0)
+      .ok_or("'return' outside of function")?;
+    self.flow = Flow::Return;
     Ok(0)
   }
 
   fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    self.trace(format_args!("Binary {}", op))?;
     // check if is logical operator
     match *op {
       Operator::LAnd | Operator::LOr => {
@@ -194,75 +495,495 @@ impl AstVisitor for InterpreterImpl {
         let lhs = self.visit(lhs)?;
         let rhs = self.visit(rhs)?;
         // perform binary operation
-        Ok(match *op {
-          Operator::Add => lhs + rhs,
-          Operator::Sub => lhs - rhs,
-          Operator::Mul => lhs * rhs,
-          Operator::Div => lhs / rhs,
-          Operator::Mod => lhs % rhs,
-          Operator::Less => (lhs < rhs) as i32,
-          Operator::LessEq => (lhs <= rhs) as i32,
-          Operator::Eq => (lhs == rhs) as i32,
-          Operator::NotEq => (lhs != rhs) as i32,
-          _ => panic!("unknown binary operator"),
-        })
+        self.eval_binary(op, lhs, rhs)
       }
     }
   }
 
   fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    self.trace(format_args!("Unary {}", op))?;
     // evaluate the operand
     let opr = self.visit(opr)?;
     // perform unary operation
-    Ok(match *op {
-      Operator::Sub => -opr,
-      Operator::LNot => !opr,
-      _ => panic!("invalid unary operator"),
-    })
+    self.eval_unary(op, opr)
   }
 
-  fn visit_funcall(&mut self, name: &String, args: &[AstBox]) -> Self::Result {
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    self.trace(format_args!("FunCall {}", name))?;
     // handle library function call
     if let Some(ret) = self.call_lib_func(name, args)? {
       return Ok(ret);
     }
+    // handle native function call
+    if self.natives.contains_key(name) {
+      let args = args
+        .iter()
+        .map(|arg| self.visit(arg))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+      let ret = self.natives[name](&args)?;
+      return Ok(if self.int64 { ret } else { ret as i32 as i64 });
+    }
     // find the specific function
     match self.funcs.clone().borrow().get(name) {
       Some(func) => {
-        // make a new environment for arguments
-        self.envs.push();
         // evaluate arguments
-        let (_, arg_names, _) = unwrap_struct!(func.as_ref(), Ast::FunDef, name, args, body);
+        let (_, arg_names, _, _, _) =
+          unwrap_struct!(func.as_ref(), Ast::FunDef, name, args, arg_types, ret_type, body);
         if arg_names.len() != args.len() {
           return Err("argument count mismatch");
         }
+        let args = args
+          .iter()
+          .map(|arg| self.visit(arg))
+          .collect::<std::result::Result<Vec<_>, _>>()?;
+        // find the cached result
+        let memoize = self.memoize && self.is_pure(name);
+        if memoize {
+          if let Some(ret) = self.memo.get(&(*name, args.clone())) {
+            return Ok(*ret);
+          }
+        }
+        // check the depth of function calls
+        if self.depth >= self.max_depth {
+          return Err("maximum recursion depth exceeded");
+        }
+        // make a new environment for arguments
+        self.envs.push();
         for (arg, name) in args.iter().zip(arg_names.iter()) {
-          // evaluate the current arguments
-          let arg = self.visit(arg)?;
           // add to the current environment
-          if !self.envs.add(name.clone(), arg) {
+          if !self.envs.add(*name, *arg) {
+            self.envs.pop();
             return Err("redifinition of argument");
           }
         }
         // call the specific function
+        self.call_count += 1;
+        self.depth += 1;
         let ret = self.visit(func);
+        self.depth -= 1;
         // exit the current environment
         self.envs.pop();
+        if let (true, Ok(ret)) = (memoize, ret) {
+          self.memo.insert((*name, args), ret);
+        }
         ret
       }
       None => Err("function not found"),
     }
   }
 
-  fn visit_int(&mut self, val: &i32) -> Self::Result {
-    Ok(*val)
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    self.trace(format_args!("Int {}", val))?;
+    if self.int64 || i32::try_from(*val).is_ok() {
+      Ok(*val)
+    } else {
+      Err("integer literal too large")
+    }
   }
 
-  fn visit_id(&mut self, val: &String) -> Self::Result {
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    self.trace(format_args!("Id {}", val))?;
     // find in environment
     self
       .envs
       .get_rec(val)
       .map_or(Err("symbol has not been defined"), |v| Ok(*v))
   }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {
+    Ok(0)
+  }
+}
+
+/// Unit tests for `Interpreter`.
+#[cfg(test)]
+mod test {
+  use super::Interpreter;
+  use std::cell::RefCell;
+  use std::io::Write;
+  use std::rc::Rc;
+  use crate::define::{Ast, AstBox, AstBuilder, OverflowMode};
+  use crate::front::{lexer::Lexer, parser::Parser};
+
+  /// Evaluates the specific program.
+  fn eval(intp: &mut Interpreter, program: &str) -> super::Result {
+    let mut parser = Parser::from_str(program);
+    while let Ok(ast) = parser.parse_next() {
+      intp.add_func_def(ast)?;
+    }
+    intp.eval()
+  }
+
+  #[test]
+  fn test_max_depth() {
+    let program = r#"
+      f(n) {
+        return f(n + 1)
+      }
+      main() {
+        return f(0)
+      }
+    "#;
+    let mut intp = Interpreter::new();
+    intp.set_max_depth(100);
+    assert_eq!(eval(&mut intp, program), Err("maximum recursion depth exceeded"));
+    let program = r#"
+      sum(n) {
+        if n == 0 {
+          return 0
+        } else {
+          return n + sum(n - 1)
+        }
+      }
+      main() {
+        return sum(100)
+      }
+    "#;
+    let mut intp = Interpreter::new();
+    intp.set_max_depth(101);
+    assert_eq!(eval(&mut intp, program), Ok(5050));
+  }
+
+  #[test]
+  fn test_default_max_depth() {
+    // test threads have the default stack size of spawned threads
+    let program = r#"
+      f(n) {
+        return 1 + (1 + f(n + 1))
+      }
+      main() {
+        return f(0)
+      }
+    "#;
+    let mut intp = Interpreter::new();
+    assert_eq!(eval(&mut intp, program), Err("maximum recursion depth exceeded"));
+  }
+
+  /// Evaluates the specific program under the specific overflow mode.
+  fn eval_overflow(mode: OverflowMode, program: &str) -> super::Result {
+    let mut intp = Interpreter::new();
+    intp.set_overflow_mode(mode);
+    eval(&mut intp, program)
+  }
+
+  #[test]
+  fn test_overflow_mode() {
+    let add = "main() { return 2147483647 + 1 }";
+    let neg = "main() { x := 0 - 2147483647 - 1 return -x }";
+    assert_eq!(eval_overflow(OverflowMode::Wrapping, add), Ok(i32::MIN.into()));
+    assert_eq!(eval_overflow(OverflowMode::Wrapping, neg), Ok(i32::MIN.into()));
+    assert_eq!(eval_overflow(OverflowMode::Checked, add), Err("integer overflow"));
+    assert_eq!(eval_overflow(OverflowMode::Checked, neg), Err("integer overflow"));
+    let no_overflow = "main() { return 2147483646 + 1 }";
+    assert_eq!(eval_overflow(OverflowMode::Checked, no_overflow), Ok(i32::MAX.into()));
+    assert_eq!(eval_overflow(OverflowMode::Panicking, no_overflow), Ok(i32::MAX.into()));
+  }
+
+  #[test]
+  fn test_comments() {
+    let program = r#"
+      # computes the answer
+      ## returns the answer
+      f() {
+        return 42 # the answer
+      }
+      main() { return f() }
+      # end of file
+    "#;
+    let mut lexer = Lexer::from_str(program);
+    lexer.set_keep_comments(true);
+    let asts = Parser::new(lexer).parse_program().unwrap();
+    assert!(matches!(asts[0].as_ref(), Ast::Comment { .. }));
+    let mut intp = Interpreter::new();
+    for ast in asts {
+      intp.add_func_def(ast).unwrap();
+    }
+    assert_eq!(intp.eval(), Ok(42));
+    let b = AstBuilder::new();
+    assert_eq!(intp.add_func_def(b.ret_void()), Err("expected function definition"));
+  }
+
+  #[test]
+  fn test_int64() {
+    /// Evaluates the specific program in the 64-bit mode.
+    fn eval64(mode: OverflowMode, program: &str) -> super::Result {
+      let mut lexer = Lexer::from_str(program);
+      lexer.set_int64(true);
+      let mut intp = Interpreter::new();
+      intp.set_int64(true);
+      intp.set_overflow_mode(mode);
+      for ast in Parser::new(lexer) {
+        intp.add_func_def(ast.unwrap())?;
+      }
+      intp.eval()
+    }
+    let big = "main() { x := 3000000000 return x * 2 + 1 }";
+    assert_eq!(eval64(OverflowMode::Wrapping, big), Ok(6000000001));
+    let overflow = "main() { return 2147483647 + 1 }";
+    assert_eq!(eval64(OverflowMode::Wrapping, overflow), Ok(2147483648));
+    // integers are 32-bit by default
+    let err = Parser::from_str(big).parse_next().err().unwrap().to_string();
+    assert_eq!(err, "integer literal too large at line 1, col 15");
+    let b = AstBuilder::new();
+    let mut intp = Interpreter::new();
+    let main = b.fundef("main", Vec::new(), b.block(vec![b.ret(b.int(3000000000))]));
+    intp.add_func_def(main).unwrap();
+    assert_eq!(intp.eval(), Err("integer literal too large"));
+    assert_eq!(eval(&mut Interpreter::new(), overflow), Ok(i32::MIN.into()));
+    let pow = "main() { return 2 ** 63 }";
+    assert_eq!(eval64(OverflowMode::Checked, pow), Err("integer overflow"));
+    // 64-bit constants and case values
+    let switch = r#"
+      const BIG := 3000000000
+      main() {
+        switch BIG * 2 {
+          case 6000000000: return 1
+        }
+        return 0
+      }
+    "#;
+    assert_eq!(eval64(OverflowMode::Wrapping, switch), Ok(1));
+    // case values must fit in the current integer type
+    let scrutinee = b.int(0);
+    let cases = vec![(3000000000, b.block(Vec::new()))];
+    let switch = Box::new(Ast::Switch { scrutinee, cases, default: None });
+    let mut intp = Interpreter::new();
+    intp.add_func_def(b.fundef("main", Vec::new(), b.block(vec![switch]))).unwrap();
+    assert_eq!(intp.eval(), Err("integer literal too large"));
+  }
+
+  #[test]
+  fn test_pow() {
+    let mut intp = Interpreter::new();
+    assert_eq!(eval(&mut intp, "main() { return 2 ** 3 ** 2 }"), Ok(512));
+    let mut intp = Interpreter::new();
+    assert_eq!(eval(&mut intp, "main() { x := 0 return -3 ** 3 + x ** 0 }"), Ok(-26));
+    let mut intp = Interpreter::new();
+    let neg = "main() { x := 0 - 1 return 2 ** x }";
+    assert_eq!(eval(&mut intp, neg), Err("negative exponent"));
+    let overflow = "main() { return 2 ** 31 }";
+    assert_eq!(eval_overflow(OverflowMode::Wrapping, overflow), Ok(i32::MIN.into()));
+    assert_eq!(eval_overflow(OverflowMode::Checked, overflow), Err("integer overflow"));
+  }
+
+  #[test]
+  #[should_panic(expected = "integer overflow")]
+  fn test_overflow_panicking() {
+    let _ = eval_overflow(OverflowMode::Panicking, "main() { return 2147483647 + 1 }");
+  }
+
+  #[test]
+  fn test_switch() {
+    let program = r#"
+      f(x) {
+        y := 0
+        switch x * 2 {
+          case 2: y = 10
+          case -4: y = 20 y = y + 1
+          default: y = x
+        }
+        return y
+      }
+      main() {
+        return f(1) * 10000 + f(-2) * 100 + f(3)
+      }
+    "#;
+    assert_eq!(eval(&mut Interpreter::new(), program), Ok(102103));
+    // switch statements without the default case
+    let program = "main() { x := 1 switch 5 { case 1: x = 2 } return x }";
+    assert_eq!(eval(&mut Interpreter::new(), program), Ok(1));
+  }
+
+  #[test]
+  fn test_void_return() {
+    let program = "f(x) { print(x) return } main() { y := f(5) return y + 1 }";
+    assert_eq!(eval(&mut Interpreter::new(), program), Ok(1));
+  }
+
+  #[test]
+  fn test_early_return() {
+    let program = r#"
+      f(x) {
+        y := 1
+        if x {
+          if x - 1 {
+            return x * 10
+          }
+          y = 2
+          return y
+        }
+        y = 3
+        return y
+      }
+      main() {
+        return f(0) * 100 + f(1) * 10 + f(5)
+      }
+    "#;
+    assert_eq!(eval(&mut Interpreter::new(), program), Ok(370));
+    // statements after return statements are not evaluated
+    let program = "main() { if 1 { return 1 } print(input()) return 0 }";
+    let mut intp = Interpreter::new();
+    intp.set_gas_limit(Some(2));
+    assert_eq!(eval(&mut intp, program), Ok(1));
+  }
+
+  #[test]
+  fn test_do_while() {
+    let program = r#"
+      f(n) {
+        i := 0
+        s := 0
+        do {
+          i = i + 1
+          s = s + i
+        } while i < n
+        return s
+      }
+      main() {
+        return f(10) * 10 + f(0)
+      }
+    "#;
+    // the body is evaluated once even if the condition is false
+    assert_eq!(eval(&mut Interpreter::new(), program), Ok(551));
+    // return statements in the body stop the loop
+    let program = "main() { i := 0 do { i = i + 1 if i == 3 { return i } } while 1 }";
+    assert_eq!(eval(&mut Interpreter::new(), program), Ok(3));
+    // empty loops consume gas
+    let mut intp = Interpreter::new();
+    intp.set_gas_limit(Some(100));
+    assert_eq!(eval(&mut intp, "main() { do {} while 1 }"), Err("out of gas"));
+  }
+
+  #[test]
+  fn test_assign_expr() {
+    let program = "main() { a := 0 b := 0 a = b = 3 return a * 10 + b + (a = 1) }";
+    let mut parser = Parser::from_str(program);
+    parser.set_assign_expr(true);
+    let mut intp = Interpreter::new();
+    intp.add_func_def(parser.parse_next().unwrap()).unwrap();
+    assert_eq!(intp.eval(), Ok(34));
+  }
+
+  #[test]
+  fn test_gas() {
+    let program = "f(n) { x := n + 1 return f(x) } main() { return f(0) }";
+    let mut intp = Interpreter::new();
+    intp.set_gas_limit(Some(100));
+    assert_eq!(eval(&mut intp, program), Err("out of gas"));
+    // the gas is consumed by statements
+    let program = "main() { x := 1 if x { x = 2 } return x }";
+    let mut intp = Interpreter::new();
+    intp.set_gas_limit(Some(4));
+    assert_eq!(eval(&mut intp, program), Ok(2));
+    let mut intp = Interpreter::new();
+    intp.set_gas_limit(Some(3));
+    assert_eq!(eval(&mut intp, program), Err("out of gas"));
+    let mut intp = Interpreter::new();
+    intp.set_gas_limit(None);
+    assert_eq!(eval(&mut intp, program), Ok(2));
+  }
+
+  #[test]
+  fn test_trace() {
+    /// Writer which shares its output with the test.
+    #[derive(Clone, Default)]
+    struct Log(Rc<RefCell<Vec<u8>>>);
+    impl Write for Log {
+      fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+      }
+      fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+      }
+    }
+    let log = Log::default();
+    let mut intp = Interpreter::new();
+    intp.set_trace(true);
+    intp.set_trace_writer(log.clone());
+    assert_eq!(eval(&mut intp, "f(x) { return -x } main() { return f(1) + 2 }"), Ok(1));
+    let log = String::from_utf8(log.0.take()).unwrap();
+    let expected = r#"FunDef main
+Block
+Return
+Binary +
+FunCall f
+Int 1
+  FunDef f
+  Block
+  Return
+  Unary -
+  Id x
+Int 2
+"#;
+    assert_eq!(log, expected);
+    // nothing is logged if the trace mode is disabled
+    let log = Log::default();
+    let mut intp = Interpreter::new();
+    intp.set_trace_writer(log.clone());
+    assert_eq!(eval(&mut intp, "main() { return 1 + 2 }"), Ok(3));
+    assert!(log.0.borrow().is_empty());
+  }
+
+  #[test]
+  fn test_memoize() {
+    let fib = r#"
+      fib(n) { if n < 2 { return n } else { return fib(n - 1) + fib(n - 2) } }
+      main() { return fib(20) }
+    "#;
+    let mut intp = Interpreter::new();
+    assert_eq!(eval(&mut intp, fib), Ok(6765));
+    assert_eq!(intp.call_count(), 21891);
+    // each subproblem is evaluated only once
+    let mut intp = Interpreter::new();
+    intp.set_memoize(true);
+    assert_eq!(eval(&mut intp, fib), Ok(6765));
+    assert_eq!(intp.call_count(), 21);
+    // impure functions are not memoized
+    let program = "f(x) { print(x) return x } main() { return f(1) + f(1) }";
+    let mut intp = Interpreter::new();
+    intp.set_memoize(true);
+    assert_eq!(eval(&mut intp, program), Ok(2));
+    assert_eq!(intp.call_count(), 2);
+  }
+
+  #[test]
+  fn test_native() {
+    let mut intp = Interpreter::new();
+    let max = |args: &[i64]| match args {
+      [a, b] => Ok(*a.max(b)),
+      _ => Err("argument count mismatch"),
+    };
+    assert_eq!(intp.register_native("max", Box::new(max)), Ok(()));
+    let program = "main() { x := max(1, 5) return max(x, 3) + max(-x, 0) }";
+    assert_eq!(eval(&mut intp, program), Ok(5));
+    assert_eq!(intp.eval_stmt(parse_expr("max(1)")), Err("argument count mismatch"));
+    // conflict with other functions
+    let mut intp = Interpreter::new();
+    assert_eq!(eval(&mut intp, "max(a, b) { return a } main() {}"), Ok(0));
+    let err = intp.register_native("max", Box::new(max));
+    assert_eq!(err, Err("function has already been defined"));
+    let err = intp.register_native("print", Box::new(max));
+    assert_eq!(err, Err("function has already been defined"));
+    let mut intp = Interpreter::new();
+    intp.register_native("max", Box::new(max)).unwrap();
+    assert_eq!(eval(&mut intp, "max(a, b) { return a }"), Err("function has already been defined"));
+  }
+
+  /// Parses the specific expression.
+  fn parse_expr(expr: &str) -> AstBox {
+    Parser::from_str(expr).parse_next_expr().unwrap()
+  }
+
+  #[test]
+  fn test_division_by_zero() {
+    let mut intp = Interpreter::new();
+    let div = "main() { x := 0 return 10 / x }";
+    assert_eq!(eval(&mut intp, div), Err("division by zero"));
+    let mut intp = Interpreter::new();
+    let rem = "main() { x := 0 return 10 % x }";
+    assert_eq!(eval(&mut intp, rem), Err("division by zero"));
+    let mut intp = Interpreter::new();
+    let div = "main() { x := 3 return 10 / x + 10 % x }";
+    assert_eq!(eval(&mut intp, div), Ok(4));
+  }
 }
@@ -1,2 +1,3 @@
 pub mod interpreter;
 pub mod compiler;
+pub mod prelude;
@@ -0,0 +1,100 @@
+use super::interpreter::Result;
+use std::convert::TryFrom;
+
+/// Native function of the prelude.
+type PreludeFn = fn(&[i64]) -> Result;
+
+/// Names and implementations of all functions in the prelude.
+///
+/// All functions wrap around on overflow, like the default
+/// overflow handling mode of the interpreter.
+pub const FUNCTIONS: &[(&str, PreludeFn)] = &[
+  ("abs", abs),
+  ("min", min),
+  ("max", max),
+  ("pow", pow),
+  ("gcd", gcd),
+];
+
+/// Returns the absolute value of `x`.
+fn abs(args: &[i64]) -> Result {
+  match args {
+    [x] => Ok(x.wrapping_abs()),
+    _ => Err("argument count mismatch"),
+  }
+}
+
+/// Returns the minimum of `a` and `b`.
+fn min(args: &[i64]) -> Result {
+  match args {
+    [a, b] => Ok(*a.min(b)),
+    _ => Err("argument count mismatch"),
+  }
+}
+
+/// Returns the maximum of `a` and `b`.
+fn max(args: &[i64]) -> Result {
+  match args {
+    [a, b] => Ok(*a.max(b)),
+    _ => Err("argument count mismatch"),
+  }
+}
+
+/// Returns `base` raised to the power of `exp`.
+fn pow(args: &[i64]) -> Result {
+  match args {
+    [_, exp] if *exp < 0 => Err("negative exponent"),
+    [base, exp] => match u32::try_from(*exp) {
+      Ok(exp) => Ok(base.wrapping_pow(exp)),
+      Err(_) => Err("exponent too large"),
+    },
+    _ => Err("argument count mismatch"),
+  }
+}
+
+/// Returns the non-negative greatest common divisor of `a` and `b`.
+fn gcd(args: &[i64]) -> Result {
+  match args {
+    [a, b] => {
+      let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+      while b != 0 {
+        (a, b) = (b, a % b);
+      }
+      Ok(a as i64)
+    }
+    _ => Err("argument count mismatch"),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::back::interpreter::{Interpreter, Result};
+  use crate::front::parser::Parser;
+
+  /// Evaluates the specific expression with the prelude.
+  fn eval(expr: &str) -> Result {
+    let mut intp = Interpreter::new();
+    intp.register_prelude().unwrap();
+    let program = format!("main() {{ return {} }}", expr);
+    for ast in Parser::from_str(&program) {
+      intp.add_func_def(ast.unwrap())?;
+    }
+    intp.eval()
+  }
+
+  #[test]
+  fn test_prelude() {
+    assert_eq!(eval("abs(-3)"), Ok(3));
+    assert_eq!(eval("abs(3) + abs(0)"), Ok(3));
+    assert_eq!(eval("min(2, -7) * max(2, -7)"), Ok(-14));
+    assert_eq!(eval("pow(-2, 5) + pow(7, 0)"), Ok(-31));
+    assert_eq!(eval("pow(2, -1)"), Err("negative exponent"));
+    assert_eq!(eval("gcd(12, 18) + gcd(-4, 6) + gcd(0, 0)"), Ok(8));
+    assert_eq!(eval("gcd(7, 0) * gcd(0, -7)"), Ok(49));
+    assert_eq!(eval("max(1)"), Err("argument count mismatch"));
+    assert_eq!(eval("abs(1, 2)"), Err("argument count mismatch"));
+    // user functions can not redefine functions in the prelude
+    let err = eval("0 } abs(x) { return x");
+    assert_eq!(err, Err("function has already been defined"));
+  }
+}
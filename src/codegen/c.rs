@@ -0,0 +1,295 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Symbol, Type};
+
+/// Number of spaces of each indentation level.
+const INDENT_WIDTH: usize = 2;
+
+/// Definitions of library functions.
+const PRELUDE: &str = r#"#include <stdio.h>
+
+static int input(void) {
+  int val = 0;
+  scanf("%d", &val);
+  return val;
+}
+
+static int print(int val) {
+  printf("%d\n", val);
+  return 0;
+}
+
+static int _pow(int lhs, int rhs) {
+  int val = 1;
+  for (; rhs > 0; --rhs) val *= lhs;
+  return val;
+}
+"#;
+
+/// C emitter, translates programs into C source code.
+///
+/// Logical operators of `first-step` yield one of their operands rather
+/// than `0`/`1`, so they are emitted as conditional expressions, using
+/// temporary variables named `_t0`, `_t1`, ... for `||`.
+/// Power operations are emitted as calls to the library function `_pow`.
+#[derive(Default)]
+pub struct CEmitter {
+  /// Current indentation level.
+  indent: usize,
+  /// Number of temporary variables of the current function.
+  temp_num: usize,
+}
+
+impl CEmitter {
+  /// Creates a new C emitter.
+  pub fn new() -> Self {
+    Self {
+      indent: 0,
+      temp_num: 0,
+    }
+  }
+
+  /// Emits C source code of the specific program (list of function definitions).
+  pub fn emit(&mut self, asts: &[AstBox]) -> String {
+    let mut text = PRELUDE.to_string();
+    // forward declarations
+    text += "\n";
+    for ast in asts {
+      if let Ast::FunDef { name, args, .. } = ast.as_ref() {
+        text += &format!("{};\n", signature(name, args));
+      }
+    }
+    // definitions
+    for ast in asts {
+      text += &format!("\n{}\n", self.visit(ast));
+    }
+    text
+  }
+
+  /// Gets the indentation of the current level.
+  fn indentation(&self) -> String {
+    " ".repeat(self.indent * INDENT_WIDTH)
+  }
+
+  /// Emits statements at the current indentation level, one per line.
+  fn emit_stmts(&mut self, stmts: &[AstBox]) -> String {
+    let mut text = String::new();
+    for stmt in stmts {
+      let stmt_text = self.visit(stmt);
+      text += &self.indentation();
+      text += &stmt_text;
+      if !matches!(
+        stmt.as_ref(),
+        Ast::If { .. } | Ast::Switch { .. } | Ast::Block { .. } | Ast::Comment { .. }
+      ) {
+        text += ";";
+      }
+      text += "\n";
+    }
+    text
+  }
+
+  /// Emits an operand of an expression, adds parentheses if necessary.
+  fn emit_operand(&mut self, ast: &AstBox) -> String {
+    let text = self.visit(ast);
+    match ast.as_ref() {
+      Ast::Binary { .. } | Ast::Unary { .. } | Ast::Assign { .. } => format!("({})", text),
+      _ => text,
+    }
+  }
+}
+
+impl AstVisitor for CEmitter {
+  type Result = String;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    let stmts = match body.as_ref() {
+      Ast::Block { stmts } => stmts,
+      _ => panic!("function body must be a block"),
+    };
+    self.temp_num = 0;
+    self.indent = 1;
+    // parameters can be shadowed in the function body in `first-step`,
+    // but not in the outermost block of a C function
+    let shadowed = stmts
+      .iter()
+      .any(|stmt| matches!(stmt.as_ref(), Ast::Define { name, .. } if args.contains(name)));
+    let body = if shadowed {
+      format!("{}{}\n", self.indentation(), self.visit(body))
+    } else {
+      self.emit_stmts(stmts)
+    };
+    self.indent = 0;
+    let mut text = format!("{} {{\n", signature(name, args));
+    if self.temp_num > 0 {
+      let temps: Vec<_> = (0..self.temp_num).map(|i| format!("_t{}", i)).collect();
+      text += &format!("  int {};\n", temps.join(", "));
+    }
+    text += &body;
+    // functions return 0 if they do not return explicitly
+    if !matches!(stmts.last().map(|stmt| stmt.as_ref()), Some(Ast::Return { .. })) {
+      text += "  return 0;\n";
+    }
+    text + "}"
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    if stmts.is_empty() {
+      return "{}".to_string();
+    }
+    let outer = self.indentation();
+    self.indent += 1;
+    let text = format!("{{\n{}{}}}", self.emit_stmts(stmts), outer);
+    self.indent -= 1;
+    text
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    format!("int {} = {}", name, self.visit(expr))
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    format!("{} = {}", name, self.visit(expr))
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    let text = format!("if ({}) {}", self.visit(cond), self.visit(then));
+    match else_then {
+      Some(else_then) => format!("{} else {}", text, self.visit(else_then)),
+      None => text,
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    let mut text = format!("switch ({}) {{\n", self.visit(scrutinee));
+    let outer = self.indentation();
+    self.indent += 1;
+    for (val, body) in cases {
+      text += &format!("{}case {}: {} break;\n", self.indentation(), val, self.visit(body));
+    }
+    if let Some(default) = default {
+      text += &format!("{}default: {}\n", self.indentation(), self.visit(default));
+    }
+    self.indent -= 1;
+    text + &outer + "}"
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    format!("do {} while ({})", self.visit(body), self.visit(cond))
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    match expr {
+      Some(expr) => format!("return {}", self.visit(expr)),
+      None => "return 0".to_string(),
+    }
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    let lhs = self.emit_operand(lhs);
+    let rhs = self.emit_operand(rhs);
+    match op {
+      Operator::LAnd => format!("{} ? {} : 0", lhs, rhs),
+      Operator::LOr => {
+        let temp = format!("_t{}", self.temp_num);
+        self.temp_num += 1;
+        format!("({} = {}) ? {} : {}", temp, lhs, temp, rhs)
+      }
+      Operator::Pow => format!("_pow({}, {})", lhs, rhs),
+      _ => format!("{} {} {}", lhs, op, rhs),
+    }
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    format!("{}{}", op, self.emit_operand(opr))
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    let args: Vec<_> = args.iter().map(|arg| self.visit(arg)).collect();
+    format!("{}({})", name, args.join(", "))
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    val.to_string()
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    val.to_string()
+  }
+
+  fn visit_comment(&mut self, text: &str, _: &bool) -> Self::Result {
+    format!("//{}", text)
+  }
+}
+
+/// Gets the C signature of the specific function.
+fn signature(name: &Symbol, args: &[Symbol]) -> String {
+  if args.is_empty() {
+    format!("int {}(void)", name)
+  } else {
+    let args: Vec<_> = args.iter().map(|arg| format!("int {}", arg)).collect();
+    format!("int {}({})", name, args.join(", "))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::CEmitter;
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use std::io::Cursor;
+
+  /// Parses the specific program and emits C source code.
+  fn emit(program: &str) -> String {
+    let mut parser = Parser::new(Lexer::new(Cursor::new(program)));
+    CEmitter::new().emit(&parser.parse_program().unwrap())
+  }
+
+  #[test]
+  fn test_c() {
+    let text = emit(
+      r#"
+      func(x) {
+        y := x * (x + 1)
+        if y < 10 || !x { print(y) } else if x { return -y }
+        return x && y
+      }
+      main() { func(input()) }
+    "#,
+    );
+    let expected = r#"int func(int x);
+int main(void);
+
+int func(int x) {
+  int _t0;
+  int y = x * (x + 1);
+  if ((_t0 = (y < 10)) ? _t0 : (!x)) {
+    print(y);
+  } else if (x) {
+    return -y;
+  }
+  return x ? y : 0;
+}
+
+int main(void) {
+  func(input());
+  return 0;
+}
+"#;
+    assert!(text.starts_with("#include <stdio.h>\n"));
+    assert!(text.ends_with(expected));
+    // shadowed parameters
+    let text = emit("f(x) { x := 1 return x }");
+    assert!(text.contains("int f(int x) {\n  {\n    int x = 1;\n    return x;\n  }\n}\n"));
+  }
+}
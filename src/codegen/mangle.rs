@@ -0,0 +1,76 @@
+use crate::define::Symbol;
+
+/// Name mangler of functions, avoids clashes between user functions and
+/// runtime symbols when linking with other code (C runtime for example).
+///
+/// The mangled name of function `name` with `n` parameters is
+/// `{prefix}{name}_{n}`. Library functions (`input` and `print`)
+/// and the entry point (`main`) are not mangled.
+#[derive(Clone, Debug)]
+pub struct Mangler {
+  /// Prefix of mangled names.
+  prefix: String,
+}
+
+impl Mangler {
+  /// Creates a new mangler with the specific prefix.
+  pub fn new(prefix: &str) -> Self {
+    Self {
+      prefix: prefix.to_string(),
+    }
+  }
+
+  /// Mangles the name of the specific function with the specific arity.
+  pub fn mangle(&self, name: &Symbol, arity: usize) -> String {
+    if is_unmangled(name.as_str()) {
+      name.to_string()
+    } else {
+      format!("{}{}_{}", self.prefix, name, arity)
+    }
+  }
+
+  /// Demangles the specific mangled name, returns the original name
+  /// and the arity, or `None` if the name is not mangled by this mangler.
+  pub fn demangle(&self, mangled: &str) -> Option<(Symbol, usize)> {
+    let (name, arity) = mangled.strip_prefix(&self.prefix)?.rsplit_once('_')?;
+    if name.is_empty() || arity.starts_with('+') {
+      return None;
+    }
+    Some((Symbol::new(name), arity.parse().ok()?))
+  }
+}
+
+impl Default for Mangler {
+  fn default() -> Self {
+    Self::new("fs_")
+  }
+}
+
+/// Checks if the specific function should not be mangled.
+fn is_unmangled(name: &str) -> bool {
+  matches!(name, "input" | "print" | "main")
+}
+
+#[cfg(test)]
+mod test {
+  use super::Mangler;
+  use crate::define::Symbol;
+
+  #[test]
+  fn test_mangle() {
+    let mangler = Mangler::default();
+    let names = [("f", 1), ("fs_f_1", 2), ("my_func_2", 0)];
+    for (name, arity) in names {
+      let mangled = mangler.mangle(&Symbol::new(name), arity);
+      assert_eq!(mangled, format!("fs_{}_{}", name, arity));
+      assert_eq!(mangler.demangle(&mangled), Some((Symbol::new(name), arity)));
+    }
+    assert_eq!(mangler.mangle(&Symbol::new("print"), 1), "print");
+    assert_eq!(mangler.mangle(&Symbol::new("main"), 0), "main");
+    // not mangled names
+    assert_eq!(mangler.demangle("main"), None);
+    assert_eq!(mangler.demangle("fs_f"), None);
+    assert_eq!(mangler.demangle("fs__1"), None);
+    assert_eq!(Mangler::new("_").demangle("_gcd_2"), Some((Symbol::new("gcd"), 2)));
+  }
+}
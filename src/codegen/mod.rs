@@ -0,0 +1,4 @@
+pub mod c;
+pub mod mangle;
+pub mod riscv;
+pub mod wasm;
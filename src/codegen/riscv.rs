@@ -0,0 +1,356 @@
+use super::mangle::Mangler;
+use crate::define::{Operator, Symbol};
+use crate::ir::regalloc::{LinearScan, Location};
+use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+use std::collections::HashMap;
+use std::io::{Result, Write};
+
+/// Number of allocatable registers, `s1` to `s11`.
+const REG_NUM: u32 = 11;
+
+/// Register for storing the intermediate result.
+const RESULT_REG: &str = "t0";
+
+/// Register for storing the temporary data.
+const TEMP_REG: &str = "t1";
+
+/// Emitter that generates RISC-V assembly of an IR function.
+/// All virtual registers are allocated to callee-saved registers,
+/// so their values are preserved across function calls.
+pub struct RiscvEmitter<'a> {
+  /// Function to be emitted.
+  func: &'a Function,
+  /// Locations of all virtual registers.
+  locations: HashMap<Reg, Location>,
+  /// Number of stack slots.
+  slot_num: usize,
+  /// Number of saved registers, `s1` to `sN`.
+  saved_num: usize,
+  /// Mangler of function names, `None` if names are not mangled.
+  mangler: Option<Mangler>,
+}
+
+impl<'a> RiscvEmitter<'a> {
+  /// Creates a new emitter and allocates registers for the specific function.
+  pub fn new(func: &'a Function) -> Self {
+    let alloc = LinearScan::new(REG_NUM).allocate(func);
+    let saved_num = alloc
+      .locations
+      .values()
+      .filter_map(|loc| match loc {
+        Location::Reg(reg) => Some(*reg as usize + 1),
+        Location::Slot(_) => None,
+      })
+      .max()
+      .unwrap_or(0);
+    Self {
+      func,
+      locations: alloc.locations,
+      slot_num: alloc.func.slot_num,
+      saved_num,
+      mangler: None,
+    }
+  }
+
+  /// Sets the mangler of function names, names are not mangled by default.
+  pub fn set_mangler(&mut self, mangler: Option<Mangler>) {
+    self.mangler = mangler;
+  }
+
+  /// Dumps RISC-V assembly of the function.
+  pub fn emit(&self, writer: &mut impl Write) -> Result<()> {
+    // dump header
+    writeln!(writer, "  .text")?;
+    let name = self.func_name(&self.func.name, self.func.params.len());
+    writeln!(writer, "  .globl {}", name)?;
+    writeln!(writer, "{}:", name)?;
+    // dump prologue
+    writeln!(writer, "  addi sp, sp, -{}", self.frame_size())?;
+    writeln!(writer, "  sw ra, {}(sp)", self.frame_size() - 4)?;
+    for i in 1..=self.saved_num {
+      writeln!(writer, "  sw s{}, {}(sp)", i, self.saved_offset(i))?;
+    }
+    debug_assert!(self.func.params.len() <= 8, "argument count is greater than 8");
+    for (i, param) in self.func.params.iter().enumerate() {
+      match self.locations[param] {
+        Location::Reg(reg) => writeln!(writer, "  mv s{}, a{}", reg + 1, i)?,
+        Location::Slot(slot) => writeln!(writer, "  sw a{}, {}(sp)", i, slot * 4)?,
+      }
+    }
+    // dump blocks
+    for (i, block) in self.func.blocks.iter().enumerate() {
+      let next = self.func.blocks.get(i + 1).map(|block| block.label);
+      self.emit_block(writer, block, next)?;
+    }
+    writeln!(writer)
+  }
+
+  /// Gets the size of the stack frame.
+  fn frame_size(&self) -> usize {
+    ((self.slot_num + self.saved_num) / 4 + 1) * 16
+  }
+
+  /// Gets the offset of the specific saved register `s{i}`.
+  fn saved_offset(&self, i: usize) -> usize {
+    (self.slot_num + i - 1) * 4
+  }
+
+  /// Gets the symbol name of the specific function with the specific arity.
+  fn func_name(&self, name: &Symbol, arity: usize) -> String {
+    match &self.mangler {
+      Some(mangler) => mangler.mangle(name, arity),
+      None => name.to_string(),
+    }
+  }
+
+  /// Dumps the label of the specific block.
+  fn label(&self, label: Label) -> String {
+    let name = self.func_name(&self.func.name, self.func.params.len());
+    format!(".L{}_{}", name, label)
+  }
+
+  /// Dumps RISC-V assembly of the specific block,
+  /// `next` is the label of the block placed after it.
+  fn emit_block(
+    &self,
+    writer: &mut impl Write,
+    block: &BasicBlock,
+    next: Option<Label>,
+  ) -> Result<()> {
+    writeln!(writer, "{}:", self.label(block.label))?;
+    for inst in &block.insts {
+      self.emit_inst(writer, inst)?;
+    }
+    match &block.term {
+      Terminator::Jump { target } => {
+        if next != Some(*target) {
+          writeln!(writer, "  j {}", self.label(*target))?;
+        }
+      }
+      Terminator::Branch {
+        cond,
+        then,
+        else_then,
+      } => {
+        let cond = self.read(writer, cond, RESULT_REG)?;
+        writeln!(writer, "  bnez {}, {}", cond, self.label(*then))?;
+        if next != Some(*else_then) {
+          writeln!(writer, "  j {}", self.label(*else_then))?;
+        }
+      }
+      Terminator::Ret { val } => {
+        // dump return value
+        match val {
+          Operand::Imm(val) => writeln!(writer, "  li a0, {}", val)?,
+          _ => {
+            let val = self.read(writer, val, RESULT_REG)?;
+            writeln!(writer, "  mv a0, {}", val)?;
+          }
+        }
+        // dump epilogue
+        for i in 1..=self.saved_num {
+          writeln!(writer, "  lw s{}, {}(sp)", i, self.saved_offset(i))?;
+        }
+        writeln!(writer, "  lw ra, {}(sp)", self.frame_size() - 4)?;
+        writeln!(writer, "  addi sp, sp, {}", self.frame_size())?;
+        writeln!(writer, "  ret")?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Dumps RISC-V assembly of the specific instruction.
+  fn emit_inst(&self, writer: &mut impl Write, inst: &Inst) -> Result<()> {
+    match inst {
+      Inst::Copy { dest, src } => {
+        let dest_reg = self.dest(dest);
+        match src {
+          Operand::Imm(val) => writeln!(writer, "  li {}, {}", dest_reg, val)?,
+          _ => {
+            let src = self.read(writer, src, RESULT_REG)?;
+            if src != dest_reg {
+              writeln!(writer, "  mv {}, {}", dest_reg, src)?;
+            }
+          }
+        }
+        self.write(writer, dest)
+      }
+      Inst::Binary { dest, op, lhs, rhs } => {
+        let lhs = self.read(writer, lhs, RESULT_REG)?;
+        let rhs = self.read(writer, rhs, TEMP_REG)?;
+        let dest_reg = self.dest(dest);
+        match op {
+          Operator::LessEq => {
+            writeln!(writer, "  sgt {}, {}, {}", dest_reg, lhs, rhs)?;
+            writeln!(writer, "  seqz {}, {}", dest_reg, dest_reg)?;
+          }
+          Operator::Eq | Operator::NotEq => {
+            let set = if *op == Operator::Eq { "seqz" } else { "snez" };
+            writeln!(writer, "  xor {}, {}, {}", dest_reg, lhs, rhs)?;
+            writeln!(writer, "  {} {}, {}", set, dest_reg, dest_reg)?;
+          }
+          Operator::LAnd => {
+            writeln!(writer, "  snez {}, {}", RESULT_REG, lhs)?;
+            writeln!(writer, "  snez {}, {}", TEMP_REG, rhs)?;
+            writeln!(writer, "  and {}, {}, {}", dest_reg, RESULT_REG, TEMP_REG)?;
+          }
+          Operator::LOr => {
+            writeln!(writer, "  or {}, {}, {}", dest_reg, lhs, rhs)?;
+            writeln!(writer, "  snez {}, {}", dest_reg, dest_reg)?;
+          }
+          _ => {
+            let name = match op {
+              Operator::Add => "add",
+              Operator::Sub => "sub",
+              Operator::Mul => "mul",
+              Operator::Div => "div",
+              Operator::Mod => "rem",
+              Operator::Less => "slt",
+              Operator::Shl => "sll",
+              _ => panic!("unknown binary operator"),
+            };
+            writeln!(writer, "  {} {}, {}, {}", name, dest_reg, lhs, rhs)?;
+          }
+        }
+        self.write(writer, dest)
+      }
+      Inst::Unary { dest, op, opr } => {
+        let opr = self.read(writer, opr, RESULT_REG)?;
+        let name = match op {
+          Operator::Sub => "neg",
+          Operator::LNot => "seqz",
+          _ => panic!("unknown unary operator"),
+        };
+        writeln!(writer, "  {} {}, {}", name, self.dest(dest), opr)?;
+        self.write(writer, dest)
+      }
+      Inst::Load { dest, slot } => {
+        writeln!(writer, "  lw {}, {}(sp)", self.dest(dest), slot * 4)?;
+        self.write(writer, dest)
+      }
+      Inst::Store { src, slot } => {
+        let src = self.read(writer, src, RESULT_REG)?;
+        writeln!(writer, "  sw {}, {}(sp)", src, slot * 4)
+      }
+      Inst::Call { dest, func, args } => {
+        // dump arguments
+        debug_assert!(args.len() <= 8, "argument count is greater than 8");
+        for (i, arg) in args.iter().enumerate() {
+          match arg {
+            Operand::Reg(reg) => match self.locations[reg] {
+              Location::Reg(reg) => writeln!(writer, "  mv a{}, s{}", i, reg + 1)?,
+              Location::Slot(slot) => writeln!(writer, "  lw a{}, {}(sp)", i, slot * 4)?,
+            },
+            Operand::Imm(val) => writeln!(writer, "  li a{}, {}", i, val)?,
+          }
+        }
+        // dump function call
+        writeln!(writer, "  call {}", self.func_name(func, args.len()))?;
+        writeln!(writer, "  mv {}, a0", self.dest(dest))?;
+        self.write(writer, dest)
+      }
+    }
+  }
+
+  /// Gets a register holding the value of the specific operand,
+  /// dumps the loading to `temp` if necessary.
+  fn read(&self, writer: &mut impl Write, opr: &Operand, temp: &str) -> Result<String> {
+    match opr {
+      Operand::Reg(reg) => match self.locations[reg] {
+        Location::Reg(reg) => Ok(format!("s{}", reg + 1)),
+        Location::Slot(slot) => {
+          writeln!(writer, "  lw {}, {}(sp)", temp, slot * 4)?;
+          Ok(temp.to_string())
+        }
+      },
+      Operand::Imm(val) => {
+        writeln!(writer, "  li {}, {}", temp, val)?;
+        Ok(temp.to_string())
+      }
+    }
+  }
+
+  /// Gets the register for storing the value of the specific virtual register.
+  fn dest(&self, reg: &Reg) -> String {
+    match self.locations[reg] {
+      Location::Reg(reg) => format!("s{}", reg + 1),
+      Location::Slot(_) => RESULT_REG.to_string(),
+    }
+  }
+
+  /// Dumps the writing of the specific virtual register if it is spilled.
+  fn write(&self, writer: &mut impl Write, reg: &Reg) -> Result<()> {
+    match self.locations[reg] {
+      Location::Reg(_) => Ok(()),
+      Location::Slot(slot) => writeln!(writer, "  sw {}, {}(sp)", RESULT_REG, slot * 4),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::RiscvEmitter;
+  use crate::codegen::mangle::Mangler;
+  use crate::define::Symbol;
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use crate::ir::gen::IrGen;
+  use std::io::Cursor;
+
+  /// Emits RISC-V assembly of the specific function definition.
+  fn emit(program: &str) -> String {
+    let mut parser = Parser::new(Lexer::new(Cursor::new(program)));
+    let func = IrGen::new().generate(&parser.parse_next().unwrap()).unwrap();
+    let mut asm = Vec::new();
+    RiscvEmitter::new(&func).emit(&mut asm).unwrap();
+    String::from_utf8(asm).unwrap()
+  }
+
+  #[test]
+  fn test_riscv() {
+    let asm = emit("add(a, b) { return a + b }");
+    let expected = r#"  .text
+  .globl add
+add:
+  addi sp, sp, -16
+  sw ra, 12(sp)
+  sw s1, 0(sp)
+  sw s2, 4(sp)
+  sw s3, 8(sp)
+  mv s1, a0
+  mv s2, a1
+.Ladd_bb0:
+  add s3, s1, s2
+  mv a0, s3
+  lw s1, 0(sp)
+  lw s2, 4(sp)
+  lw s3, 8(sp)
+  lw ra, 12(sp)
+  addi sp, sp, 16
+  ret
+
+"#;
+    assert_eq!(asm, expected);
+    // branches & calls
+    let asm = emit("f(n) { if n <= 1 { return 1 } else { return n * f(n - 1) } }");
+    assert!(asm.contains("  bnez s2, .Lf_bb1\n  j .Lf_bb2\n"));
+    assert!(asm.contains("  mv a0, s2\n  call f\n  mv s3, a0\n  mul s2, s1, s3\n"));
+  }
+
+  #[test]
+  fn test_riscv_mangle() {
+    let program = "f(n) { print(n) return f(n - 1) }";
+    let mut parser = Parser::new(Lexer::new(Cursor::new(program)));
+    let func = IrGen::new().generate(&parser.parse_next().unwrap()).unwrap();
+    let mut emitter = RiscvEmitter::new(&func);
+    let mangler = Mangler::default();
+    emitter.set_mangler(Some(mangler.clone()));
+    let mut asm = Vec::new();
+    emitter.emit(&mut asm).unwrap();
+    let asm = String::from_utf8(asm).unwrap();
+    assert!(asm.starts_with("  .text\n  .globl fs_f_1\nfs_f_1:\n"));
+    assert!(asm.contains(".Lfs_f_1_bb0:\n"));
+    assert!(asm.contains("  call print\n"));
+    assert!(asm.contains("  call fs_f_1\n"));
+    assert_eq!(mangler.demangle("fs_f_1"), Some((Symbol::new("f"), 1)));
+  }
+}
@@ -0,0 +1,290 @@
+use crate::define::Operator;
+use crate::ir::{Function, Inst, Label, Operand, Reg, Terminator};
+use std::collections::HashMap;
+use std::io::{Result, Write};
+
+/// Imports of library functions.
+const IMPORTS: &[&str] = &[
+  r#"(import "env" "input" (func $input (result i32)))"#,
+  r#"(import "env" "print" (func $print (param i32) (result i32)))"#,
+];
+
+/// Dumps a WebAssembly module in text format containing the specific functions,
+/// library functions are imported from module `env`.
+pub fn emit_module(funcs: &[Function], writer: &mut impl Write) -> Result<()> {
+  writeln!(writer, "(module")?;
+  for import in IMPORTS {
+    writeln!(writer, "  {}", import)?;
+  }
+  for func in funcs {
+    WatEmitter::new(func).emit(writer)?;
+  }
+  writeln!(writer, ")")
+}
+
+/// Emitter that generates WebAssembly text format of an IR function.
+///
+/// Basic blocks are placed in nested `block`s inside a dispatch `loop`,
+/// a `br_table` selects the block to be executed by the value of local
+/// `$next`, and terminators jump to other blocks by setting `$next` and
+/// branching to the loop. Jumps to the next block simply fall through.
+pub struct WatEmitter<'a> {
+  /// Function to be emitted.
+  func: &'a Function,
+  /// Indices of blocks in the function.
+  indices: HashMap<Label, usize>,
+}
+
+impl<'a> WatEmitter<'a> {
+  /// Creates a new emitter for the specific function.
+  pub fn new(func: &'a Function) -> Self {
+    let indices = func
+      .blocks
+      .iter()
+      .enumerate()
+      .map(|(i, block)| (block.label, i))
+      .collect();
+    Self { func, indices }
+  }
+
+  /// Dumps WebAssembly text format of the function.
+  pub fn emit(&self, writer: &mut impl Write) -> Result<()> {
+    // dump signature
+    write!(writer, "  (func ${} (export \"{}\")", self.func.name, self.func.name)?;
+    for param in &self.func.params {
+      write!(writer, " (param {} i32)", reg(param))?;
+    }
+    writeln!(writer, " (result i32)")?;
+    // dump locals
+    for i in 0..self.func.reg_num {
+      if !self.func.params.contains(&Reg(i)) {
+        writeln!(writer, "    (local {} i32)", reg(&Reg(i)))?;
+      }
+    }
+    for i in 0..self.func.slot_num {
+      writeln!(writer, "    (local $s{} i32)", i)?;
+    }
+    writeln!(writer, "    (local $next i32)")?;
+    // dump dispatcher
+    let blocks = &self.func.blocks;
+    writeln!(writer, "    loop $dispatch")?;
+    for (i, block) in blocks.iter().enumerate().rev() {
+      writeln!(writer, "{}block ${}", indent(blocks.len() - 1 - i), block.label)?;
+    }
+    let labels: Vec<_> = blocks.iter().map(|block| format!("${}", block.label)).collect();
+    let depth = indent(blocks.len());
+    writeln!(writer, "{}local.get $next", depth)?;
+    writeln!(writer, "{}br_table {}", depth, labels.join(" "))?;
+    // dump blocks
+    for (i, block) in blocks.iter().enumerate() {
+      let depth = indent(blocks.len() - 1 - i);
+      writeln!(writer, "{}end", depth)?;
+      for inst in &block.insts {
+        self.emit_inst(writer, &depth, inst)?;
+      }
+      let next = blocks.get(i + 1).map(|block| block.label);
+      self.emit_term(writer, &depth, &block.term, next)?;
+    }
+    writeln!(writer, "    end")?;
+    writeln!(writer, "    unreachable")?;
+    writeln!(writer, "  )")
+  }
+
+  /// Dumps the specific instruction.
+  fn emit_inst(&self, writer: &mut impl Write, depth: &str, inst: &Inst) -> Result<()> {
+    let dest = match inst {
+      Inst::Copy { dest, src } => {
+        emit_opr(writer, depth, src)?;
+        dest
+      }
+      Inst::Binary { dest, op, lhs, rhs } => {
+        match op {
+          // `select` yields the first operand if the condition is non-zero
+          Operator::LAnd => {
+            emit_opr(writer, depth, rhs)?;
+            emit_opr(writer, depth, lhs)?;
+            emit_opr(writer, depth, lhs)?;
+            writeln!(writer, "{}select", depth)?;
+          }
+          Operator::LOr => {
+            emit_opr(writer, depth, lhs)?;
+            emit_opr(writer, depth, rhs)?;
+            emit_opr(writer, depth, lhs)?;
+            writeln!(writer, "{}select", depth)?;
+          }
+          _ => {
+            let name = match op {
+              Operator::Add => "add",
+              Operator::Sub => "sub",
+              Operator::Mul => "mul",
+              Operator::Div => "div_s",
+              Operator::Mod => "rem_s",
+              Operator::Less => "lt_s",
+              Operator::LessEq => "le_s",
+              Operator::Eq => "eq",
+              Operator::NotEq => "ne",
+              Operator::Shl => "shl",
+              _ => panic!("unknown binary operator"),
+            };
+            emit_opr(writer, depth, lhs)?;
+            emit_opr(writer, depth, rhs)?;
+            writeln!(writer, "{}i32.{}", depth, name)?;
+          }
+        }
+        dest
+      }
+      Inst::Unary { dest, op, opr } => {
+        match op {
+          Operator::Sub => {
+            writeln!(writer, "{}i32.const 0", depth)?;
+            emit_opr(writer, depth, opr)?;
+            writeln!(writer, "{}i32.sub", depth)?;
+          }
+          Operator::LNot => {
+            emit_opr(writer, depth, opr)?;
+            writeln!(writer, "{}i32.eqz", depth)?;
+          }
+          _ => panic!("unknown unary operator"),
+        }
+        dest
+      }
+      Inst::Load { dest, slot } => {
+        writeln!(writer, "{}local.get $s{}", depth, slot)?;
+        dest
+      }
+      Inst::Store { src, slot } => {
+        emit_opr(writer, depth, src)?;
+        return writeln!(writer, "{}local.set $s{}", depth, slot);
+      }
+      Inst::Call { dest, func, args } => {
+        for arg in args {
+          emit_opr(writer, depth, arg)?;
+        }
+        writeln!(writer, "{}call ${}", depth, func)?;
+        dest
+      }
+    };
+    writeln!(writer, "{}local.set {}", depth, reg(dest))
+  }
+
+  /// Dumps the specific terminator,
+  /// `next` is the label of the block placed after the current block.
+  fn emit_term(
+    &self,
+    writer: &mut impl Write,
+    depth: &str,
+    term: &Terminator,
+    next: Option<Label>,
+  ) -> Result<()> {
+    match term {
+      Terminator::Jump { target } => {
+        if next != Some(*target) {
+          self.emit_jump(writer, depth, *target)?;
+          writeln!(writer, "{}br $dispatch", depth)?;
+        }
+        Ok(())
+      }
+      Terminator::Branch {
+        cond,
+        then,
+        else_then,
+      } => {
+        self.emit_jump(writer, depth, *then)?;
+        emit_opr(writer, depth, cond)?;
+        writeln!(writer, "{}br_if $dispatch", depth)?;
+        self.emit_term(writer, depth, &Terminator::Jump { target: *else_then }, next)
+      }
+      Terminator::Ret { val } => {
+        emit_opr(writer, depth, val)?;
+        writeln!(writer, "{}return", depth)
+      }
+    }
+  }
+
+  /// Dumps the setting of `$next` to the specific block.
+  fn emit_jump(&self, writer: &mut impl Write, depth: &str, target: Label) -> Result<()> {
+    writeln!(writer, "{}i32.const {}", depth, self.indices[&target])?;
+    writeln!(writer, "{}local.set $next", depth)
+  }
+}
+
+/// Gets the name of the local of the specific register.
+fn reg(reg: &Reg) -> String {
+  format!("$r{}", reg.0)
+}
+
+/// Gets the indentation of instructions in the specific nested block.
+fn indent(depth: usize) -> String {
+  " ".repeat(6 + depth * 2)
+}
+
+/// Dumps the pushing of the specific operand.
+fn emit_opr(writer: &mut impl Write, depth: &str, opr: &Operand) -> Result<()> {
+  match opr {
+    Operand::Reg(opr) => writeln!(writer, "{}local.get {}", depth, reg(opr)),
+    Operand::Imm(val) => writeln!(writer, "{}i32.const {}", depth, val),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::emit_module;
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use crate::ir::gen::IrGen;
+  use std::io::Cursor;
+
+  /// Emits WebAssembly text format of the specific program.
+  fn emit(program: &str) -> String {
+    let mut parser = Parser::new(Lexer::new(Cursor::new(program)));
+    let funcs: Vec<_> = parser
+      .parse_program()
+      .unwrap()
+      .iter()
+      .map(|ast| IrGen::new().generate(ast).unwrap())
+      .collect();
+    let mut wat = Vec::new();
+    emit_module(&funcs, &mut wat).unwrap();
+    String::from_utf8(wat).unwrap()
+  }
+
+  #[test]
+  fn test_wat() {
+    let wat = emit("f(x) { if x { x = x + 1 } return x }");
+    let expected = r#"(module
+  (import "env" "input" (func $input (result i32)))
+  (import "env" "print" (func $print (param i32) (result i32)))
+  (func $f (export "f") (param $r0 i32) (result i32)
+    (local $r1 i32)
+    (local $next i32)
+    loop $dispatch
+      block $bb2
+        block $bb1
+          block $bb0
+            local.get $next
+            br_table $bb0 $bb1 $bb2
+          end
+          i32.const 1
+          local.set $next
+          local.get $r0
+          br_if $dispatch
+          i32.const 2
+          local.set $next
+          br $dispatch
+        end
+        local.get $r0
+        i32.const 1
+        i32.add
+        local.set $r1
+        local.get $r1
+        local.set $r0
+      end
+      local.get $r0
+      return
+    end
+    unreachable
+  )
+)
+"#;
+    assert_eq!(wat, expected);
+  }
+}
@@ -0,0 +1,139 @@
+use crate::define::Operator;
+use core::convert::TryFrom;
+
+/// Overflow handling mode of integer arithmetic.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OverflowMode {
+  /// Wraps around at the boundary of the type.
+  #[default]
+  Wrapping,
+  /// Reports an error on overflow.
+  Checked,
+  /// Panics on overflow.
+  Panicking,
+}
+
+/// `Result` for arithmetic operations.
+pub type Result<T = i32> = core::result::Result<T, &'static str>;
+
+/// Integer types supported by arithmetic operations.
+pub trait Integer: Copy + PartialOrd + From<bool> + core::ops::Shr<u32, Output = Self> {
+  /// Zero value of the type.
+  const ZERO: Self;
+  /// Number of bits of the type.
+  const BITS: u32;
+
+  // arithmetic methods of primitive integers
+  fn checked_add(self, rhs: Self) -> Option<Self>;
+  fn wrapping_add(self, rhs: Self) -> Self;
+  fn checked_sub(self, rhs: Self) -> Option<Self>;
+  fn wrapping_sub(self, rhs: Self) -> Self;
+  fn checked_mul(self, rhs: Self) -> Option<Self>;
+  fn wrapping_mul(self, rhs: Self) -> Self;
+  fn checked_div(self, rhs: Self) -> Option<Self>;
+  fn wrapping_div(self, rhs: Self) -> Self;
+  fn checked_rem(self, rhs: Self) -> Option<Self>;
+  fn wrapping_rem(self, rhs: Self) -> Self;
+  fn checked_pow(self, exp: u32) -> Option<Self>;
+  fn wrapping_pow(self, exp: u32) -> Self;
+  fn checked_neg(self) -> Option<Self>;
+  fn wrapping_neg(self) -> Self;
+  fn wrapping_shl(self, rhs: u32) -> Self;
+  /// Converts to `u32`, returns `None` if the value is out of range.
+  fn to_u32(self) -> Option<u32>;
+}
+
+/// Implements `Integer` for the specific types.
+macro_rules! impl_integer {
+  ($($ty:ty),*) => {
+    $(impl Integer for $ty {
+      const ZERO: Self = 0;
+      const BITS: u32 = <$ty>::BITS;
+
+      fn checked_add(self, rhs: Self) -> Option<Self> { <$ty>::checked_add(self, rhs) }
+      fn wrapping_add(self, rhs: Self) -> Self { <$ty>::wrapping_add(self, rhs) }
+      fn checked_sub(self, rhs: Self) -> Option<Self> { <$ty>::checked_sub(self, rhs) }
+      fn wrapping_sub(self, rhs: Self) -> Self { <$ty>::wrapping_sub(self, rhs) }
+      fn checked_mul(self, rhs: Self) -> Option<Self> { <$ty>::checked_mul(self, rhs) }
+      fn wrapping_mul(self, rhs: Self) -> Self { <$ty>::wrapping_mul(self, rhs) }
+      fn checked_div(self, rhs: Self) -> Option<Self> { <$ty>::checked_div(self, rhs) }
+      fn wrapping_div(self, rhs: Self) -> Self { <$ty>::wrapping_div(self, rhs) }
+      fn checked_rem(self, rhs: Self) -> Option<Self> { <$ty>::checked_rem(self, rhs) }
+      fn wrapping_rem(self, rhs: Self) -> Self { <$ty>::wrapping_rem(self, rhs) }
+      fn checked_pow(self, exp: u32) -> Option<Self> { <$ty>::checked_pow(self, exp) }
+      fn wrapping_pow(self, exp: u32) -> Self { <$ty>::wrapping_pow(self, exp) }
+      fn checked_neg(self) -> Option<Self> { <$ty>::checked_neg(self) }
+      fn wrapping_neg(self) -> Self { <$ty>::wrapping_neg(self) }
+      fn wrapping_shl(self, rhs: u32) -> Self { <$ty>::wrapping_shl(self, rhs) }
+      fn to_u32(self) -> Option<u32> { u32::try_from(self).ok() }
+    })*
+  };
+}
+
+impl_integer!(i32, i64);
+
+impl OverflowMode {
+  /// Selects the result of an operation according to the current mode.
+  fn select<T>(self, checked: Option<T>, wrapping: T) -> Result<T> {
+    match self {
+      OverflowMode::Wrapping => Ok(wrapping),
+      OverflowMode::Checked => checked.ok_or("integer overflow"),
+      OverflowMode::Panicking => Ok(checked.expect("integer overflow")),
+    }
+  }
+
+  /// Gets the mode for evaluating operations at compile time,
+  /// so that operations that would panic at runtime report errors.
+  pub fn const_eval_mode(self) -> Self {
+    match self {
+      OverflowMode::Wrapping => OverflowMode::Wrapping,
+      OverflowMode::Checked | OverflowMode::Panicking => OverflowMode::Checked,
+    }
+  }
+}
+
+/// Performs the specific binary operation on integers.
+///
+/// Logical operators are evaluated without short-circuiting,
+/// and return the value of the last evaluated operand.
+pub fn eval_binary<T: Integer>(op: &Operator, lhs: T, rhs: T, mode: OverflowMode) -> Result<T> {
+  let zero = T::ZERO;
+  match op {
+    Operator::Add => mode.select(lhs.checked_add(rhs), lhs.wrapping_add(rhs)),
+    Operator::Sub => mode.select(lhs.checked_sub(rhs), lhs.wrapping_sub(rhs)),
+    Operator::Mul => mode.select(lhs.checked_mul(rhs), lhs.wrapping_mul(rhs)),
+    Operator::Div | Operator::Mod if rhs == zero => Err("division by zero"),
+    Operator::Div => mode.select(lhs.checked_div(rhs), lhs.wrapping_div(rhs)),
+    Operator::Mod => mode.select(lhs.checked_rem(rhs), lhs.wrapping_rem(rhs)),
+    Operator::Pow => match rhs.to_u32() {
+      Some(exp) => mode.select(lhs.checked_pow(exp), lhs.wrapping_pow(exp)),
+      None if rhs < zero => Err("negative exponent"),
+      // exponents out of `u32` are only possible for 64-bit integers
+      None => Err("exponent too large"),
+    },
+    Operator::Less => Ok((lhs < rhs).into()),
+    Operator::LessEq => Ok((lhs <= rhs).into()),
+    Operator::Eq => Ok((lhs == rhs).into()),
+    Operator::NotEq => Ok((lhs != rhs).into()),
+    Operator::LAnd => Ok(if lhs == zero { lhs } else { rhs }),
+    Operator::LOr => Ok(if lhs != zero { lhs } else { rhs }),
+    // overflows if shifting back does not yield the original value
+    Operator::Shl => match rhs.to_u32().filter(|rhs| *rhs < T::BITS) {
+      Some(rhs) => {
+        let val = lhs.wrapping_shl(rhs);
+        mode.select((val >> rhs == lhs).then_some(val), val)
+      }
+      None => mode.select((lhs == zero).then_some(zero), zero),
+    },
+    _ => panic!("unknown binary operator"),
+  }
+}
+
+/// Performs the specific unary operation on integers.
+pub fn eval_unary<T: Integer>(op: &Operator, opr: T, mode: OverflowMode) -> Result<T> {
+  match op {
+    Operator::Sub => mode.select(opr.checked_neg(), opr.wrapping_neg()),
+    Operator::LNot => Ok((opr == T::ZERO).into()),
+    _ => panic!("invalid unary operator"),
+  }
+}
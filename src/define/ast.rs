@@ -1,32 +1,60 @@
-use crate::define::Operator;
+use crate::define::{Operator, Symbol};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
 
 /// ASTs of `first-step`.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Ast {
   /// Function definition.
+  ///
+  /// Types of arguments and the return value are optional annotations,
+  /// `arg_types` always has the same length as `args`.
+  /// `doc` is the text of doc comments before the definition,
+  /// one line per comment.
   FunDef {
-    name: String,
-    args: Vec<String>,
+    name: Symbol,
+    args: ArgList<Symbol>,
+    arg_types: ArgList<Option<Type>>,
+    ret_type: Option<Type>,
     body: AstBox,
+    doc: Option<String>,
   },
 
   /// Statement block.
   Block { stmts: Vec<AstBox> },
 
   /// Define statement.
-  Define { name: String, expr: AstBox },
+  Define { name: Symbol, expr: AstBox },
 
   /// Assign statement.
-  Assign { name: String, expr: AstBox },
+  Assign { name: Symbol, expr: AstBox },
 
   /// If-else statement.
+  ///
+  /// `else if` chains are represented as nested if-else statements
+  /// in `else_then`, the final `else` block is the innermost `else_then`.
   If {
     cond: AstBox,
     then: AstBox,
     else_then: Option<AstBox>,
   },
 
-  /// Return statement.
-  Return { expr: AstBox },
+  /// Switch statement.
+  ///
+  /// Bodies of cases and the default case are statement blocks,
+  /// there is no fall through between cases.
+  Switch {
+    scrutinee: AstBox,
+    cases: Vec<(i64, AstBox)>,
+    default: Option<AstBox>,
+  },
+
+  /// Do-while statement, `body` is evaluated at least once.
+  DoWhile { body: AstBox, cond: AstBox },
+
+  /// Return statement, `expr` is `None` if there is no return value.
+  Return { expr: Option<AstBox> },
 
   /// Binary expression.
   Binary {
@@ -39,18 +67,199 @@ pub enum Ast {
   Unary { op: Operator, opr: AstBox },
 
   /// Function call.
-  FunCall { name: String, args: Vec<AstBox> },
+  FunCall { name: Symbol, args: ArgList<AstBox> },
 
   /// Integer literal.
-  Int { val: i32 },
+  Int { val: i64 },
 
   /// Identifier.
-  Id { id: String },
+  Id { id: Symbol },
+
+  /// Comment, only generated if comments are kept by the lexer.
+  ///
+  /// `text` is the text after the comment character, `trailing` is `true`
+  /// if the comment is in the same line as the previous token.
+  Comment { text: String, trailing: bool },
+}
+
+/// Prints ASTs as indented trees, one node per line, like:
+///
+/// ```text
+/// Return
+///   Binary Add
+///     Id x
+///     Int 1
+/// ```
+impl fmt::Debug for Ast {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.fmt_tree(f, 0)
+  }
+}
+
+impl Ast {
+  /// Writes the tree of the current AST at the specific depth,
+  /// nodes at depth `n` are indented by `2n` spaces.
+  fn fmt_tree(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    write!(f, "{:1$}", "", depth * 2)?;
+    let label = |f: &mut fmt::Formatter, label: fmt::Arguments| {
+      write!(f, "\n{:2$}{}", "", label, (depth + 1) * 2)
+    };
+    match self {
+      Ast::FunDef {
+        name,
+        args,
+        arg_types,
+        ret_type,
+        body,
+        ..
+      } => {
+        write!(f, "FunDef {}(", name)?;
+        for (i, (arg, ty)) in args.iter().zip(arg_types).enumerate() {
+          if i != 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{}", arg)?;
+          if let Some(ty) = ty {
+            write!(f, ": {}", ty)?;
+          }
+        }
+        write!(f, ")")?;
+        if let Some(ty) = ret_type {
+          write!(f, " -> {}", ty)?;
+        }
+        Self::fmt_children(f, depth, [body])
+      }
+      Ast::Block { stmts } => {
+        write!(f, "Block")?;
+        Self::fmt_children(f, depth, stmts)
+      }
+      Ast::Define { name, expr } => {
+        write!(f, "Define {}", name)?;
+        Self::fmt_children(f, depth, [expr])
+      }
+      Ast::Assign { name, expr } => {
+        write!(f, "Assign {}", name)?;
+        Self::fmt_children(f, depth, [expr])
+      }
+      Ast::If {
+        cond,
+        then,
+        else_then,
+      } => {
+        write!(f, "If")?;
+        let children = IntoIterator::into_iter([cond, then]).chain(else_then);
+        Self::fmt_children(f, depth, children)
+      }
+      Ast::Switch {
+        scrutinee,
+        cases,
+        default,
+      } => {
+        write!(f, "Switch")?;
+        Self::fmt_children(f, depth, [scrutinee])?;
+        for (val, body) in cases {
+          label(f, format_args!("Case {}", val))?;
+          Self::fmt_children(f, depth + 1, [body])?;
+        }
+        if let Some(default) = default {
+          label(f, format_args!("Default"))?;
+          Self::fmt_children(f, depth + 1, [default])?;
+        }
+        Ok(())
+      }
+      Ast::DoWhile { body, cond } => {
+        write!(f, "DoWhile")?;
+        Self::fmt_children(f, depth, [body, cond])
+      }
+      Ast::Return { expr } => {
+        write!(f, "Return")?;
+        Self::fmt_children(f, depth, expr)
+      }
+      Ast::Binary { op, lhs, rhs } => {
+        write!(f, "Binary {:?}", op)?;
+        Self::fmt_children(f, depth, [lhs, rhs])
+      }
+      Ast::Unary { op, opr } => {
+        write!(f, "Unary {:?}", op)?;
+        Self::fmt_children(f, depth, [opr])
+      }
+      Ast::FunCall { name, args } => {
+        write!(f, "FunCall {}", name)?;
+        Self::fmt_children(f, depth, args)
+      }
+      Ast::Int { val } => write!(f, "Int {}", val),
+      Ast::Id { id } => write!(f, "Id {}", id),
+      Ast::Comment { text, trailing } => {
+        write!(f, "Comment {:?}", text)?;
+        if *trailing {
+          write!(f, " (trailing)")?;
+        }
+        Ok(())
+      }
+    }
+  }
+
+  /// Writes the specific children of the AST at the specific depth.
+  fn fmt_children<'a>(
+    f: &mut fmt::Formatter,
+    depth: usize,
+    children: impl IntoIterator<Item = &'a AstBox>,
+  ) -> fmt::Result {
+    for child in children {
+      writeln!(f)?;
+      child.fmt_tree(f, depth + 1)?;
+    }
+    Ok(())
+  }
+}
+
+/// Types of `first-step`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Type {
+  /// Integer.
+  Int,
+  /// Boolean (result of relational and logical operations).
+  Bool,
+  /// Type of statements and function definitions.
+  Unit,
+}
+
+impl fmt::Display for Type {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let ty = match self {
+      Type::Int => "int",
+      Type::Bool => "bool",
+      Type::Unit => "unit",
+    };
+    write!(f, "{}", ty)
+  }
 }
 
 /// Box for ASTs.
 pub type AstBox = Box<Ast>;
 
+/// Program (list of function definitions).
+pub type Program = Vec<AstBox>;
+
+/// Argument list of function definitions and calls.
+#[cfg(not(feature = "smallvec"))]
+pub type ArgList<T> = Vec<T>;
+
+/// Argument list of function definitions and calls,
+/// stores up to 4 arguments inline without heap allocation.
+#[cfg(feature = "smallvec")]
+pub type ArgList<T> = smallvec::SmallVec<[T; 4]>;
+
+// ASTs can be sent and shared between threads,
+// so that parsing and analyses can run on different threads
+const _: fn() = || {
+  fn assert_send_sync<T: Send + Sync>() {}
+  assert_send_sync::<Ast>();
+  assert_send_sync::<AstBox>();
+  assert_send_sync::<Program>();
+};
+
 /// AST visitor for visiting ASTs.
 pub trait AstVisitor {
   type Result;
@@ -59,7 +268,14 @@ pub trait AstVisitor {
   fn visit(&mut self, ast: &AstBox) -> Self::Result {
     use Ast::*;
     match ast.as_ref() {
-      FunDef { name, args, body } => self.visit_fundef(name, args, body),
+      FunDef {
+        name,
+        args,
+        arg_types,
+        ret_type,
+        body,
+        doc,
+      } => self.visit_fundef(name, args, arg_types, ret_type, body, doc),
       Block { stmts } => self.visit_block(stmts),
       Define { name, expr } => self.visit_define(name, expr),
       Assign { name, expr } => self.visit_assign(name, expr),
@@ -68,35 +284,403 @@ pub trait AstVisitor {
         then,
         else_then,
       } => self.visit_if(cond, then, else_then),
+      Switch {
+        scrutinee,
+        cases,
+        default,
+      } => self.visit_switch(scrutinee, cases, default),
+      DoWhile { body, cond } => self.visit_do_while(body, cond),
       Return { expr } => self.visit_return(expr),
       Binary { op, lhs, rhs } => self.visit_binary(op, lhs, rhs),
       Unary { op, opr } => self.visit_unary(op, opr),
       FunCall { name, args } => self.visit_funcall(name, args),
       Int { val } => self.visit_int(val),
       Id { id } => self.visit_id(id),
+      Comment { text, trailing } => self.visit_comment(text, trailing),
     }
   }
 
-  /// Visits function definitions.
-  fn visit_fundef(&mut self, name: &String, args: &[String], body: &AstBox) -> Self::Result;
+  /// Visits function definitions and their doc comments.
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    arg_types: &[Option<Type>],
+    ret_type: &Option<Type>,
+    body: &AstBox,
+    doc: &Option<String>,
+  ) -> Self::Result;
   /// Visits statement blocks.
   fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result;
   /// Visits define statements.
-  fn visit_define(&mut self, name: &String, expr: &AstBox) -> Self::Result;
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result;
   /// Visits assign statements.
-  fn visit_assign(&mut self, name: &String, expr: &AstBox) -> Self::Result;
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result;
   /// Visits if-else statements.
   fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result;
+  /// Visits switch statements.
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result;
+  /// Visits do-while statements.
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result;
   /// Visits return statements.
-  fn visit_return(&mut self, expr: &AstBox) -> Self::Result;
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result;
   /// Visits binary statements.
   fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result;
   /// Visits unary statements.
   fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result;
   /// Visits function calls.
-  fn visit_funcall(&mut self, name: &String, args: &[AstBox]) -> Self::Result;
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result;
   /// Visits integer literals.
-  fn visit_int(&mut self, val: &i32) -> Self::Result;
+  fn visit_int(&mut self, val: &i64) -> Self::Result;
   /// Visits identifiers.
-  fn visit_id(&mut self, val: &String) -> Self::Result;
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result;
+  /// Visits comments.
+  fn visit_comment(&mut self, text: &str, trailing: &bool) -> Self::Result;
+}
+
+/// Combinators of fallible AST visitors, whose results are `Result`s.
+/// All of them stop visiting at the first error.
+pub trait TryAstVisitor<T, E>: AstVisitor<Result = Result<T, E>> {
+  /// Visits ASTs in order, returns all results or the first error.
+  fn try_visit(&mut self, asts: &[AstBox]) -> Result<Vec<T>, E> {
+    asts.iter().map(|ast| self.visit(ast)).collect()
+  }
+
+  /// Visits an optional AST.
+  fn try_visit_opt(&mut self, ast: &Option<AstBox>) -> Result<Option<T>, E> {
+    ast.as_ref().map(|ast| self.visit(ast)).transpose()
+  }
+}
+
+impl<V, T, E> TryAstVisitor<T, E> for V where V: AstVisitor<Result = Result<T, E>> {}
+
+/// AST transformer for rewriting ASTs, takes the ownership of ASTs
+/// and returns the rewritten ones.
+///
+/// By default, all methods rebuild the node with its children transformed,
+/// so implementors only need to override methods of nodes to be rewritten.
+pub trait AstTransformer {
+  /// Transforms an AST.
+  // takes `AstBox` since all children are boxed in the tree
+  #[allow(clippy::boxed_local)]
+  fn transform(&mut self, ast: AstBox) -> AstBox {
+    use Ast::*;
+    match *ast {
+      FunDef {
+        name,
+        args,
+        arg_types,
+        ret_type,
+        body,
+        doc,
+      } => self.transform_fundef(name, args, arg_types, ret_type, body, doc),
+      Block { stmts } => self.transform_block(stmts),
+      Define { name, expr } => self.transform_define(name, expr),
+      Assign { name, expr } => self.transform_assign(name, expr),
+      If {
+        cond,
+        then,
+        else_then,
+      } => self.transform_if(cond, then, else_then),
+      Switch {
+        scrutinee,
+        cases,
+        default,
+      } => self.transform_switch(scrutinee, cases, default),
+      DoWhile { body, cond } => self.transform_do_while(body, cond),
+      Return { expr } => self.transform_return(expr),
+      Binary { op, lhs, rhs } => self.transform_binary(op, lhs, rhs),
+      Unary { op, opr } => self.transform_unary(op, opr),
+      FunCall { name, args } => self.transform_funcall(name, args),
+      Int { val } => self.transform_int(val),
+      Id { id } => self.transform_id(id),
+      Comment { text, trailing } => self.transform_comment(text, trailing),
+    }
+  }
+
+  /// Transforms function definitions.
+  fn transform_fundef(
+    &mut self,
+    name: Symbol,
+    args: ArgList<Symbol>,
+    arg_types: ArgList<Option<Type>>,
+    ret_type: Option<Type>,
+    body: AstBox,
+    doc: Option<String>,
+  ) -> AstBox {
+    let body = self.transform(body);
+    Box::new(Ast::FunDef {
+      name,
+      args,
+      arg_types,
+      ret_type,
+      body,
+      doc,
+    })
+  }
+
+  /// Transforms statement blocks.
+  fn transform_block(&mut self, stmts: Vec<AstBox>) -> AstBox {
+    let stmts = stmts.into_iter().map(|stmt| self.transform(stmt)).collect();
+    Box::new(Ast::Block { stmts })
+  }
+
+  /// Transforms define statements.
+  fn transform_define(&mut self, name: Symbol, expr: AstBox) -> AstBox {
+    let expr = self.transform(expr);
+    Box::new(Ast::Define { name, expr })
+  }
+
+  /// Transforms assign statements.
+  fn transform_assign(&mut self, name: Symbol, expr: AstBox) -> AstBox {
+    let expr = self.transform(expr);
+    Box::new(Ast::Assign { name, expr })
+  }
+
+  /// Transforms if-else statements.
+  fn transform_if(&mut self, cond: AstBox, then: AstBox, else_then: Option<AstBox>) -> AstBox {
+    Box::new(Ast::If {
+      cond: self.transform(cond),
+      then: self.transform(then),
+      else_then: else_then.map(|ast| self.transform(ast)),
+    })
+  }
+
+  /// Transforms switch statements.
+  fn transform_switch(
+    &mut self,
+    scrutinee: AstBox,
+    cases: Vec<(i64, AstBox)>,
+    default: Option<AstBox>,
+  ) -> AstBox {
+    Box::new(Ast::Switch {
+      scrutinee: self.transform(scrutinee),
+      cases: cases
+        .into_iter()
+        .map(|(val, body)| (val, self.transform(body)))
+        .collect(),
+      default: default.map(|ast| self.transform(ast)),
+    })
+  }
+
+  /// Transforms do-while statements.
+  fn transform_do_while(&mut self, body: AstBox, cond: AstBox) -> AstBox {
+    let body = self.transform(body);
+    let cond = self.transform(cond);
+    Box::new(Ast::DoWhile { body, cond })
+  }
+
+  /// Transforms return statements.
+  fn transform_return(&mut self, expr: Option<AstBox>) -> AstBox {
+    let expr = expr.map(|expr| self.transform(expr));
+    Box::new(Ast::Return { expr })
+  }
+
+  /// Transforms binary expressions.
+  fn transform_binary(&mut self, op: Operator, lhs: AstBox, rhs: AstBox) -> AstBox {
+    let lhs = self.transform(lhs);
+    let rhs = self.transform(rhs);
+    Box::new(Ast::Binary { op, lhs, rhs })
+  }
+
+  /// Transforms unary expressions.
+  fn transform_unary(&mut self, op: Operator, opr: AstBox) -> AstBox {
+    let opr = self.transform(opr);
+    Box::new(Ast::Unary { op, opr })
+  }
+
+  /// Transforms function calls.
+  fn transform_funcall(&mut self, name: Symbol, args: ArgList<AstBox>) -> AstBox {
+    let args = args.into_iter().map(|arg| self.transform(arg)).collect();
+    Box::new(Ast::FunCall { name, args })
+  }
+
+  /// Transforms integer literals.
+  fn transform_int(&mut self, val: i64) -> AstBox {
+    Box::new(Ast::Int { val })
+  }
+
+  /// Transforms identifiers.
+  fn transform_id(&mut self, id: Symbol) -> AstBox {
+    Box::new(Ast::Id { id })
+  }
+
+  /// Transforms comments.
+  fn transform_comment(&mut self, text: String, trailing: bool) -> AstBox {
+    Box::new(Ast::Comment { text, trailing })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{ArgList, Ast, AstBox, AstTransformer, AstVisitor};
+  use crate::define::{Operator, Symbol};
+  use crate::dump::pretty::Printer;
+  use crate::front::parser::Parser;
+
+  #[test]
+  fn test_clone() {
+    let program = "(a + 1) * -b";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next_expr().unwrap();
+    let mut cloned = ast.clone();
+    let mut printer = Printer::new();
+    assert_eq!(printer.visit(&cloned), printer.visit(&ast));
+    // modifying the clone should not affect the original tree
+    if let Ast::Binary { lhs, .. } = cloned.as_mut() {
+      *lhs = AstBox::new(Ast::Int { val: 2 });
+    }
+    assert!(matches!(cloned.as_ref(), Ast::Binary { op: Operator::Mul, .. }));
+    assert_eq!(printer.visit(&cloned), "2 * -b");
+    assert_eq!(printer.visit(&ast), "(a + 1) * -b");
+  }
+
+  #[test]
+  fn test_eq() {
+    let parse = |program| {
+      let parser = Parser::from_str(program);
+      parser.map(|ast| ast.unwrap()).collect::<Vec<_>>()
+    };
+    let program = "f(a) { if a { return f(a - 1) * 2 } return 1 }";
+    assert!(parse(program) == parse(program));
+    // spaces and comments do not matter
+    assert!(parse(program) == parse("f(a){if a{return f(a-1)*2}return 1} # f"));
+    // payloads and structures do matter
+    assert!(parse(program) != parse("f(a) { if a { return f(a - 1) * 3 } return 1 }"));
+    assert!(parse(program) != parse("f(b) { if b { return f(b - 1) * 2 } return 1 }"));
+    assert!(parse(program) != parse("f(a) { if a { return f(a - 1) * 2 } else { return 1 } }"));
+  }
+
+  #[test]
+  fn test_debug() {
+    let program = r#"
+f(a: int, b) -> int {
+  if a < -b { return f(a, 1) }
+  switch b { case 1: a = 2 default: }
+  return
+}
+"#;
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next().unwrap();
+    let expected = r#"FunDef f(a: int, b) -> int
+  Block
+    If
+      Binary Less
+        Id a
+        Unary Sub
+          Id b
+      Block
+        Return
+          FunCall f
+            Id a
+            Int 1
+    Switch
+      Id b
+      Case 1
+        Block
+          Assign a
+            Int 2
+      Default
+        Block
+    Return"#;
+    assert_eq!(format!("{:?}", ast), expected);
+    // subtrees are printed without the indentation of their parents
+    let body = match ast.as_ref() {
+      Ast::FunDef { body, .. } => body,
+      _ => panic!("expected function definition"),
+    };
+    assert!(format!("{:?}", body).starts_with("Block\n  If\n    Binary Less\n"));
+    assert_eq!(format!("{:?}", Some(body)), format!("Some({:?})", body));
+  }
+
+  #[test]
+  fn test_transformer() {
+    /// Replaces calls of function `f` with `0`.
+    struct CallReplacer;
+
+    impl AstTransformer for CallReplacer {
+      fn transform_funcall(&mut self, name: Symbol, args: ArgList<AstBox>) -> AstBox {
+        if name == "f" {
+          Box::new(Ast::Int { val: 0 })
+        } else {
+          let args = args.into_iter().map(|arg| self.transform(arg)).collect();
+          Box::new(Ast::FunCall { name, args })
+        }
+      }
+    }
+
+    let program = "g(a) { x := a + f(a, 1) * -b if x { return g(f(2)) } return (x) }";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next().unwrap();
+    let transformed = CallReplacer.transform(ast.clone());
+    let expected = "g(a) {\n  x := a + 0 * -b\n  if x {\n    return g(0)\n  }\n  return x\n}";
+    assert_eq!(Printer::new().visit(&transformed), expected);
+    // siblings of the rewritten subtrees are left intact
+    let stmts = |ast: &AstBox| match ast.as_ref() {
+      Ast::FunDef { body, .. } => match body.as_ref() {
+        Ast::Block { stmts } => stmts.clone(),
+        _ => panic!("expected block"),
+      },
+      _ => panic!("expected function definition"),
+    };
+    let (old, new) = (stmts(&ast), stmts(&transformed));
+    assert!(old[1] != new[1]);
+    assert!(old[2] == new[2]);
+  }
+
+  #[test]
+  fn test_arg_list() {
+    let program = "f() { return g(1, 2, 3) } g(a, b, c) { return a + b + c }";
+    let asts = Parser::from_str(program).parse_program().unwrap();
+    let args: Vec<&ArgList<Symbol>> = asts
+      .iter()
+      .map(|ast| match ast.as_ref() {
+        Ast::FunDef { args, .. } => args,
+        _ => panic!("not a function"),
+      })
+      .collect();
+    assert!(args[0].is_empty());
+    assert_eq!(args[1].as_slice(), ["a", "b", "c"]);
+    // the argument list of a function without arguments is not allocated
+    #[cfg(not(feature = "smallvec"))]
+    assert_eq!(args[0].capacity(), 0);
+    // short argument lists are stored inline
+    #[cfg(feature = "smallvec")]
+    assert!(!args[0].spilled() && !args[1].spilled());
+  }
+
+  #[test]
+  fn test_send() {
+    use crate::pass::metrics::NodeCounter;
+    let program = "f(x) { return x + 1 } main() { return f(1) }";
+    let asts = Parser::from_str(program).parse_program().unwrap();
+    // parse on this thread, analyze on another
+    let handle = std::thread::spawn(move || {
+      let counts = NodeCounter::new().count(&asts);
+      (counts.fundef, asts)
+    });
+    let (fundef, asts) = handle.join().unwrap();
+    assert_eq!(fundef, 2);
+    assert!(matches!(asts[1].as_ref(), Ast::FunDef { name, .. } if *name == "main"));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde() {
+    use super::Program;
+    let program = "f(a, b) { if a < b { return -a } else { c := f(b, a) return c } }";
+    let parser = Parser::from_str(program);
+    let asts: Program = parser.map(|ast| ast.unwrap()).collect();
+    let json = serde_json::to_string(&asts).unwrap();
+    let fundef = r#"{"FunDef":{"name":"f","args":["a","b"],"arg_types":[null,null],"ret_type":null,"#;
+    assert!(json.contains(fundef));
+    let restored: Program = serde_json::from_str(&json).unwrap();
+    // the restored tree should be exactly the same
+    assert!(restored == asts);
+  }
 }
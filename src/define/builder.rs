@@ -0,0 +1,155 @@
+use crate::define::{ArgList, Ast, AstBox, Operator, Symbol};
+use alloc::boxed::Box;
+
+/// AST builder, constructs boxed ASTs programmatically,
+/// like `b.binary(Operator::Add, b.id("x"), b.int(1))`.
+#[derive(Default)]
+pub struct AstBuilder;
+
+impl AstBuilder {
+  /// Creates a new AST builder.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Builds a function definition without type annotations.
+  pub fn fundef<'a, I>(&self, name: &str, args: I, body: AstBox) -> AstBox
+  where
+    I: IntoIterator<Item = &'a str>,
+  {
+    let args: ArgList<_> = args.into_iter().map(Symbol::new).collect();
+    Box::new(Ast::FunDef {
+      name: Symbol::new(name),
+      arg_types: args.iter().map(|_| None).collect(),
+      args,
+      ret_type: None,
+      body,
+      doc: None,
+    })
+  }
+
+  /// Builds a statement block.
+  pub fn block(&self, stmts: impl IntoIterator<Item = AstBox>) -> AstBox {
+    Box::new(Ast::Block {
+      stmts: stmts.into_iter().collect(),
+    })
+  }
+
+  /// Builds a define statement.
+  pub fn define(&self, name: &str, expr: AstBox) -> AstBox {
+    Box::new(Ast::Define {
+      name: Symbol::new(name),
+      expr,
+    })
+  }
+
+  /// Builds an assign statement.
+  pub fn assign(&self, name: &str, expr: AstBox) -> AstBox {
+    Box::new(Ast::Assign {
+      name: Symbol::new(name),
+      expr,
+    })
+  }
+
+  /// Builds an if statement without else branch.
+  pub fn if_then(&self, cond: AstBox, then: AstBox) -> AstBox {
+    Box::new(Ast::If {
+      cond,
+      then,
+      else_then: None,
+    })
+  }
+
+  /// Builds an if-else statement.
+  pub fn if_else(&self, cond: AstBox, then: AstBox, else_then: AstBox) -> AstBox {
+    Box::new(Ast::If {
+      cond,
+      then,
+      else_then: Some(else_then),
+    })
+  }
+
+  /// Builds a return statement.
+  pub fn ret(&self, expr: AstBox) -> AstBox {
+    Box::new(Ast::Return { expr: Some(expr) })
+  }
+
+  /// Builds a return statement without return value.
+  pub fn ret_void(&self) -> AstBox {
+    Box::new(Ast::Return { expr: None })
+  }
+
+  /// Builds a binary expression.
+  pub fn binary(&self, op: Operator, lhs: AstBox, rhs: AstBox) -> AstBox {
+    Box::new(Ast::Binary { op, lhs, rhs })
+  }
+
+  /// Builds a unary expression.
+  pub fn unary(&self, op: Operator, opr: AstBox) -> AstBox {
+    Box::new(Ast::Unary { op, opr })
+  }
+
+  /// Builds a function call.
+  pub fn funcall(&self, name: &str, args: impl IntoIterator<Item = AstBox>) -> AstBox {
+    Box::new(Ast::FunCall {
+      name: Symbol::new(name),
+      args: args.into_iter().collect(),
+    })
+  }
+
+  /// Builds an integer literal.
+  pub fn int(&self, val: i64) -> AstBox {
+    Box::new(Ast::Int { val })
+  }
+
+  /// Builds an identifier.
+  pub fn id(&self, id: &str) -> AstBox {
+    Box::new(Ast::Id { id: Symbol::new(id) })
+  }
+
+  /// Builds a comment.
+  pub fn comment(&self, text: &str, trailing: bool) -> AstBox {
+    Box::new(Ast::Comment {
+      text: text.into(),
+      trailing,
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::AstBuilder;
+  use crate::define::Operator::*;
+  use crate::front::parser::Parser;
+
+  #[test]
+  fn test_builder() {
+    let program = r#"
+      f(x, y) {
+        z := -x * 2
+        if z < y { z = f(y, 1) } else if !z { return 0 }
+        return z + y
+      }
+    "#;
+    let mut parser = Parser::from_str(program);
+    let parsed = parser.parse_next().unwrap();
+    let b = AstBuilder::new();
+    let built = b.fundef(
+      "f",
+      ["x", "y"],
+      b.block([
+        b.define("z", b.binary(Mul, b.unary(Sub, b.id("x")), b.int(2))),
+        b.if_else(
+          b.binary(Less, b.id("z"), b.id("y")),
+          b.block([b.assign("z", b.funcall("f", [b.id("y"), b.int(1)]))]),
+          b.if_then(b.unary(LNot, b.id("z")), b.block([b.ret(b.int(0))])),
+        ),
+        b.ret(b.binary(Add, b.id("z"), b.id("y"))),
+      ]),
+    );
+    assert!(built == parsed);
+    let empty = b.fundef("main", [], b.block([]));
+    assert!(empty == Parser::from_str("main() {}").parse_next().unwrap());
+    assert!(b.int(1) != b.id("x"));
+  }
+}
@@ -0,0 +1,69 @@
+use alloc::{vec, vec::Vec};
+
+/// Computes the edit distance between two strings, which is the minimum
+/// number of single-character insertions, deletions, substitutions and
+/// transpositions of adjacent characters required to change one into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let (a, b): (Vec<_>, Vec<_>) = (a.chars().collect(), b.chars().collect());
+  // distances between prefixes of `a` and all prefixes of `b`,
+  // in the last two rows of the table
+  let mut prev2 = vec![0; b.len() + 1];
+  let mut prev: Vec<_> = (0..=b.len()).collect();
+  for i in 1..=a.len() {
+    let mut cur = vec![i; b.len() + 1];
+    for j in 1..=b.len() {
+      let cost = (a[i - 1] != b[j - 1]) as usize;
+      cur[j] = (prev[j - 1] + cost).min(prev[j] + 1).min(cur[j - 1] + 1);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        cur[j] = cur[j].min(prev2[j - 2] + 1);
+      }
+    }
+    prev2 = core::mem::replace(&mut prev, cur);
+  }
+  prev[b.len()]
+}
+
+/// Finds the candidate closest to the specific name by edit distance,
+/// returns `None` if no candidate is within distance `max`.
+///
+/// Candidates whose distance is not less than the length of the name
+/// are ignored, since they share nothing with the name.
+pub fn closest<'a, I>(name: &str, candidates: I, max: usize) -> Option<&'a str>
+where
+  I: IntoIterator<Item = &'a str>,
+{
+  let len = name.chars().count();
+  candidates
+    .into_iter()
+    .map(|cand| (edit_distance(name, cand), cand))
+    .filter(|&(dist, _)| dist <= max && dist < len)
+    .min()
+    .map(|(_, cand)| cand)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{closest, edit_distance};
+
+  #[test]
+  fn test_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("abc", ""), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(edit_distance("cont", "count"), 1);
+    assert_eq!(edit_distance("retrun", "return"), 1);
+    assert_eq!(edit_distance("ab", "ba"), 1);
+    assert_eq!(edit_distance("abc", "ca"), 3);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("变量", "变量名"), 1);
+  }
+
+  #[test]
+  fn test_closest() {
+    assert_eq!(closest("cont", ["x", "count", "contain"], 2), Some("count"));
+    assert_eq!(closest("cont", ["count", "conta"], 2), Some("conta"));
+    assert_eq!(closest("cont", ["x", "total"], 2), None);
+    // too short to suggest anything
+    assert_eq!(closest("y", ["x"], 2), None);
+  }
+}
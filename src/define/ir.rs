@@ -92,8 +92,8 @@ impl FunctionDef {
   /// Creates a new function definition.
   pub fn new(name: String, arg_num: usize) -> Self {
     Self {
-      name: name,
-      arg_num: arg_num,
+      name,
+      arg_num,
       slot_num: 0,
       insts: Some(Vec::new()),
     }
@@ -102,8 +102,8 @@ impl FunctionDef {
   /// Creates a new library function declaration.
   pub fn new_lib(name: String, arg_num: usize) -> Self {
     Self {
-      name: name,
-      arg_num: arg_num,
+      name,
+      arg_num,
       slot_num: 0,
       insts: None,
     }
@@ -146,7 +146,7 @@ impl FunctionDef {
     for inst in self.insts.as_ref().unwrap() {
       dump_inst(writer, inst, self)?;
     }
-    writeln!(writer, "")
+    writeln!(writer)
   }
 
   /// Gets the argument number.
@@ -176,18 +176,18 @@ fn dump_inst(writer: &mut impl Write, inst: &InstBox, func: &FunctionDef) -> Res
       dump_read(writer, cond)?;
       write!(writer, "  bnez {}, ", RESULT_REG)?;
       dump_read(writer, label)?;
-      writeln!(writer, "")
+      writeln!(writer)
     }
     Inst::BranchEqz { cond, label } => {
       dump_read(writer, cond)?;
       write!(writer, "  beqz {}, ", RESULT_REG)?;
       dump_read(writer, label)?;
-      writeln!(writer, "")
+      writeln!(writer)
     }
     Inst::Jump { label } => {
       write!(writer, "  j ")?;
       dump_read(writer, label)?;
-      writeln!(writer, "")
+      writeln!(writer)
     }
     Inst::Label { label } => {
       dump_read(writer, label)?;
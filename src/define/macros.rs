@@ -10,11 +10,11 @@ macro_rules! unwrap_enum {
   };
 }
 
-/// Unwraps a structure.
+/// Unwraps a structure, fields which are not listed are ignored.
 #[macro_export]
 macro_rules! unwrap_struct {
   ($v:expr, $p:path, $($t:tt),*) => {
-    if let $p { $($t,)* } = $v {
+    if let $p { $($t,)* .. } = $v {
       ($($t,)*)
     } else {
       panic!("Failed to unwrap struct!")
@@ -29,10 +29,10 @@ macro_rules! unwrap_struct {
 macro_rules! collection {
   // map-like
   ($($k:expr => $v:expr),* $(,)?) => {
-    std::iter::Iterator::collect(std::array::IntoIter::new([$(($k, $v),)*]))
+    core::iter::Iterator::collect(core::iter::IntoIterator::into_iter([$(($k, $v),)*]))
   };
   // set-like
   ($($v:expr),* $(,)?) => {
-    std::iter::Iterator::collect(std::array::IntoIter::new([$($v,)*]))
+    core::iter::Iterator::collect(core::iter::IntoIterator::into_iter([$($v,)*]))
   };
 }
@@ -1,10 +1,24 @@
+mod arith;
 mod ast;
+mod builder;
+mod distance;
+#[cfg(feature = "std")]
 mod ir;
 mod macros;
+#[cfg(feature = "std")]
 mod nested;
+#[cfg(not(feature = "std"))]
+mod spin;
+mod symbol;
 mod token;
 
-pub use ast::{Ast, AstBox, AstVisitor};
+pub use arith::{eval_binary, eval_unary, OverflowMode};
+pub use ast::{ArgList, Ast, AstBox, AstTransformer, AstVisitor, Program, TryAstVisitor, Type};
+pub use builder::AstBuilder;
+pub use distance::{closest, edit_distance};
+#[cfg(feature = "std")]
 pub use ir::{FunDefRc, FunDefWeak, FunctionDef, Inst, InstBox, ValRc, Value};
+#[cfg(feature = "std")]
 pub use nested::NestedMap;
+pub use symbol::Symbol;
 pub use token::{Keyword, Operator, Token};
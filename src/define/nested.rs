@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::collections::hash_map::{Entry, HashMap};
 use std::hash::Hash;
 
 /// Nested hash map (for implementing environments).
@@ -41,7 +42,7 @@ where
   /// Panics when popping from root map.
   pub fn pop(&mut self) {
     self.cur = self.cur.as_mut().unwrap().outer.take();
-    assert!(!self.cur.is_none(), "popping from the root map")
+    assert!(self.cur.is_some(), "popping from the root map")
   }
 
   /// Adds item to the current map,
@@ -52,37 +53,62 @@ where
 
   /// Gets item by the specific key,
   /// returns `None` if key not found.
-  pub fn get(&self, k: &K, recursive: bool) -> Option<&V> {
+  pub fn get<Q>(&self, k: &Q, recursive: bool) -> Option<&V>
+  where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+  {
     self.cur.as_ref().unwrap().get(k, recursive)
   }
 
   /// Gets item recursively by the specific key,
   /// returns `None` if key not found.
-  pub fn get_rec(&self, k: &K) -> Option<&V> {
+  pub fn get_rec<Q>(&self, k: &Q) -> Option<&V>
+  where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+  {
     self.get(k, true)
   }
 
   /// Updates item by the specific key,
   /// returns true if the update operation takes effect.
-  pub fn update(&mut self, k: &K, v: V, recursive: bool) -> bool {
+  pub fn update<Q>(&mut self, k: &Q, v: V, recursive: bool) -> bool
+  where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+  {
     self.cur.as_mut().unwrap().update(k, v, recursive)
   }
 
   /// Updates item recursively by the specific key,
   /// returns true if the update operation takes effect.
-  pub fn update_rec(&mut self, k: &K, v: V) -> bool {
+  pub fn update_rec<Q>(&mut self, k: &Q, v: V) -> bool
+  where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+  {
     self.update(k, v, true)
   }
 
   /// Updates item recursively by the specific key,
   /// stops updating when the predicate returns false,
   /// returns true if the update operation takes effect.
-  pub fn update_until<F>(&mut self, k: &K, v: V, predicate: F) -> bool
+  pub fn update_until<Q, F>(&mut self, k: &Q, v: V, predicate: F) -> bool
   where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
     F: Fn(&HashMap<K, V>) -> bool,
   {
     self.cur.as_mut().unwrap().update_until(k, v, predicate)
   }
+
+  /// Returns an iterator over keys of all maps,
+  /// from the current map to the root map, shadowed keys included.
+  pub fn keys(&self) -> impl Iterator<Item = &K> {
+    std::iter::successors(self.cur.as_deref(), |node| node.outer.as_deref())
+      .flat_map(|node| node.map.keys())
+  }
 }
 
 impl<K, V> Node<K, V>
@@ -91,53 +117,72 @@ where
 {
   /// Implementation of `add` method of `NestedMap`.
   fn add(&mut self, k: K, v: V) -> bool {
-    if !self.map.contains_key(&k) {
-      self.map.insert(k, v);
-      true
-    } else {
-      false
+    match self.map.entry(k) {
+      Entry::Vacant(e) => {
+        e.insert(v);
+        true
+      }
+      Entry::Occupied(_) => false,
     }
   }
 
   /// Implementation of `get` method of `NestedMap`.
-  fn get(&self, k: &K, recursive: bool) -> Option<&V> {
-    if let Some(v) = self.map.get(k) {
-      Some(v)
-    } else if self.outer.is_some() && recursive {
-      self.outer.as_ref().unwrap().get(k, recursive)
-    } else {
-      None
+  fn get<Q>(&self, k: &Q, recursive: bool) -> Option<&V>
+  where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+  {
+    match (self.map.get(k), &self.outer) {
+      (Some(v), _) => Some(v),
+      (None, Some(outer)) if recursive => outer.get(k, recursive),
+      _ => None,
     }
   }
 
   /// Implementation of `update` method of `NestedMap`.
-  fn update(&mut self, k: &K, v: V, recursive: bool) -> bool {
-    if let Some(val) = self.map.get_mut(k) {
-      *val = v;
-      true
-    } else if self.outer.is_some() && recursive {
-      self.outer.as_mut().unwrap().update(k, v, recursive)
-    } else {
-      false
+  fn update<Q>(&mut self, k: &Q, v: V, recursive: bool) -> bool
+  where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+  {
+    match (self.map.get_mut(k), &mut self.outer) {
+      (Some(val), _) => {
+        *val = v;
+        true
+      }
+      (None, Some(outer)) if recursive => outer.update(k, v, recursive),
+      _ => false,
     }
   }
 
   /// Implementation of `update_until` method of `NestedMap`.
-  fn update_until<F>(&mut self, k: &K, v: V, predicate: F) -> bool
+  fn update_until<Q, F>(&mut self, k: &Q, v: V, predicate: F) -> bool
   where
+    K: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
     F: Fn(&HashMap<K, V>) -> bool,
   {
     if let Some(val) = self.map.get_mut(k) {
       *val = v;
       true
-    } else if self.outer.is_some() && !predicate(&self.map) {
-      self.outer.as_mut().unwrap().update_until(k, v, predicate)
     } else {
-      false
+      match &mut self.outer {
+        Some(outer) if !predicate(&self.map) => outer.update_until(k, v, predicate),
+        _ => false,
+      }
     }
   }
 }
 
+impl<K, V> Default for NestedMap<K, V>
+where
+  K: Hash + Eq,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::NestedMap;
@@ -158,7 +203,10 @@ mod test {
     assert_eq!(nested.get_rec(&"test3"), Some(&3));
     assert!(nested.update(&"test3", 4, false));
     assert_eq!(nested.get_rec(&"test3"), Some(&4));
-    assert!(!nested.add(&"test1", 12));
+    assert!(!nested.add("test1", 12));
+    let mut keys: Vec<_> = nested.keys().copied().collect();
+    keys.sort();
+    assert_eq!(keys, ["test1", "test1", "test2", "test3"]);
     nested.pop();
     assert_eq!(nested.get_rec(&"test3"), None);
   }
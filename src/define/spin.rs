@@ -0,0 +1,65 @@
+use core::cell::UnsafeCell;
+use core::hint::spin_loop;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Spin lock, the replacement of `std::sync::Mutex` in `no_std` environments.
+pub struct SpinLock<T> {
+  /// Whether the lock is held.
+  locked: AtomicBool,
+  /// Data protected by the lock.
+  data: UnsafeCell<T>,
+}
+
+// the data can only be accessed by the holder of the lock
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+  /// Creates a new spin lock.
+  pub const fn new(data: T) -> Self {
+    Self {
+      locked: AtomicBool::new(false),
+      data: UnsafeCell::new(data),
+    }
+  }
+
+  /// Acquires the lock, spins until it is available.
+  pub fn lock(&self) -> SpinLockGuard<'_, T> {
+    while self
+      .locked
+      .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+      .is_err()
+    {
+      spin_loop();
+    }
+    SpinLockGuard { lock: self }
+  }
+}
+
+/// Guard of `SpinLock`, releases the lock when dropped.
+pub struct SpinLockGuard<'a, T> {
+  /// The lock being held.
+  lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    // safe since the lock is held
+    unsafe { &*self.lock.data.get() }
+  }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+  fn deref_mut(&mut self) -> &mut T {
+    // safe since the lock is held exclusively
+    unsafe { &mut *self.lock.data.get() }
+  }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+  fn drop(&mut self) {
+    self.lock.locked.store(false, Ordering::Release);
+  }
+}
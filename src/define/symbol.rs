@@ -0,0 +1,121 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use super::spin::SpinLock as Mutex;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+/// Interned string (for identifiers).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+/// String interner.
+struct Interner {
+  /// Ids of all interned strings.
+  ids: BTreeMap<&'static str, u32>,
+  /// All interned strings.
+  strs: Vec<&'static str>,
+}
+
+/// Global string interner shared by all lexers and parsers,
+/// it lives as long as the process and is never cleared.
+static INTERNER: Mutex<Interner> = Mutex::new(Interner {
+  ids: BTreeMap::new(),
+  strs: Vec::new(),
+});
+
+impl Symbol {
+  /// Interns the specific string and returns its symbol.
+  ///
+  /// Interned strings are leaked and live until the end of the process,
+  /// even after all lexers, parsers and ASTs using them are dropped.
+  /// Each distinct string is only stored once, but a long-running process
+  /// that interns an unbounded number of distinct strings (for example,
+  /// a server parsing untrusted programs) keeps growing.
+  pub fn new(s: &str) -> Self {
+    let mut interner = interner();
+    if let Some(id) = interner.ids.get(s) {
+      return Symbol(*id);
+    }
+    let s: &'static str = Box::leak(s.to_string().into_boxed_str());
+    let id = interner.strs.len() as u32;
+    interner.strs.push(s);
+    interner.ids.insert(s, id);
+    Symbol(id)
+  }
+
+  /// Gets the original string of the symbol.
+  pub fn as_str(&self) -> &'static str {
+    interner().strs[self.0 as usize]
+  }
+}
+
+impl From<&str> for Symbol {
+  fn from(s: &str) -> Self {
+    Symbol::new(s)
+  }
+}
+
+impl PartialEq<&str> for Symbol {
+  fn eq(&self, other: &&str) -> bool {
+    self.as_str() == *other
+  }
+}
+
+impl fmt::Display for Symbol {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.as_str())
+  }
+}
+
+impl fmt::Debug for Symbol {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:?}", self.as_str())
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self.as_str())
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Symbol {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = <alloc::borrow::Cow<str>>::deserialize(deserializer)?;
+    Ok(Symbol::new(&s))
+  }
+}
+
+/// Locks and returns the global string interner.
+#[cfg(feature = "std")]
+fn interner() -> impl core::ops::DerefMut<Target = Interner> {
+  INTERNER.lock().unwrap()
+}
+
+/// Locks and returns the global string interner.
+#[cfg(not(feature = "std"))]
+fn interner() -> impl core::ops::DerefMut<Target = Interner> {
+  INTERNER.lock()
+}
+
+#[cfg(test)]
+mod test {
+  use super::Symbol;
+
+  #[test]
+  fn test_symbol() {
+    let a = Symbol::new("count");
+    let b = Symbol::new("count");
+    let c = Symbol::new("counter");
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(a.as_str(), "count");
+    assert_eq!(c.to_string(), "counter");
+  }
+}
@@ -1,16 +1,28 @@
+use crate::define::Symbol;
+use alloc::string::String;
+use core::fmt;
+
 /// Keywords of `first-step`.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Keyword {
-  If, Else, Return,
+  If, Else, Return, Const,
+  Switch, Case, Default,
+  Int, Bool,
+  Do, While,
 }
 
 /// Operators of `first-step`.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operator {
-  Add, Sub, Mul, Div, Mod,
+  Add, Sub, Mul, Div, Mod, Pow,
   Less, LessEq, Eq, NotEq,
   LAnd, LOr, LNot,
   Define, Assign,
+  /// Left shift, only generated by optimizations on the IR.
+  Shl,
+  /// Arrow of return type annotations, not an operation.
+  Arrow,
 }
 
 /// Token generated by `Lexer`.
@@ -19,13 +31,98 @@ pub enum Token {
   /// End of file
   End,
   /// Identifier
-  Id(String),
+  Id(Symbol),
   /// Integer literal
-  Int(i32),
+  Int(i64),
   /// Keyword
   Key(Keyword),
   /// Operator
   Op(Operator),
   /// Other characters
   Other(char),
+  /// Comment, with the text after the comment character
+  Comment(String),
+  /// Documentation comment (like `## text`), with the text after
+  /// the doubled comment character
+  DocComment(String),
+}
+
+impl fmt::Display for Keyword {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let key = match self {
+      Keyword::If => "if",
+      Keyword::Else => "else",
+      Keyword::Return => "return",
+      Keyword::Const => "const",
+      Keyword::Switch => "switch",
+      Keyword::Case => "case",
+      Keyword::Default => "default",
+      Keyword::Int => "int",
+      Keyword::Bool => "bool",
+      Keyword::Do => "do",
+      Keyword::While => "while",
+    };
+    write!(f, "{}", key)
+  }
+}
+
+impl fmt::Display for Operator {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let op = match self {
+      Operator::Add => "+",
+      Operator::Sub => "-",
+      Operator::Mul => "*",
+      Operator::Div => "/",
+      Operator::Mod => "%",
+      Operator::Pow => "**",
+      Operator::Less => "<",
+      Operator::LessEq => "<=",
+      Operator::Eq => "==",
+      Operator::NotEq => "!=",
+      Operator::LAnd => "&&",
+      Operator::LOr => "||",
+      Operator::LNot => "!",
+      Operator::Define => ":=",
+      Operator::Assign => "=",
+      Operator::Shl => "<<",
+      Operator::Arrow => "->",
+    };
+    write!(f, "{}", op)
+  }
+}
+
+impl fmt::Display for Token {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Token::End => write!(f, "end of file"),
+      Token::Id(id) => write!(f, "{}", id),
+      Token::Int(val) => write!(f, "{}", val),
+      Token::Key(key) => write!(f, "{}", key),
+      Token::Op(op) => write!(f, "{}", op),
+      Token::Other(c) => write!(f, "{}", c),
+      Token::Comment(text) => write!(f, "#{}", text),
+      Token::DocComment(text) => write!(f, "##{}", text),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Keyword, Operator, Token};
+  use crate::define::Symbol;
+
+  #[test]
+  fn test_display() {
+    assert_eq!(format!("{}", Operator::NotEq), "!=");
+    assert_eq!(Operator::LOr.to_string(), "||");
+    assert_eq!(Operator::Define.to_string(), ":=");
+    assert_eq!(Token::Key(Keyword::Return).to_string(), "return");
+    assert_eq!(Token::Op(Operator::LessEq).to_string(), "<=");
+    assert_eq!(Token::Id(Symbol::new("x")).to_string(), "x");
+    assert_eq!(Token::Int(42).to_string(), "42");
+    assert_eq!(Token::Other('{').to_string(), "{");
+    assert_eq!(Token::Comment(" note".into()).to_string(), "# note");
+    assert_eq!(Token::DocComment(" doc".into()).to_string(), "## doc");
+    assert_eq!(Token::End.to_string(), "end of file");
+  }
 }
@@ -0,0 +1,86 @@
+use crate::define::{Ast, AstBox, Symbol};
+
+/// Extracts documentations of all documented function definitions
+/// in the specific program, for documentation generation.
+///
+/// Returns pairs of function names and doc texts, in the order of
+/// definitions. Doc comments (`##`) are only kept if the lexer
+/// keeps comments.
+pub fn extract_docs(asts: &[AstBox]) -> Vec<(Symbol, String)> {
+  asts
+    .iter()
+    .filter_map(|ast| match ast.as_ref() {
+      Ast::FunDef {
+        name,
+        doc: Some(doc),
+        ..
+      } => Some((*name, doc.clone())),
+      _ => None,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::extract_docs;
+  use crate::define::{Program, Symbol};
+  use crate::dump::pretty::Printer;
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use crate::pass::{dce::DeadCodeEliminator, simplify::Simplifier};
+  use std::io::Cursor;
+
+  /// Parses the specific program with comments kept.
+  fn parse(program: &str) -> Program {
+    let mut lexer = Lexer::new(Cursor::new(program.to_string()));
+    lexer.set_keep_comments(true);
+    Parser::new(lexer).parse_program().unwrap()
+  }
+
+  #[test]
+  fn test_extract_docs() {
+    let program = r#"
+## adds two numbers
+add(a, b) { return a + b }
+
+# not a doc comment
+sub(a, b) { return a - b }
+
+## returns `a` to the power of `b`
+##
+## `b` must be non-negative
+pow(a, b) {
+  ## just a comment
+  return a ** b
+} ## also a comment
+
+## dangling
+"#;
+    let asts = parse(program);
+    let docs = extract_docs(&asts);
+    assert_eq!(
+      docs,
+      [
+        (Symbol::new("add"), "adds two numbers".to_string()),
+        (
+          Symbol::new("pow"),
+          "returns `a` to the power of `b`\n\n`b` must be non-negative".to_string()
+        ),
+      ]
+    );
+    // doc comments survive formatting
+    let text = Printer::new().print_program(&asts);
+    assert!(text.starts_with("## adds two numbers\nadd(a, b) {"));
+    assert!(text.contains("\n## returns `a` to the power of `b`\n##\n## `b` must"));
+    assert!(text.contains("  ## just a comment\n"));
+    assert!(parse(&text) == asts);
+    // doc comments survive optimization passes
+    let optimized: Program = asts
+      .iter()
+      .map(|ast| DeadCodeEliminator::new().eliminate(&Simplifier::new().simplify(ast)))
+      .collect();
+    assert_eq!(extract_docs(&optimized), docs);
+    // no docs if comments are skipped
+    let asts = Parser::from_str(program).parse_program().unwrap();
+    assert!(extract_docs(&asts).is_empty());
+  }
+}
@@ -0,0 +1,198 @@
+use crate::define::{AstBox, AstVisitor, Operator, Symbol, Type};
+
+/// Graphviz DOT exporter of ASTs.
+///
+/// Each AST node is exported as a labeled vertex `nN`,
+/// and each parent-child relationship is exported as an edge.
+#[derive(Default)]
+pub struct DotExporter {
+  /// Declarations of all vertices.
+  nodes: String,
+  /// Declarations of all edges.
+  edges: String,
+  /// Number of vertices.
+  count: usize,
+}
+
+impl DotExporter {
+  /// Creates a new DOT exporter.
+  pub fn new() -> Self {
+    Self {
+      nodes: String::new(),
+      edges: String::new(),
+      count: 0,
+    }
+  }
+
+  /// Exports the specific ASTs as a DOT graph.
+  pub fn export(&mut self, asts: &[AstBox]) -> String {
+    for ast in asts {
+      self.visit(ast);
+    }
+    let graph = format!("digraph ast {{\n{}{}}}\n", self.nodes, self.edges);
+    *self = Self::new();
+    graph
+  }
+
+  /// Adds a new vertex with the specific label, returns its id.
+  fn node(&mut self, label: String) -> usize {
+    let id = self.count;
+    self.count += 1;
+    self.nodes += &format!("  n{} [label=\"{}\"];\n", id, escape(&label));
+    id
+  }
+
+  /// Visits the specific child and adds an edge to it.
+  fn child(&mut self, parent: usize, ast: &AstBox, label: Option<&str>) {
+    let child = self.visit(ast);
+    self.edges += &match label {
+      Some(label) => format!("  n{} -> n{} [label=\"{}\"];\n", parent, child, label),
+      None => format!("  n{} -> n{};\n", parent, child),
+    };
+  }
+}
+
+impl AstVisitor for DotExporter {
+  type Result = usize;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    let args: Vec<_> = args.iter().map(|arg| arg.as_str()).collect();
+    let id = self.node(format!("FunDef {}({})", name, args.join(", ")));
+    self.child(id, body, None);
+    id
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    let id = self.node("Block".to_string());
+    for stmt in stmts {
+      self.child(id, stmt, None);
+    }
+    id
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    let id = self.node(format!("Define {}", name));
+    self.child(id, expr, None);
+    id
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    let id = self.node(format!("Assign {}", name));
+    self.child(id, expr, None);
+    id
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    let id = self.node("If".to_string());
+    self.child(id, cond, Some("cond"));
+    self.child(id, then, Some("then"));
+    if let Some(else_then) = else_then {
+      self.child(id, else_then, Some("else"));
+    }
+    id
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    let id = self.node("Switch".to_string());
+    self.child(id, scrutinee, Some("value"));
+    for (val, body) in cases {
+      self.child(id, body, Some(&format!("case {}", val)));
+    }
+    if let Some(default) = default {
+      self.child(id, default, Some("default"));
+    }
+    id
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    let id = self.node("DoWhile".to_string());
+    self.child(id, body, Some("body"));
+    self.child(id, cond, Some("cond"));
+    id
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    let id = self.node("Return".to_string());
+    if let Some(expr) = expr {
+      self.child(id, expr, None);
+    }
+    id
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    let id = self.node(format!("Binary {:?}", op));
+    self.child(id, lhs, Some("lhs"));
+    self.child(id, rhs, Some("rhs"));
+    id
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    let id = self.node(format!("Unary {:?}", op));
+    self.child(id, opr, None);
+    id
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    let id = self.node(format!("FunCall {}", name));
+    for arg in args {
+      self.child(id, arg, None);
+    }
+    id
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    self.node(format!("Int {}", val))
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    self.node(format!("Id {}", val))
+  }
+
+  fn visit_comment(&mut self, text: &str, _: &bool) -> Self::Result {
+    self.node(format!("Comment #{}", text))
+  }
+}
+
+/// Escapes the specific label for DOT strings.
+fn escape(label: &str) -> String {
+  label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+  use super::DotExporter;
+  use crate::front::parser::Parser;
+
+  #[test]
+  fn test_dot() {
+    let program = "main() { x := 1 + y return -x }";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next().unwrap();
+    let dot = DotExporter::new().export(&[ast]);
+    assert!(dot.starts_with("digraph ast {\n"));
+    assert!(dot.ends_with("}\n"));
+    // root function and its body
+    assert!(dot.contains("  n0 [label=\"FunDef main()\"];\n"));
+    assert!(dot.contains("  n1 [label=\"Block\"];\n"));
+    assert!(dot.contains("  n0 -> n1;\n"));
+    // scalar payloads and labeled edges
+    assert!(dot.contains("  n3 [label=\"Binary Add\"];\n"));
+    assert!(dot.contains("  n3 -> n4 [label=\"lhs\"];\n"));
+    assert!(dot.contains("  n4 [label=\"Int 1\"];\n"));
+    assert!(dot.contains("  n5 [label=\"Id y\"];\n"));
+    assert!(dot.contains("  n7 [label=\"Unary Sub\"];\n"));
+  }
+}
@@ -0,0 +1,5 @@
+pub mod docs;
+pub mod dot;
+pub mod pretty;
+pub mod sexpr;
+pub mod tokens;
@@ -0,0 +1,379 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Symbol, Type};
+use crate::front::parser::binding_power;
+
+/// Number of spaces of each indentation level.
+const INDENT_WIDTH: usize = 2;
+
+/// Pretty printer, reconstructs source code from ASTs.
+#[derive(Default)]
+pub struct Printer {
+  /// Current indentation level.
+  indent: usize,
+}
+
+impl Printer {
+  /// Creates a new printer.
+  pub fn new() -> Self {
+    Self { indent: 0 }
+  }
+
+  /// Prints the specific program (list of function definitions).
+  ///
+  /// Function definitions are separated by empty lines, comments
+  /// before a function definition are kept next to it,
+  /// and documentations are printed as doc comments.
+  pub fn print_program(&mut self, asts: &[AstBox]) -> String {
+    let mut text = String::new();
+    let mut after_fundef = false;
+    for ast in asts {
+      let is_fundef = !matches!(ast.as_ref(), Ast::Comment { .. });
+      if !is_trailing(ast) || !after_fundef {
+        if after_fundef {
+          text.push('\n');
+        }
+        after_fundef = is_fundef;
+      }
+      if let Ast::FunDef { doc: Some(doc), .. } = ast.as_ref() {
+        for line in doc.split('\n') {
+          text += format!("## {}", line).trim_end();
+          text.push('\n');
+        }
+      }
+      self.print_line(&mut text, ast);
+    }
+    if text.is_empty() {
+      text.push('\n');
+    }
+    text
+  }
+
+  /// Gets the indentation of the current level.
+  fn indentation(&self) -> String {
+    " ".repeat(self.indent * INDENT_WIDTH)
+  }
+
+  /// Prints the specific AST as a new line of the text,
+  /// trailing comments are appended to the last line instead.
+  fn print_line(&mut self, text: &mut String, ast: &AstBox) {
+    let line = self.visit(ast);
+    if is_trailing(ast) && text.ends_with('\n') {
+      text.pop();
+      *text += &format!(" {}\n", line);
+    } else {
+      *text += &format!("{}{}\n", self.indentation(), line);
+    }
+  }
+
+  /// Prints a case of switch statements with the specific label,
+  /// statements of the body are printed without braces.
+  fn print_case(&mut self, label: &str, body: &AstBox) -> String {
+    let mut text = format!("{}{}:\n", self.indentation(), label);
+    self.indent += 1;
+    if let Ast::Block { stmts } = body.as_ref() {
+      for stmt in stmts {
+        self.print_line(&mut text, stmt);
+      }
+    }
+    self.indent -= 1;
+    text
+  }
+
+  /// Prints an operand of a binary expression, adds parentheses
+  /// if the operand is a binary expression for which `need_paren`
+  /// returns `true` (with its left and right binding powers).
+  fn print_operand(&mut self, ast: &AstBox, need_paren: impl Fn(u8, u8) -> bool) -> String {
+    let text = self.visit(ast);
+    match ast.as_ref() {
+      Ast::Binary { op, .. } => {
+        let (l, r) = binding_power(op).unwrap();
+        if need_paren(l, r) {
+          format!("({})", text)
+        } else {
+          text
+        }
+      }
+      Ast::Assign { .. } => format!("({})", text),
+      _ => text,
+    }
+  }
+}
+
+impl AstVisitor for Printer {
+  type Result = String;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    arg_types: &[Option<Type>],
+    ret_type: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    let args: Vec<_> = args
+      .iter()
+      .zip(arg_types)
+      .map(|(arg, ty)| match ty {
+        Some(ty) => format!("{}: {}", arg, ty),
+        None => arg.to_string(),
+      })
+      .collect();
+    let ret_type = ret_type.map_or(String::new(), |ty| format!(" -> {}", ty));
+    format!("{}({}){} {}", name, args.join(", "), ret_type, self.visit(body))
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    if stmts.is_empty() {
+      return "{}".to_string();
+    }
+    let outer = self.indentation();
+    self.indent += 1;
+    let mut text = "{\n".to_string();
+    for stmt in stmts {
+      self.print_line(&mut text, stmt);
+    }
+    self.indent -= 1;
+    text + &outer + "}"
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    format!("{} := {}", name, self.visit(expr))
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    format!("{} = {}", name, self.visit(expr))
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    let text = format!("if {} {}", self.visit(cond), self.visit(then));
+    match else_then {
+      Some(else_then) => format!("{} else {}", text, self.visit(else_then)),
+      None => text,
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    let mut text = format!("switch {} {{\n", self.visit(scrutinee));
+    let outer = self.indentation();
+    self.indent += 1;
+    for (val, body) in cases {
+      text += &self.print_case(&format!("case {}", val), body);
+    }
+    if let Some(default) = default {
+      text += &self.print_case("default", default);
+    }
+    self.indent -= 1;
+    text + &outer + "}"
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    format!("do {} while {}", self.visit(body), self.visit(cond))
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    match expr {
+      Some(expr) => format!("return {}", self.visit(expr)),
+      None => "return".to_string(),
+    }
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    let (l, r) = binding_power(op).unwrap();
+    // the left operand would be split by this operator
+    // if it does not bind its right operand tighter,
+    // relational operations can not be chained without parentheses
+    let is_rel = matches!(op, Operator::Less | Operator::LessEq);
+    let lhs = self.print_operand(lhs, |_, lhs_r| lhs_r <= l || (is_rel && lhs_r == r));
+    // the right operand would not be parsed as a whole
+    // if it binds its left operand looser
+    let rhs = self.print_operand(rhs, |rhs_l, _| rhs_l < r);
+    format!("{} {} {}", lhs, op, rhs)
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    let opr = self.print_operand(opr, |_, _| true);
+    // separate adjacent operators, since they would be lexed
+    // as a single operator (like `--`)
+    if opr.starts_with(['-', '!']) {
+      format!("{} {}", op, opr)
+    } else {
+      format!("{}{}", op, opr)
+    }
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    let args: Vec<_> = args.iter().map(|arg| self.visit(arg)).collect();
+    format!("{}({})", name, args.join(", "))
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    val.to_string()
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    val.to_string()
+  }
+
+  fn visit_comment(&mut self, text: &str, _: &bool) -> Self::Result {
+    format!("#{}", text)
+  }
+}
+
+/// Checks if the specific AST is a trailing comment.
+fn is_trailing(ast: &AstBox) -> bool {
+  matches!(ast.as_ref(), Ast::Comment { trailing: true, .. })
+}
+
+#[cfg(test)]
+mod test {
+  use super::Printer;
+  use crate::define::{AstBox, AstBuilder, AstVisitor, Operator::{self, *}, Program};
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use std::io::Cursor;
+
+  /// Parses the specific program.
+  fn parse(program: &str) -> Program {
+    Parser::from_str(program).parse_program().unwrap()
+  }
+
+  /// Parses and prints the specific program.
+  fn reprint(program: &str) -> String {
+    Printer::new().print_program(&parse(program))
+  }
+
+  #[test]
+  fn test_printer() {
+    let program = r#"
+      # comment
+      f(a,b){x:=(a+b)*(a-(b-1)) if x<0|| !(a==b)&&b {return -(-x)} else if a {x=f(a-1,b)}else{} return x}
+      main() { return (1 - 2) - 3 * (4 / 5) % 6 }
+    "#;
+    let expected = r#"f(a, b) {
+  x := (a + b) * (a - (b - 1))
+  if x < 0 || !(a == b) && b {
+    return - -x
+  } else if a {
+    x = f(a - 1, b)
+  } else {}
+  return x
+}
+
+main() {
+  return 1 - 2 - 3 * (4 / 5) % 6
+}
+"#;
+    let text = reprint(program);
+    assert_eq!(text, expected);
+    // printing the output again should not change it
+    assert_eq!(reprint(&text), expected);
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let corpus = [
+      include_str!("../../examples/fact.fstep"),
+      include_str!("../../examples/fib.fstep"),
+      "f(a, b, c) { return -a * b }",
+      "f(a, b, c) { return a || b && c }",
+      "f(a, b, c) { return (a || b) && c }",
+      "f(a, b, c) { return -(a * b) + !(b < c) }",
+      "f(a, b, c) { return a - (b - c) - (a - b) - c }",
+      "f(a, b, c) { return a / (b * c) % (a % b) }",
+      "f(a, b, c) { return ! !a == (b != c) != (- -a <= -b) }",
+      "f(a, b, c) { return (a < b) <= c || a < (b <= c) }",
+      "f(a, b, c) { return f(-1, (a), f(b, c, a) * (a + b)) }",
+      "f(a, b, c) { if a { if b { return c } } else if !c { a = 1 } else {} return 0 }",
+      "f() {} g() { x := f() y := -x z := x <= y || y < x }",
+      "f(a: int, b, c: bool) -> bool { return c || a < b }",
+      "f(a) { do { a = a - 1 do {} while !a } while 0 < a return a }",
+      "f(a) { switch a + 1 { case 1: a = 2 case -1: case 2: return a default: } return 0 }",
+    ];
+    for source in &corpus {
+      let asts = parse(source);
+      let text = Printer::new().print_program(&asts);
+      assert!(parse(&text) == asts, "round trip failed:\n{}\n{}", source, text);
+    }
+    // assignment expressions
+    let b = AstBuilder::new();
+    let ast = b.assign("a", b.assign("b", b.binary(Mul, b.assign("c", b.int(1)), b.int(2))));
+    let text = Printer::new().visit(&ast);
+    assert_eq!(text, "a = b = (c = 1) * 2");
+    let mut parser = Parser::from_str(&text);
+    parser.set_assign_expr(true);
+    assert!(parser.parse_next_stmt().unwrap() == ast);
+    // random expressions
+    let mut state = 0x9e3779b97f4a7c15u64;
+    for _ in 0..500 {
+      let expr = random_expr(&mut state, 4);
+      let b = AstBuilder::new();
+      let asts = vec![b.fundef("f", ["a", "b"], b.block([b.ret(expr)]))];
+      let text = Printer::new().print_program(&asts);
+      assert!(parse(&text) == asts, "round trip failed:\n{}", text);
+    }
+  }
+
+  #[test]
+  fn test_comments() {
+    let parse = |program: &str| {
+      let mut lexer = Lexer::new(Cursor::new(program.to_string()));
+      lexer.set_keep_comments(true);
+      Parser::new(lexer).parse_program().unwrap()
+    };
+    let program = r#"
+# entry
+main() { # starts here
+  # note
+  x := 1
+  switch x { case 1: x = 2 # two
+  }
+  return x # result
+  # done
+} # end
+"#;
+    let expected = r#"# entry
+main() { # starts here
+  # note
+  x := 1
+  switch x {
+    case 1:
+      x = 2 # two
+  }
+  return x # result
+  # done
+} # end
+"#;
+    let asts = parse(program);
+    let text = Printer::new().print_program(&asts);
+    assert_eq!(text, expected);
+    assert!(parse(&text) == asts);
+    // comments are skipped by default
+    assert!(!reprint(program).contains('#'));
+  }
+
+  /// Generates a random expression of the specific maximum depth.
+  fn random_expr(state: &mut u64, depth: usize) -> AstBox {
+    const OPS: &[Operator] = &[Add, Sub, Mul, Div, Mod, Pow, Less, LessEq, Eq, NotEq, LAnd, LOr];
+    // xorshift generator, for reproducible pseudo-random trees
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    let b = AstBuilder::new();
+    // kind of the node, and the choice inside the kind
+    let (kind, rand) = (*state % 8, (*state >> 8) as usize);
+    match kind {
+      _ if depth == 0 => b.id(["a", "b"][rand % 2]),
+      0 => b.int((rand % 100) as i64),
+      1 => b.unary([Sub, LNot][rand % 2].clone(), random_expr(state, depth - 1)),
+      2 => b.funcall("f", [random_expr(state, depth - 1), random_expr(state, depth - 1)]),
+      _ => {
+        let op = OPS[rand % OPS.len()].clone();
+        b.binary(op, random_expr(state, depth - 1), random_expr(state, depth - 1))
+      }
+    }
+  }
+}
@@ -0,0 +1,143 @@
+use crate::define::{AstBox, AstVisitor, Operator, Symbol, Type};
+
+/// S-expression printer, renders ASTs as Lisp-style lists,
+/// like `(fundef f (args x) (block (return (+ x 1))))`.
+#[derive(Default)]
+pub struct SExprPrinter;
+
+impl SExprPrinter {
+  /// Creates a new S-expression printer.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Prints the specific program, one function definition per line.
+  pub fn print_program(&mut self, asts: &[AstBox]) -> String {
+    asts.iter().map(|ast| self.visit(ast) + "\n").collect()
+  }
+}
+
+impl AstVisitor for SExprPrinter {
+  type Result = String;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    let args = list("args", args.iter().map(|arg| arg.to_string()));
+    list("fundef", [name.to_string(), args, self.visit(body)])
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    let stmts: Vec<_> = stmts.iter().map(|stmt| self.visit(stmt)).collect();
+    list("block", stmts)
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    list("define", [name.to_string(), self.visit(expr)])
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    list("assign", [name.to_string(), self.visit(expr)])
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    let mut elems = vec![self.visit(cond), self.visit(then)];
+    if let Some(else_then) = else_then {
+      elems.push(self.visit(else_then));
+    }
+    list("if", elems)
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    let mut elems = vec![self.visit(scrutinee)];
+    for (val, body) in cases {
+      let body = self.visit(body);
+      elems.push(list("case", [val.to_string(), body]));
+    }
+    if let Some(default) = default {
+      let default = self.visit(default);
+      elems.push(list("default", [default]));
+    }
+    list("switch", elems)
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    list("do-while", [self.visit(body), self.visit(cond)])
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    list("return", expr.iter().map(|expr| self.visit(expr)).collect::<Vec<_>>())
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    list(&op.to_string(), [self.visit(lhs), self.visit(rhs)])
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    list(&op.to_string(), [self.visit(opr)])
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    let mut elems = vec![name.to_string()];
+    elems.extend(args.iter().map(|arg| self.visit(arg)));
+    list("call", elems)
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    val.to_string()
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    val.to_string()
+  }
+
+  fn visit_comment(&mut self, text: &str, _: &bool) -> Self::Result {
+    list("comment", [format!("{:?}", text)])
+  }
+}
+
+/// Creates a list of the specific head and elements.
+fn list(head: &str, elems: impl IntoIterator<Item = String>) -> String {
+  let mut text = format!("({}", head);
+  for elem in elems {
+    text += " ";
+    text += &elem;
+  }
+  text + ")"
+}
+
+#[cfg(test)]
+mod test {
+  use super::SExprPrinter;
+  use crate::define::AstVisitor;
+  use crate::front::parser::Parser;
+
+  #[test]
+  fn test_sexpr() {
+    let program = r#"
+      func(x) { if x == 10 { return f(x, -1) } else { y := !x * 2 } }
+      main() {}
+    "#;
+    let mut parser = Parser::from_str(program);
+    let asts = parser.parse_program().unwrap();
+    let expected = "(fundef func (args x) (block (if (== x 10) \
+                    (block (return (call f x (- 1)))) \
+                    (block (define y (* (! x) 2))))))\n\
+                    (fundef main (args) (block))\n";
+    assert_eq!(SExprPrinter::new().print_program(&asts), expected);
+    let mut parser = Parser::from_str("if a { b = 1 }");
+    let stmt = parser.parse_next_stmt().unwrap();
+    assert_eq!(SExprPrinter::new().visit(&stmt), "(if a (block (assign b 1)))");
+  }
+}
@@ -0,0 +1,75 @@
+use crate::define::Token;
+use crate::front::lexer::{LexError, Lexer};
+use std::io::Read;
+
+/// Lexes the whole input and dumps the token stream as a JSON array.
+///
+/// Each token is an object with its kind, payload and position,
+/// like `{"kind": "Int", "value": 1, "line": 1, "col": 6}`.
+pub fn dump_tokens(reader: impl Read) -> Result<String, LexError> {
+  let mut lexer = Lexer::new(reader);
+  let mut tokens = Vec::new();
+  loop {
+    let token = lexer.next_token()?;
+    let (line, col) = lexer.pos();
+    let (kind, value) = match token {
+      Token::End => break,
+      Token::Id(id) => ("Id", string(id.as_str())),
+      Token::Int(val) => ("Int", val.to_string()),
+      Token::Key(key) => ("Key", string(&format!("{:?}", key))),
+      Token::Op(op) => ("Op", string(&format!("{:?}", op))),
+      Token::Other(c) => ("Other", string(&c.to_string())),
+      Token::Comment(text) => ("Comment", string(&text)),
+      Token::DocComment(text) => ("DocComment", string(&text)),
+    };
+    tokens.push(format!(
+      "  {{\"kind\": \"{}\", \"value\": {}, \"line\": {}, \"col\": {}}}",
+      kind, value, line, col
+    ));
+  }
+  if tokens.is_empty() {
+    Ok("[]\n".to_string())
+  } else {
+    Ok(format!("[\n{}\n]\n", tokens.join(",\n")))
+  }
+}
+
+/// Converts the specific string to a JSON string literal.
+fn string(s: &str) -> String {
+  let mut text = "\"".to_string();
+  for c in s.chars() {
+    match c {
+      '"' => text += "\\\"",
+      '\\' => text += "\\\\",
+      c if c.is_control() => text += &format!("\\u{:04x}", c as u32),
+      c => text.push(c),
+    }
+  }
+  text + "\""
+}
+
+#[cfg(test)]
+mod test {
+  use super::dump_tokens;
+  use std::io::Cursor;
+
+  #[test]
+  fn test_dump_tokens() {
+    let json = dump_tokens(Cursor::new("x := 1\nif \"")).unwrap();
+    let expected = r#"[
+  {"kind": "Id", "value": "x", "line": 1, "col": 1},
+  {"kind": "Op", "value": "Define", "line": 1, "col": 3},
+  {"kind": "Int", "value": 1, "line": 1, "col": 6},
+  {"kind": "Key", "value": "If", "line": 2, "col": 1},
+  {"kind": "Other", "value": "\"", "line": 2, "col": 4}
+]
+"#;
+    assert_eq!(json, expected);
+    // the output is valid JSON
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value[2]["kind"], "Int");
+    assert_eq!(value[2]["value"], 1);
+    assert_eq!(dump_tokens(Cursor::new("# comment")).unwrap(), "[]\n");
+    assert!(dump_tokens(Cursor::new("x := 99999999999999999999")).is_err());
+  }
+}
@@ -0,0 +1,44 @@
+use crate::define::{eval_binary, eval_unary, Ast, AstBox, OverflowMode};
+
+/// Evaluates the specific constant expression, which only consists of
+/// integer literals and operators, returns the error message if failed.
+///
+/// Constants are evaluated as 64-bit integers, overflow is reported as an error.
+pub fn eval_const(ast: &AstBox) -> Result<i64, &'static str> {
+  match ast.as_ref() {
+    Ast::Int { val } => Ok(*val),
+    Ast::Binary { op, lhs, rhs } => {
+      let (lhs, rhs) = (eval_const(lhs)?, eval_const(rhs)?);
+      eval_binary(op, lhs, rhs, OverflowMode::Checked)
+    }
+    Ast::Unary { op, opr } => eval_unary(op, eval_const(opr)?, OverflowMode::Checked),
+    Ast::Id { .. } => Err("variables can not be used in constant expressions"),
+    Ast::FunCall { .. } => Err("functions can not be called in constant expressions"),
+    _ => Err("invalid constant expression"),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::eval_const;
+  use crate::define::AstBuilder;
+  use crate::define::Operator::*;
+
+  #[test]
+  fn test_eval_const() {
+    let b = AstBuilder::new();
+    let expr = b.binary(Add, b.binary(Mul, b.int(2), b.int(3)), b.unary(Sub, b.int(-1)));
+    assert_eq!(eval_const(&expr), Ok(7));
+    assert_eq!(eval_const(&b.binary(LAnd, b.int(0), b.int(1))), Ok(0));
+    let expr = b.binary(Add, b.int(1), b.id("x"));
+    assert_eq!(eval_const(&expr), Err("variables can not be used in constant expressions"));
+    let expr = b.unary(LNot, b.funcall("f", []));
+    assert_eq!(eval_const(&expr), Err("functions can not be called in constant expressions"));
+    assert_eq!(eval_const(&b.binary(Div, b.int(1), b.int(0))), Err("division by zero"));
+    let expr = b.binary(Add, b.int(i32::MAX.into()), b.int(1));
+    assert_eq!(eval_const(&expr), Ok(2147483648));
+    let expr = b.binary(Add, b.int(i64::MAX), b.int(1));
+    assert_eq!(eval_const(&expr), Err("integer overflow"));
+    assert_eq!(eval_const(&b.ret(b.int(1))), Err("invalid constant expression"));
+  }
+}
@@ -0,0 +1,76 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+
+/// Span of source code, a range of characters in a line.
+/// Line and column numbers start from 1, as reported by `Lexer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+  /// Line number.
+  pub line: u32,
+  /// Column number of the first character.
+  pub col: u32,
+  /// Number of characters.
+  pub len: u32,
+}
+
+impl Span {
+  /// Creates a new span.
+  pub fn new(line: u32, col: u32, len: u32) -> Self {
+    Self { line, col, len }
+  }
+}
+
+/// Renders a diagnostic of the specific span in the source code like `rustc`,
+/// prints the message followed by the offending line with the span underlined.
+///
+/// Only the message and the position are rendered if the line does not exist.
+pub fn render(source: &str, span: Span, message: &str) -> String {
+  let mut text = format!("error: {}\n", message);
+  let line = match source.lines().nth(span.line.saturating_sub(1) as usize) {
+    Some(line) if span.line > 0 => line,
+    _ => return text + &format!(" --> line {}, col {}\n", span.line, span.col),
+  };
+  let line_num = span.line.to_string();
+  let gutter = " ".repeat(line_num.len());
+  text += &format!("{}--> line {}, col {}\n", gutter, span.line, span.col);
+  text += &format!("{} |\n", gutter);
+  text += &format!("{} | {}\n", line_num, line);
+  // keep tabs before the span, so that the underline is aligned
+  let prefix: String = line
+    .chars()
+    .take(span.col.saturating_sub(1) as usize)
+    .map(|c| if c == '\t' { '\t' } else { ' ' })
+    .collect();
+  let underline = "^".repeat(span.len.max(1) as usize);
+  text + &format!("{} | {}{}\n", gutter, prefix, underline)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{render, Span};
+
+  #[test]
+  fn test_render() {
+    let source = "f(x) {\n  y := x +* 1\n  return y\n}\n";
+    let expected = r#"error: invalid operator
+ --> line 2, col 10
+  |
+2 |   y := x +* 1
+  |          ^^
+"#;
+    assert_eq!(render(source, Span::new(2, 10, 2), "invalid operator"), expected);
+    // tabs & wide line numbers
+    let source = "\n".repeat(9) + "\tx := 1 @ 2";
+    let expected = r#"error: invalid character
+  --> line 10, col 9
+   |
+10 | 	x := 1 @ 2
+   | 	       ^
+"#;
+    assert_eq!(render(&source, Span::new(10, 9, 0), "invalid character"), expected);
+    // lines out of range
+    let expected = "error: unexpected end of file\n --> line 5, col 1\n";
+    assert_eq!(render("x", Span::new(5, 1, 1), "unexpected end of file"), expected);
+  }
+}
@@ -1,40 +1,148 @@
-use crate::define::{Keyword, Operator, Token};
+use super::source::ByteSource;
+use crate::define::{closest, Keyword, Operator, Symbol, Token};
+use alloc::string::{String, ToString};
+use alloc::{boxed::Box, vec};
+use core::fmt;
 use phf::phf_map;
-use std::io::Read;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
 /// Lexer for `first-step` language.
-pub struct Lexer<T: Read> {
+pub struct Lexer<T: ByteSource> {
   reader: T,
+  /// Bytes read from the reader but not yet scanned.
+  buf: Box<[u8]>,
+  /// Position of the next byte in the buffer.
+  buf_pos: usize,
+  /// Number of valid bytes in the buffer.
+  buf_len: usize,
   last_char: Option<char>,
+  /// Whether the last character is converted from `\r`.
+  after_cr: bool,
+  /// Line number of the last character.
+  line: u32,
+  /// Column number of the last character.
+  col: u32,
+  /// Position of the last token.
+  token_pos: (u32, u32),
+  /// Character that starts line comments.
+  comment_char: char,
+  /// Width of tab stops in columns.
+  tab_width: u32,
+  /// Whether comments are returned as tokens.
+  keep_comments: bool,
+  /// Whether integer literals are 64-bit.
+  int64: bool,
 }
 
+/// Error information of `Lexer`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+  pub message: String,
+  pub line: u32,
+  pub col: u32,
+}
+
+impl fmt::Display for LexError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} at line {}, col {}", self.message, self.line, self.col)
+  }
+}
+
+impl core::error::Error for LexError {}
+
 /// `Result` for token handlers of `Lexer`.
-pub type Result = std::result::Result<Token, String>;
+pub type Result = core::result::Result<Token, LexError>;
 
-impl<T: Read> Lexer<T> {
-  /// Creates a new `Lexer` object from the specific `Read` object.
+/// Size of the internal buffer of `Lexer` in bytes.
+const BUFFER_SIZE: usize = 8192;
+
+impl<T: ByteSource> Lexer<T> {
+  /// Creates a new `Lexer` object from the specific byte source.
   pub fn new(reader: T) -> Self {
     Self {
-      reader: reader,
+      reader,
+      buf: vec![0; BUFFER_SIZE].into_boxed_slice(),
+      buf_pos: 0,
+      buf_len: 0,
       last_char: Some(' '),
+      after_cr: false,
+      line: 1,
+      col: 0,
+      token_pos: (1, 0),
+      comment_char: '#',
+      tab_width: 1,
+      keep_comments: false,
+      int64: false,
     }
   }
 
+  /// Sets the character that starts line comments, defaults to `#`.
+  ///
+  /// The character is no longer treated as a token,
+  /// so it should not be used by operators.
+  pub fn set_comment_char(&mut self, comment_char: char) {
+    self.comment_char = comment_char;
+  }
+
+  /// Sets the width of tab stops in columns (1 by default), a tab advances
+  /// the column to the next tab stop. Widths less than 1 are treated as 1.
+  ///
+  /// Columns are no longer character indices if the width is not 1,
+  /// so they should not be used with `diagnostic::render`.
+  pub fn set_tab_width(&mut self, tab_width: u32) {
+    self.tab_width = tab_width.max(1);
+  }
+
+  /// Sets whether comments are returned as `Token::Comment` tokens
+  /// instead of being skipped, defaults to `false`.
+  ///
+  /// Comments starting with the doubled comment character (like `##`)
+  /// are returned as `Token::DocComment` tokens.
+  pub fn set_keep_comments(&mut self, keep_comments: bool) {
+    self.keep_comments = keep_comments;
+  }
+
+  /// Sets whether integer literals are 64-bit, defaults to `false`,
+  /// should match the mode of the interpreter.
+  ///
+  /// Literals that do not fit in the integer type are reported as errors.
+  pub fn set_int64(&mut self, int64: bool) {
+    self.int64 = int64;
+  }
+
+  /// Checks if integer literals are 64-bit.
+  pub fn int64(&self) -> bool {
+    self.int64
+  }
+
+  /// Gets the position (line and column) of the last token.
+  pub fn pos(&self) -> (u32, u32) {
+    self.token_pos
+  }
+
   /// Gets the next token from file.
   pub fn next_token(&mut self) -> Result {
-    // skip spaces
-    while self.last_char.map_or(false, |c| c.is_whitespace()) {
-      self.next_char()?;
+    // skip spaces and comments
+    loop {
+      while self.last_char.is_some_and(|c| c.is_whitespace()) {
+        self.next_char()?;
+      }
+      if self.last_char != Some(self.comment_char) {
+        break;
+      }
+      if self.keep_comments {
+        self.token_pos = (self.line, self.col);
+        return self.handle_comment();
+      }
+      self.skip_comment()?;
     }
+    self.token_pos = (self.line, self.col);
     // check the last character
     if let Some(c) = self.last_char {
-      if c == '#' {
-        // skip comments
-        self.handle_comment()
-      } else if c.is_alphabetic() || c == '_' {
+      if is_xid_start(c) || c == '_' {
         // id or keyword
         self.handle_id()
-      } else if c.is_numeric() {
+      } else if c.is_ascii_digit() {
         // integer literal
         self.handle_integer()
       } else if is_operator_char(c) {
@@ -52,34 +160,107 @@ impl<T: Read> Lexer<T> {
   }
 
   /// Reads a character from file.
-  fn next_char(&mut self) -> std::result::Result<(), String> {
-    // NOTE: UTF-8 characters will not be handled here.
-    let mut single_char = [0];
-    self.last_char = (self
-      .reader
-      .read(&mut single_char)
-      .map_err(|err| format!("{}", err))?
-      != 0)
-      .then(|| single_char[0] as char);
+  fn next_char(&mut self) -> core::result::Result<(), LexError> {
+    // update the position
+    match self.last_char {
+      Some('\n') => {
+        self.line += 1;
+        self.col = 1;
+      }
+      // advance to the next tab stop
+      Some('\t') => {
+        let width = self.tab_width;
+        self.col = (self.col.saturating_sub(1) / width + 1) * width + 1;
+      }
+      _ => self.col += 1,
+    }
+    // read the first byte of the UTF-8 sequence
+    let mut byte = self.next_byte()?;
+    // `\r\n` and `\r` are line breaks, `\r` has been converted to `\n`
+    if self.after_cr && byte == Some(b'\n') {
+      byte = self.next_byte()?;
+    }
+    self.after_cr = byte == Some(b'\r');
+    let first = match byte {
+      Some(byte) => byte,
+      None => {
+        self.last_char = None;
+        return Ok(());
+      }
+    };
+    // fast path for ASCII characters
+    if first.is_ascii() {
+      self.last_char = Some(if self.after_cr { '\n' } else { first as char });
+      return Ok(());
+    }
+    // read the rest bytes and decode
+    let len = match first.leading_ones() {
+      0 => 1,
+      n @ 2..=4 => n as usize,
+      _ => 0,
+    };
+    let mut bytes = [first, 0, 0, 0];
+    for byte in bytes.iter_mut().take(len).skip(1) {
+      match self.next_byte()? {
+        Some(b) => *byte = b,
+        None => break,
+      }
+    }
+    self.last_char = core::str::from_utf8(&bytes[..len])
+      .ok()
+      .and_then(|s| s.chars().next());
+    if self.last_char.is_none() {
+      self.last_char = Some(char::REPLACEMENT_CHARACTER);
+      return Err(self.error("invalid UTF-8 character", (self.line, self.col)));
+    }
+    // skip the byte-order mark at the start of input
+    if self.last_char == Some('\u{feff}') && (self.line, self.col) == (1, 1) {
+      self.col = 0;
+      return self.next_char();
+    }
     Ok(())
   }
 
+  /// Reads a byte from the buffer, refills the buffer if it is exhausted,
+  /// returns `None` if reaches EOF.
+  fn next_byte(&mut self) -> core::result::Result<Option<u8>, LexError> {
+    if self.buf_pos == self.buf_len {
+      self.buf_len = match self.reader.read(&mut self.buf) {
+        Ok(len) => len,
+        Err(err) => return Err(self.error(&err, (self.line, self.col))),
+      };
+      self.buf_pos = 0;
+      if self.buf_len == 0 {
+        return Ok(None);
+      }
+    }
+    let byte = self.buf[self.buf_pos];
+    self.buf_pos += 1;
+    Ok(Some(byte))
+  }
+
+  /// Creates a new error at the specific position.
+  fn error(&self, message: &str, pos: (u32, u32)) -> LexError {
+    LexError {
+      message: message.to_string(),
+      line: pos.0,
+      col: pos.1,
+    }
+  }
+
   /// Handles identifiers or keywords.
   fn handle_id(&mut self) -> Result {
     // read to string
     let mut id = String::new();
-    while self
-      .last_char
-      .map_or(false, |c| c.is_alphanumeric() || c == '_')
-    {
-      id.push(self.last_char.unwrap());
+    while let Some(c) = self.last_char.filter(|c| is_xid_continue(*c)) {
+      id.push(c);
       self.next_char()?;
     }
     // check if string is keyword
     if let Some(keyword) = parse_keyword(&id) {
       Ok(Token::Key(keyword))
     } else {
-      Ok(Token::Id(id))
+      Ok(Token::Id(Symbol::new(&id)))
     }
   }
 
@@ -87,63 +268,126 @@ impl<T: Read> Lexer<T> {
   fn handle_integer(&mut self) -> Result {
     // read to string
     let mut num = String::new();
-    while self.last_char.map_or(false, |c| c.is_numeric()) {
-      num.push(self.last_char.unwrap());
+    while let Some(c) = self.last_char.filter(char::is_ascii_digit) {
+      num.push(c);
       self.next_char()?;
     }
-    // convert to integer
+    // convert to integer, which fails only if the literal overflows
+    let max = if self.int64 { i64::MAX } else { i32::MAX.into() };
     num
-      .parse::<i32>()
-      .map(|i| Token::Int(i))
-      .map_err(|_| String::from("invalid integer literal"))
+      .parse::<i64>()
+      .ok()
+      .filter(|val| *val <= max)
+      .map(Token::Int)
+      .ok_or_else(|| self.error("integer literal too large", self.token_pos))
   }
 
   /// Handles operators.
   fn handle_operator(&mut self) -> Result {
     // read to string
     let mut op = String::new();
-    while self.last_char.map_or(false, |c| is_operator_char(c)) {
-      op.push(self.last_char.unwrap());
+    while let Some(c) = self.last_char.filter(|c| is_operator_char(*c)) {
+      op.push(c);
       self.next_char()?;
     }
+    // a single ':' is not an operator, it is used by switch statements
+    if op == ":" {
+      return Ok(Token::Other(':'));
+    }
     // check if is a valid operator
     parse_operator(&op)
-      .map(|op| Token::Op(op))
-      .ok_or(String::from("invalid operator"))
+      .map(Token::Op)
+      .ok_or_else(|| self.error("invalid operator", self.token_pos))
   }
 
-  /// Handles comment.
+  /// Handles the comment in the current line.
   fn handle_comment(&mut self) -> Result {
-    // skip the current line
-    while self.last_char.map_or(false, |c| c != '\r' && c != '\n') {
+    // eat the comment character
+    self.next_char()?;
+    let is_doc = self.last_char == Some(self.comment_char);
+    if is_doc {
       self.next_char()?;
     }
-    // return the next token
-    self.next_token()
+    let mut text = String::new();
+    while let Some(c) = self.last_char.filter(|c| *c != '\n') {
+      text.push(c);
+      self.next_char()?;
+    }
+    Ok(if is_doc {
+      Token::DocComment(text)
+    } else {
+      Token::Comment(text)
+    })
+  }
+
+  /// Skips the comment in the current line.
+  fn skip_comment(&mut self) -> core::result::Result<(), LexError> {
+    while self.last_char.is_some_and(|c| c != '\n') {
+      self.next_char()?;
+    }
+    Ok(())
+  }
+}
+
+impl<'a> Lexer<&'a [u8]> {
+  /// Creates a new `Lexer` object from the specific string.
+  // borrows the string, which is not possible for `FromStr`
+  #[allow(clippy::should_implement_trait)]
+  pub fn from_str(source: &'a str) -> Self {
+    Self::new(source.as_bytes())
+  }
+}
+
+impl<T: ByteSource> Iterator for Lexer<T> {
+  type Item = Result;
+
+  /// Gets the next token, returns `None` if reaches EOF.
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.next_token() {
+      Ok(Token::End) => None,
+      token => Some(token),
+    }
   }
 }
 
 /// Checks whether the specific character may appear in the operator.
 fn is_operator_char(c: char) -> bool {
-  "+-*/%<=!&|:".contains(c)
+  "+-*/%<=!&|:>".contains(c)
 }
 
+/// All keywords of `first-step`.
+static KEYWORDS: phf::Map<&'static str, Keyword> = phf_map! {
+  "if" => Keyword::If,
+  "else" => Keyword::Else,
+  "return" => Keyword::Return,
+  "const" => Keyword::Const,
+  "switch" => Keyword::Switch,
+  "case" => Keyword::Case,
+  "default" => Keyword::Default,
+  "int" => Keyword::Int,
+  "bool" => Keyword::Bool,
+  "do" => Keyword::Do,
+  "while" => Keyword::While,
+};
+
 /// Parses keyword from the specific string.
 fn parse_keyword(s: &str) -> Option<Keyword> {
-  static KEYWORDS: phf::Map<&'static str, Keyword> = phf_map! {
-    "if" => Keyword::If,
-    "else" => Keyword::Else,
-    "return" => Keyword::Return,
-  };
   KEYWORDS.get(s).cloned()
 }
 
+/// Finds the keyword which the specific identifier is probably a misspelling of,
+/// returns `None` if no keyword is within edit distance 1.
+pub fn similar_keyword(s: &str) -> Option<Keyword> {
+  closest(s, KEYWORDS.keys().copied(), 1).and_then(parse_keyword)
+}
+
 /// Parses operator from the specific string.
 fn parse_operator(s: &str) -> Option<Operator> {
   static OPERATORS: phf::Map<&'static str, Operator> = phf_map! {
     "+" => Operator::Add,
     "-" => Operator::Sub,
     "*" => Operator::Mul,
+    "**" => Operator::Pow,
     "/" => Operator::Div,
     "%" => Operator::Mod,
     "<" => Operator::Less,
@@ -155,6 +399,7 @@ fn parse_operator(s: &str) -> Option<Operator> {
     "!" => Operator::LNot,
     ":=" => Operator::Define,
     "=" => Operator::Assign,
+    "->" => Operator::Arrow,
   };
   OPERATORS.get(s).cloned()
 }
@@ -162,7 +407,7 @@ fn parse_operator(s: &str) -> Option<Operator> {
 /// Unit tests for `Lexer`.
 #[cfg(test)]
 mod tests {
-  use super::{Keyword, Lexer, Operator, Token::*};
+  use super::{Keyword, LexError, Lexer, Operator, Symbol, Token::*};
   use std::io::Cursor;
 
   #[test]
@@ -179,18 +424,18 @@ mod tests {
       "#,
     );
     let mut lexer = Lexer::new(buf);
-    assert_eq!(lexer.next_token(), Ok(Id("func".to_string())));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("func"))));
     assert_eq!(lexer.next_token(), Ok(Other('(')));
-    assert_eq!(lexer.next_token(), Ok(Id("x".to_string())));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
     assert_eq!(lexer.next_token(), Ok(Other(')')));
     assert_eq!(lexer.next_token(), Ok(Other('{')));
     assert_eq!(lexer.next_token(), Ok(Key(Keyword::If)));
-    assert_eq!(lexer.next_token(), Ok(Id("x".to_string())));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
     assert_eq!(lexer.next_token(), Ok(Op(Operator::Eq)));
     assert_eq!(lexer.next_token(), Ok(Int(10)));
     assert_eq!(lexer.next_token(), Ok(Other('{')));
     assert_eq!(lexer.next_token(), Ok(Key(Keyword::Return)));
-    assert_eq!(lexer.next_token(), Ok(Id("x".to_string())));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
     assert_eq!(lexer.next_token(), Ok(Op(Operator::Add)));
     assert_eq!(lexer.next_token(), Ok(Int(11)));
     assert_eq!(lexer.next_token(), Ok(Other('}')));
@@ -199,4 +444,234 @@ mod tests {
     assert_eq!(lexer.next_token(), Ok(End));
     assert_eq!(lexer.next_token(), Ok(End));
   }
+
+  #[test]
+  fn test_iterator() {
+    let lexer = Lexer::new(Cursor::new("x := f(1) # comment"));
+    let tokens: Vec<_> = lexer.collect();
+    assert_eq!(
+      tokens,
+      [
+        Ok(Id(Symbol::new("x"))),
+        Ok(Op(Operator::Define)),
+        Ok(Id(Symbol::new("f"))),
+        Ok(Other('(')),
+        Ok(Int(1)),
+        Ok(Other(')')),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_intern() {
+    let tokens: Vec<_> = Lexer::new(Cursor::new("counter + counter")).collect();
+    let (lhs, rhs) = match (&tokens[0], &tokens[2]) {
+      (Ok(Id(lhs)), Ok(Id(rhs))) => (*lhs, *rhs),
+      _ => panic!("expected identifiers"),
+    };
+    assert_eq!(lhs, rhs);
+    assert_eq!(lhs.as_str(), "counter");
+  }
+
+  #[test]
+  fn test_error_pos() {
+    let mut lexer = Lexer::new(Cursor::new("x := 1\n  y := x & 1"));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    assert_eq!(lexer.pos(), (1, 1));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Define)));
+    assert_eq!(lexer.pos(), (1, 3));
+    assert_eq!(lexer.next_token(), Ok(Int(1)));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("y"))));
+    assert_eq!(lexer.pos(), (2, 3));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Define)));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    let err = LexError {
+      message: "invalid operator".to_string(),
+      line: 2,
+      col: 10,
+    };
+    assert_eq!(lexer.next_token(), Err(err));
+  }
+
+  #[test]
+  fn test_line_endings() {
+    let mut lexer = Lexer::new(Cursor::new("# comment\r\nx\r\n\r\n  y # comment\rz\r\r\n w"));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    assert_eq!(lexer.pos(), (2, 1));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("y"))));
+    assert_eq!(lexer.pos(), (4, 3));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("z"))));
+    assert_eq!(lexer.pos(), (5, 1));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("w"))));
+    assert_eq!(lexer.pos(), (7, 2));
+    assert_eq!(lexer.next_token(), Ok(End));
+  }
+
+  #[test]
+  fn test_bom() {
+    // gets all tokens and their positions
+    let lex = |src: String| {
+      let mut lexer = Lexer::new(Cursor::new(src));
+      let mut tokens = Vec::new();
+      while let Ok(token) = lexer.next_token() {
+        tokens.push((lexer.pos(), token.to_string()));
+        if token == End {
+          break;
+        }
+      }
+      tokens
+    };
+    let src = "x := 1 # comment\n  y";
+    assert_eq!(lex(format!("\u{feff}{}", src)), lex(src.to_string()));
+    // byte-order marks are only skipped at the start of input
+    let mut lexer = Lexer::new(Cursor::new("x\u{feff}"));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    assert_eq!(lexer.next_token(), Ok(Other('\u{feff}')));
+  }
+
+  #[test]
+  fn test_int_overflow() {
+    let err = LexError {
+      message: "integer literal too large".to_string(),
+      line: 2,
+      col: 8,
+    };
+    // integer literals are 32-bit by default
+    let src = "2147483647\n  x := 2147483648 + 1";
+    let mut lexer = Lexer::new(Cursor::new(src));
+    assert_eq!(lexer.next_token(), Ok(Int(i32::MAX.into())));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Define)));
+    assert_eq!(lexer.next_token(), Err(err.clone()));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Add)));
+    // 64-bit integer literals
+    let src = "9223372036854775807\n  x := 9223372036854775808 + 1";
+    let mut lexer = Lexer::new(Cursor::new(src));
+    lexer.set_int64(true);
+    assert_eq!(lexer.next_token(), Ok(Int(i64::MAX)));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Define)));
+    assert_eq!(lexer.next_token(), Err(err));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Add)));
+  }
+
+  #[test]
+  fn test_tab_width() {
+    let src = "\tx\n\t\ty\na\tz  \tw";
+    let cols = |tab_width| {
+      let mut lexer = Lexer::new(Cursor::new(src));
+      lexer.set_tab_width(tab_width);
+      let mut cols = Vec::new();
+      while lexer.next_token() != Ok(End) {
+        cols.push(lexer.pos().1);
+      }
+      cols
+    };
+    assert_eq!(cols(1), [2, 3, 1, 3, 7]);
+    assert_eq!(cols(4), [5, 9, 1, 5, 9]);
+    assert_eq!(cols(8), [9, 17, 1, 9, 17]);
+  }
+
+  #[test]
+  fn test_unicode() {
+    let mut lexer = Lexer::new(Cursor::new("计数 := 数_1 + ü\n  λ"));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("计数"))));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Define)));
+    assert_eq!(lexer.pos(), (1, 4));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("数_1"))));
+    assert_eq!(lexer.next_token(), Ok(Op(Operator::Add)));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("ü"))));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("λ"))));
+    assert_eq!(lexer.pos(), (2, 3));
+    assert_eq!(lexer.next_token(), Ok(End));
+    let mut lexer = Lexer::new(Cursor::new(&b"x \xff"[..]));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("x"))));
+    assert!(lexer.next_token().is_err());
+  }
+
+  #[test]
+  fn test_large_input() {
+    // multi-byte characters are split across boundaries of the buffer
+    let line = |i| format!("λ{}(x) {{ return x * {} + 1 }} # 注释\n", i, i);
+    let source: String = (0..20000).map(line).collect();
+    assert!(source.len() > super::BUFFER_SIZE * 50);
+    let mut lexer = Lexer::new(Cursor::new(source));
+    let (mut count, mut ints) = (0, 0i64);
+    for token in lexer.by_ref() {
+      match token.unwrap() {
+        Int(val) => ints += val,
+        Id(id) if id.as_str().starts_with('λ') && count % 12 == 0 => {
+          assert_eq!(id.as_str(), format!("λ{}", count / 12))
+        }
+        Id(id) => assert_eq!(id, "x"),
+        _ => (),
+      }
+      count += 1;
+    }
+    assert_eq!(count, 20000 * 12);
+    assert_eq!(ints, (0..20000).map(|i| i + 1).sum::<i64>());
+    assert_eq!(lexer.pos(), (20001, 1));
+  }
+
+  #[test]
+  fn test_comment_char() {
+    let mut lexer = Lexer::new(Cursor::new("a ; comment # b\n# c"));
+    lexer.set_comment_char(';');
+    let tokens: Vec<_> = lexer.collect();
+    assert_eq!(
+      tokens,
+      [
+        Ok(Id(Symbol::new("a"))),
+        Ok(Other('#')),
+        Ok(Id(Symbol::new("c"))),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_keep_comments() {
+    let mut lexer = Lexer::new(Cursor::new("a # note\r\n  #\n## doc #\nb"));
+    lexer.set_keep_comments(true);
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("a"))));
+    assert_eq!(lexer.next_token(), Ok(Comment(" note".into())));
+    assert_eq!(lexer.pos(), (1, 3));
+    assert_eq!(lexer.next_token(), Ok(Comment("".into())));
+    assert_eq!(lexer.pos(), (2, 3));
+    assert_eq!(lexer.next_token(), Ok(DocComment(" doc #".into())));
+    assert_eq!(lexer.next_token(), Ok(Id(Symbol::new("b"))));
+    assert_eq!(lexer.next_token(), Ok(End));
+  }
+
+  #[test]
+  fn test_pow() {
+    let tokens: Vec<_> = Lexer::new(Cursor::new("a ** b a * *b")).collect();
+    assert_eq!(
+      tokens,
+      [
+        Ok(Id(Symbol::new("a"))),
+        Ok(Op(Operator::Pow)),
+        Ok(Id(Symbol::new("b"))),
+        Ok(Id(Symbol::new("a"))),
+        Ok(Op(Operator::Mul)),
+        Ok(Op(Operator::Mul)),
+        Ok(Id(Symbol::new("b"))),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_id_chars() {
+    let tokens: Vec<_> = Lexer::new(Cursor::new("my_var2 _x1 1x 12_")).collect();
+    assert_eq!(
+      tokens,
+      [
+        Ok(Id(Symbol::new("my_var2"))),
+        Ok(Id(Symbol::new("_x1"))),
+        Ok(Int(1)),
+        Ok(Id(Symbol::new("x"))),
+        Ok(Int(12)),
+        Ok(Id(Symbol::new("_"))),
+      ]
+    );
+  }
 }
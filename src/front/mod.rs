@@ -1,2 +1,6 @@
+pub mod consteval;
+pub mod diagnostic;
 pub mod lexer;
 pub mod parser;
+pub mod source;
+pub mod symbols;
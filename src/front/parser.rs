@@ -1,12 +1,40 @@
-use super::lexer::Lexer;
+use super::consteval::eval_const;
+use super::lexer::{similar_keyword, LexError, Lexer};
+use super::source::ByteSource;
 use crate::define;
-use define::{Ast, AstBox, Keyword, Operator, Token};
-use std::io::Read;
+use alloc::string::{String, ToString};
+use alloc::{boxed::Box, collections::VecDeque, format, vec::Vec};
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::ControlFlow;
+use define::{ArgList, Ast, AstBox, Keyword, Operator, Program, Symbol, Token, Type};
 
 /// Parser for `first-step` language.
-pub struct Parser<T: Read> {
+pub struct Parser<T: ByteSource> {
   lexer: Lexer<T>,
   cur_token: super::lexer::Result,
+  /// Position (line and column) of the current token.
+  cur_pos: (u32, u32),
+  /// Line number of the token before the current token.
+  prev_line: u32,
+  /// Tokens after the current token which have been read for peeking,
+  /// and their positions.
+  lookahead: VecDeque<(super::lexer::Result, (u32, u32))>,
+  /// Current nesting depth of blocks and expressions.
+  depth: usize,
+  /// Maximum nesting depth of blocks and expressions.
+  max_depth: usize,
+  /// Values of all constants defined so far.
+  consts: Vec<(Symbol, i64)>,
+  /// Whether assignments can be used as expressions.
+  assign_expr: bool,
+  /// Whether statements in the same line must be separated by `;`.
+  strict_separators: bool,
+  /// Comment tokens read but not yet attached to ASTs, and whether they are
+  /// trailing comments.
+  comments: VecDeque<(Token, bool)>,
+  /// Line number of the last token read from the lexer.
+  last_line: u32,
 }
 
 /// Error information of `Parser`.
@@ -18,46 +46,285 @@ pub enum Error {
   Error(String),
 }
 
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::End => write!(f, "end of input"),
+      Error::Error(err) => write!(f, "{}", err),
+    }
+  }
+}
+
+impl core::error::Error for Error {}
+
+impl From<LexError> for Error {
+  fn from(err: LexError) -> Self {
+    Error::Error(err.to_string())
+  }
+}
+
 /// `Result` for parser functions of `Parser`
-pub type Result = std::result::Result<AstBox, Error>;
+pub type Result = core::result::Result<AstBox, Error>;
+
+/// Default maximum nesting depth of blocks and expressions.
+const DEFAULT_MAX_DEPTH: usize = 256;
 
-impl<T: Read> Parser<T> {
+impl<T: ByteSource> Parser<T> {
   /// Creates a new `Parser` object from the specific `Lexer`.
   pub fn new(lexer: Lexer<T>) -> Self {
     let mut parser = Self {
-      lexer: lexer,
+      lexer,
       cur_token: Ok(Token::End),
+      cur_pos: (1, 0),
+      prev_line: 0,
+      lookahead: VecDeque::new(),
+      depth: 0,
+      max_depth: DEFAULT_MAX_DEPTH,
+      consts: Vec::new(),
+      assign_expr: false,
+      strict_separators: false,
+      comments: VecDeque::new(),
+      last_line: 0,
     };
     parser.next_token();
     parser
   }
 
+  /// Sets the maximum nesting depth of blocks and expressions,
+  /// deeper constructs are reported as errors instead of overflowing the stack.
+  pub fn set_max_depth(&mut self, max_depth: usize) {
+    self.max_depth = max_depth;
+  }
+
+  /// Sets whether assignments can be used as expressions, like `a = b = 3`.
+  /// Assignment expressions yield the assigned values, and have the lowest
+  /// precedence and right associativity.
+  pub fn set_assign_expr(&mut self, assign_expr: bool) {
+    self.assign_expr = assign_expr;
+  }
+
+  /// Sets whether statements in the same line must be separated by `;`,
+  /// like `x := 1; y := 2`, instead of being parsed as run-on statements.
+  /// Statements can always be followed by an optional `;`.
+  pub fn set_strict_separators(&mut self, strict_separators: bool) {
+    self.strict_separators = strict_separators;
+  }
+
   /// Parses the next AST.
+  ///
+  /// Constant definitions before the AST are evaluated, and uses of
+  /// the constants in the following ASTs are replaced with their values.
   pub fn parse_next(&mut self) -> Result {
+    while self.is_token_key(Keyword::Const) {
+      self.parse_const()?;
+    }
+    // doc comments right before the function definition are attached to it
+    let docs = self
+      .comments
+      .iter()
+      .rev()
+      .take_while(|(token, trailing)| matches!(token, Token::DocComment(_)) && !trailing)
+      .count();
+    if self.comments.len() > docs || (docs > 0 && self.is_end()) {
+      return Ok(self.next_comment().unwrap());
+    }
     match &self.cur_token {
       Ok(Token::End) => Err(Error::End),
-      Ok(_) => self.parse_fundef(),
-      Err(err) => Err(Error::Error(err.clone())),
+      Ok(_) => {
+        let doc = self.take_doc();
+        self.parse_fundef(doc)
+      }
+      Err(err) => Err(err.clone().into()),
     }
   }
 
+  /// Gets the value of the specific constant.
+  pub fn constant(&self, name: &Symbol) -> Option<i64> {
+    self
+      .consts
+      .iter()
+      .find(|(n, _)| n == name)
+      .map(|(_, val)| *val)
+  }
+
+  /// Parses all remaining function definitions.
+  pub fn parse_program(&mut self) -> core::result::Result<Program, Error> {
+    self.collect()
+  }
+
+  /// Parses function definitions one by one and feeds them to the specific
+  /// callback, stops when reaches EOF or the callback returns `Break`.
+  ///
+  /// Only the current function definition is kept in memory, since the lexer
+  /// reads the source incrementally.
+  pub fn parse_each<F>(mut self, mut f: F) -> core::result::Result<(), Error>
+  where
+    F: FnMut(AstBox) -> ControlFlow<()>,
+  {
+    loop {
+      match self.parse_next() {
+        Ok(ast) => {
+          if f(ast).is_break() {
+            return Ok(());
+          }
+        }
+        Err(Error::End) => return Ok(()),
+        Err(err) => return Err(err),
+      }
+    }
+  }
+
+  /// Parses the next statement.
+  pub fn parse_next_stmt(&mut self) -> Result {
+    if let Some(comment) = self.next_comment() {
+      return Ok(comment);
+    }
+    match &self.cur_token {
+      Ok(Token::End) => Err(Error::End),
+      Ok(_) => {
+        let stmt = self.parse_statement()?;
+        self.parse_separator()?;
+        Ok(stmt)
+      }
+      Err(err) => Err(err.clone().into()),
+    }
+  }
+
+  /// Parses the next expression.
+  pub fn parse_next_expr(&mut self) -> Result {
+    match &self.cur_token {
+      Ok(Token::End) => Err(Error::End),
+      Ok(_) => self.parse_expr(),
+      Err(err) => Err(err.clone().into()),
+    }
+  }
+
+  /// Checks if all tokens have been consumed.
+  pub fn is_end(&self) -> bool {
+    matches!(self.cur_token, Ok(Token::End))
+  }
+
+  /// Peeks the `n`th token after the current token without consuming it,
+  /// `peek(0)` returns the current token.
+  pub fn peek(&mut self, n: usize) -> &super::lexer::Result {
+    if n == 0 {
+      return &self.cur_token;
+    }
+    while self.lookahead.len() < n {
+      let token = self.read_token();
+      self.lookahead.push_back(token);
+    }
+    &self.lookahead[n - 1].0
+  }
+
   /// Gets the next token and returns it.
   fn next_token(&mut self) {
-    self.cur_token = self.lexer.next_token();
+    self.prev_line = self.cur_pos.0;
+    (self.cur_token, self.cur_pos) = match self.lookahead.pop_front() {
+      Some(token) => token,
+      None => self.read_token(),
+    };
   }
 
-  /// Parses function definitions.
-  fn parse_fundef(&mut self) -> Result {
+  /// Reads the next token and its position from the lexer,
+  /// comments are saved for being attached to ASTs later.
+  fn read_token(&mut self) -> (super::lexer::Result, (u32, u32)) {
+    loop {
+      let token = self.lexer.next_token();
+      let pos = self.lexer.pos();
+      match token {
+        Ok(token @ (Token::Comment(_) | Token::DocComment(_))) => {
+          self.comments.push_back((token, pos.0 == self.last_line))
+        }
+        token => {
+          self.last_line = pos.0;
+          return (token, pos);
+        }
+      }
+    }
+  }
+
+  /// Takes the next saved comment as an AST,
+  /// doc comments are treated as ordinary comments.
+  fn next_comment(&mut self) -> Option<AstBox> {
+    let (token, trailing) = self.comments.pop_front()?;
+    let text = match token {
+      Token::DocComment(text) => format!("#{}", text),
+      Token::Comment(text) => text,
+      _ => panic!("not a comment"),
+    };
+    Some(Box::new(Ast::Comment { text, trailing }))
+  }
+
+  /// Takes all saved doc comments as lines of the documentation,
+  /// a leading space of each line is removed.
+  fn take_doc(&mut self) -> Option<String> {
+    let lines: Vec<_> = self
+      .comments
+      .drain(..)
+      .filter_map(|(token, _)| match token {
+        Token::DocComment(text) => Some(text.strip_prefix(' ').unwrap_or(&text).to_string()),
+        _ => None,
+      })
+      .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+  }
+
+  /// Moves all saved comments to the specific statement list.
+  fn take_comments(&mut self, stmts: &mut Vec<AstBox>) {
+    while let Some(comment) = self.next_comment() {
+      stmts.push(comment);
+    }
+  }
+
+  /// Parses constant definitions, like `const N := 2 * 3 + 1`.
+  fn parse_const(&mut self) -> core::result::Result<(), Error> {
+    // eat 'const'
+    self.next_token();
+    // get constant name
+    let name = self.expect_id()?;
+    if self.constant(&name).is_some() {
+      return Err(self.error("constant has already been defined"));
+    }
+    // check & eat ':='
+    if !self.is_token_op(Operator::Define) {
+      return Err(self.unexpected("expected ':='"));
+    }
+    self.next_token();
+    // evaluate the expression, the value must fit in the integer type of the lexer
+    let expr = self.parse_expr()?;
+    let val = eval_const(&expr).map_err(|err| self.error(err))?;
+    if !self.lexer.int64() && i32::try_from(val).is_err() {
+      return Err(self.error("integer overflow"));
+    }
+    self.consts.push((name, val));
+    Ok(())
+  }
+
+  /// Parses function definitions with the specific documentation.
+  fn parse_fundef(&mut self, doc: Option<String>) -> Result {
     // get function name
     let name = self.expect_id()?;
     // check & eat '('
     self.expect_char('(')?;
     // get formal arguments
-    let mut args = Vec::new();
+    let mut args = ArgList::new();
+    let mut arg_types = ArgList::new();
     if !self.is_token_char(')') {
       loop {
-        // get name of the current argument
-        args.push(self.expect_id()?);
+        // get name and type annotation of the current argument,
+        // constants can not be used as argument names
+        let arg = self.expect_id()?;
+        if self.constant(&arg).is_some() {
+          return Err(self.error("can not define or assign constants"));
+        }
+        args.push(arg);
+        arg_types.push(if self.is_token_char(':') {
+          self.next_token();
+          Some(self.parse_type()?)
+        } else {
+          None
+        });
         // eat ','
         if !self.is_token_char(',') {
           break;
@@ -67,55 +334,115 @@ impl<T: Read> Parser<T> {
     }
     // check & eat ')'
     self.expect_char(')')?;
+    // get return type annotation
+    let ret_type = if self.is_token_op(Operator::Arrow) {
+      self.next_token();
+      Some(self.parse_type()?)
+    } else {
+      None
+    };
     // get function body
     self.parse_block().map(|body| {
       Box::new(Ast::FunDef {
-        name: name,
-        args: args,
-        body: body,
+        name,
+        args,
+        arg_types,
+        ret_type,
+        body,
+        doc,
       })
     })
   }
 
+  /// Parses type annotations.
+  fn parse_type(&mut self) -> core::result::Result<Type, Error> {
+    let ty = match self.cur_token {
+      Ok(Token::Key(Keyword::Int)) => Type::Int,
+      Ok(Token::Key(Keyword::Bool)) => Type::Bool,
+      _ => return Err(self.unexpected("expected type")),
+    };
+    self.next_token();
+    Ok(ty)
+  }
+
   /// Parses blocks.
   fn parse_block(&mut self) -> Result {
+    self.nested(Self::parse_block_body)
+  }
+
+  /// Parses blocks, without checking the nesting depth.
+  fn parse_block_body(&mut self) -> Result {
     // check & eat '{'
     self.expect_char('{')?;
     // get statements
     let mut stmts = Vec::new();
     while !self.is_token_char('}') {
+      self.take_comments(&mut stmts);
       stmts.push(self.parse_statement()?);
+      self.parse_separator()?;
     }
+    self.take_comments(&mut stmts);
     // eat '}'
     self.next_token();
-    Ok(Box::new(Ast::Block { stmts: stmts }))
+    Ok(Box::new(Ast::Block { stmts }))
   }
 
   /// Parses statements.
   fn parse_statement(&mut self) -> Result {
     match &self.cur_token {
       Ok(Token::Id(id)) => {
-        let id = id.to_string();
+        let id = *id;
         self.parse_define_assign(id)
       }
       Ok(Token::Key(Keyword::If)) => self.parse_if_else(),
+      Ok(Token::Key(Keyword::Switch)) => self.parse_switch(),
+      Ok(Token::Key(Keyword::Do)) => self.parse_do_while(),
       Ok(Token::Key(Keyword::Return)) => self.parse_return(),
-      _ => Self::get_error("invalid statement"),
+      _ => self.get_error("invalid statement"),
+    }
+  }
+
+  /// Parses the optional `;` after statements.
+  ///
+  /// In the strict mode, statements must be followed by `;`, a new line,
+  /// or the end of the statement list.
+  fn parse_separator(&mut self) -> core::result::Result<(), Error> {
+    if self.is_token_char(';') {
+      self.next_token();
+    } else if self.strict_separators
+      && self.cur_pos.0 == self.prev_line
+      && !self.is_end()
+      && !self.is_token_char('}')
+      && !self.is_token_key(Keyword::Case)
+      && !self.is_token_key(Keyword::Default)
+    {
+      return Err(self.unexpected("expected ';' or new line between statements"));
     }
+    Ok(())
   }
 
   /// Parses define/assign statements.
-  fn parse_define_assign(&mut self, id: String) -> Result {
+  fn parse_define_assign(&mut self, id: Symbol) -> Result {
     // eat id
     self.next_token();
     // check if is a function call
     if self.is_token_char('(') {
       return self.parse_funcall(id);
     }
+    // constants can not be redefined or assigned
+    if self.constant(&id).is_some() {
+      return Err(self.error("can not define or assign constants"));
+    }
     // check if is define/assign
     let is_define = self.is_token_op(Operator::Define);
     if !is_define && !self.is_token_op(Operator::Assign) {
-      return Self::get_error("expected ':=' or '='");
+      // the identifier may be a misspelled keyword, like `retrun x`
+      return Err(match (self.unexpected("expected ':=' or '='"), similar_keyword(id.as_str())) {
+        (Error::Error(err), Some(key)) => {
+          Error::Error(format!("{}, did you mean '{}'?", err, key))
+        }
+        (err, _) => err,
+      });
     }
     self.next_token();
     // get expression
@@ -123,18 +450,21 @@ impl<T: Read> Parser<T> {
       Box::new(if is_define {
         Ast::Define {
           name: id,
-          expr: expr,
+          expr,
         }
       } else {
         Ast::Assign {
           name: id,
-          expr: expr,
+          expr,
         }
       })
     })
   }
 
   /// Parses if-else statements.
+  ///
+  /// `if a {} else if b {} else {}` is parsed as
+  /// `if a {} else { if b {} else {} }`, without the extra block.
   fn parse_if_else(&mut self) -> Result {
     // eat 'if'
     self.next_token();
@@ -144,14 +474,14 @@ impl<T: Read> Parser<T> {
     let then = self.parse_block()?;
     // check & get 'else-then' body
     Ok(Box::new(Ast::If {
-      cond: cond,
-      then: then,
+      cond,
+      then,
       else_then: if self.is_token_key(Keyword::Else) {
         // eat 'else'
         self.next_token();
-        // parse 'if' or block of 'else'
+        // parse 'if' of the 'else if' chain or block of 'else'
         Some(if self.is_token_key(Keyword::If) {
-          self.parse_if_else()
+          self.nested(Self::parse_if_else)
         } else {
           self.parse_block()
         }?)
@@ -161,50 +491,153 @@ impl<T: Read> Parser<T> {
     }))
   }
 
-  /// Parses return statements.
-  fn parse_return(&mut self) -> Result {
-    // eat 'return'
+  /// Parses switch statements, like
+  /// `switch x { case 1: y = 1 case -1: y = 2 default: y = 3 }`.
+  fn parse_switch(&mut self) -> Result {
+    // eat 'switch'
     self.next_token();
-    // get return value
-    self
-      .parse_expr()
-      .map(|expr| Box::new(Ast::Return { expr: expr }))
+    // get scrutinee
+    let scrutinee = self.parse_expr()?;
+    // check & eat '{'
+    self.expect_char('{')?;
+    // get cases and the default case
+    let mut cases: Vec<(i64, AstBox)> = Vec::new();
+    let mut default = None;
+    while !self.is_token_char('}') {
+      if self.is_token_key(Keyword::Case) {
+        // eat 'case'
+        self.next_token();
+        let val = self.parse_case_value()?;
+        if cases.iter().any(|(v, _)| *v == val) {
+          return Err(self.error("duplicate case value"));
+        }
+        let body = self.parse_case_body()?;
+        cases.push((val, body));
+      } else if self.is_token_key(Keyword::Default) {
+        if default.is_some() {
+          return Err(self.error("duplicate default case"));
+        }
+        // eat 'default'
+        self.next_token();
+        default = Some(self.parse_case_body()?);
+      } else {
+        return self.get_error("expected 'case' or 'default'");
+      }
+    }
+    // eat '}'
+    self.next_token();
+    Ok(Box::new(Ast::Switch {
+      scrutinee,
+      cases,
+      default,
+    }))
   }
 
-  /// Parses expressions.
-  fn parse_expr(&mut self) -> Result {
-    let f = |p: &mut Parser<T>| p.parse_land_expr();
-    self.parse_binary(f, &[Operator::LOr])
+  /// Parses values of cases, which are integer literals
+  /// with an optional minus sign.
+  fn parse_case_value(&mut self) -> core::result::Result<i64, Error> {
+    let neg = self.is_token_op(Operator::Sub);
+    if neg {
+      self.next_token();
+    }
+    match self.cur_token {
+      Ok(Token::Int(val)) => {
+        self.next_token();
+        Ok(if neg { -val } else { val })
+      }
+      _ => Err(self.unexpected("expected integer literal")),
+    }
   }
 
-  /// Parses logical AND expressions.
-  fn parse_land_expr(&mut self) -> Result {
-    let f = |p: &mut Parser<T>| p.parse_eq_expr();
-    self.parse_binary(f, &[Operator::LAnd])
+  /// Parses the colon and statements of cases, until the next case
+  /// or the end of the switch statement.
+  fn parse_case_body(&mut self) -> Result {
+    self.nested(|parser| {
+      parser.expect_char(':')?;
+      let mut stmts = Vec::new();
+      while !parser.is_token_char('}')
+        && !parser.is_token_key(Keyword::Case)
+        && !parser.is_token_key(Keyword::Default)
+      {
+        parser.take_comments(&mut stmts);
+        stmts.push(parser.parse_statement()?);
+        parser.parse_separator()?;
+      }
+      parser.take_comments(&mut stmts);
+      Ok(Box::new(Ast::Block { stmts }))
+    })
   }
 
-  /// Parses EQ expressions.
-  fn parse_eq_expr(&mut self) -> Result {
-    let f = |p: &mut Parser<T>| p.parse_rel_expr();
-    self.parse_binary(f, &[Operator::Eq, Operator::NotEq])
+  /// Parses do-while statements.
+  fn parse_do_while(&mut self) -> Result {
+    // eat 'do'
+    self.next_token();
+    // get body
+    let body = self.parse_block()?;
+    // check & eat 'while'
+    if !self.is_token_key(Keyword::While) {
+      return self.get_error("expected 'while'");
+    }
+    self.next_token();
+    // get condition
+    let cond = self.parse_expr()?;
+    Ok(Box::new(Ast::DoWhile { body, cond }))
   }
 
-  /// Parses relation expressions.
-  fn parse_rel_expr(&mut self) -> Result {
-    let f = |p: &mut Parser<T>| p.parse_add_expr();
-    self.parse_binary(f, &[Operator::Less, Operator::LessEq])
+  /// Parses return statements.
+  fn parse_return(&mut self) -> Result {
+    // eat 'return'
+    self.next_token();
+    // get return value if any
+    let expr = if self.has_return_value() {
+      Some(self.parse_expr()?)
+    } else {
+      None
+    };
+    Ok(Box::new(Ast::Return { expr }))
   }
 
-  /// Parses add/sub expressions.
-  fn parse_add_expr(&mut self) -> Result {
-    let f = |p: &mut Parser<T>| p.parse_mul_expr();
-    self.parse_binary(f, &[Operator::Add, Operator::Sub])
+  /// Checks if the current token starts the return value of return
+  /// statements. Return values must start on the same line as 'return',
+  /// return statements without return values are followed by a line break,
+  /// '}', keywords, define/assign statements or the end of file.
+  fn has_return_value(&mut self) -> bool {
+    if self.cur_pos.0 != self.prev_line {
+      return false;
+    }
+    match self.peek(0) {
+      Ok(Token::End) | Ok(Token::Key(_)) | Ok(Token::Other('}')) => false,
+      Ok(Token::Id(_)) => !matches!(
+        self.peek(1),
+        Ok(Token::Op(Operator::Define)) | Ok(Token::Op(Operator::Assign))
+      ),
+      _ => true,
+    }
   }
 
-  /// Parses mul/div/mod expressions.
-  fn parse_mul_expr(&mut self) -> Result {
-    let f = |p: &mut Parser<T>| p.parse_unary();
-    self.parse_binary(f, &[Operator::Mul, Operator::Div, Operator::Mod])
+  /// Parses expressions.
+  fn parse_expr(&mut self) -> Result {
+    self.nested(|parser| {
+      if parser.assign_expr && parser.peek(1) == &Ok(Token::Op(Operator::Assign)) {
+        if let Ok(Token::Id(id)) = parser.cur_token {
+          return parser.parse_assign_expr(id);
+        }
+      }
+      parser.parse_binary(0)
+    })
+  }
+
+  /// Parses assignment expressions.
+  fn parse_assign_expr(&mut self, name: Symbol) -> Result {
+    if self.constant(&name).is_some() {
+      return Err(self.error("can not define or assign constants"));
+    }
+    // eat id and '='
+    self.next_token();
+    self.next_token();
+    // get the assigned expression, which is right associative
+    let expr = self.parse_expr()?;
+    Ok(Box::new(Ast::Assign { name, expr }))
   }
 
   /// Parses unary expressions.
@@ -216,12 +649,12 @@ impl<T: Read> Parser<T> {
       // check if is a valid unary operator
       match op {
         Operator::Sub | Operator::LNot => (),
-        _ => return Self::get_error("invalid unary operator"),
+        _ => return self.get_error("invalid unary operator"),
       }
       // get operand
       self
-        .parse_expr()
-        .map(|expr| Box::new(Ast::Unary { op: op, opr: expr }))
+        .nested(Self::parse_unary)
+        .map(|expr| Box::new(Ast::Unary { op, opr: expr }))
     } else {
       self.parse_value()
     }
@@ -235,17 +668,19 @@ impl<T: Read> Parser<T> {
         let val = *int;
         self.next_token();
         // integer literal
-        Ok(Box::new(Ast::Int { val: val }))
+        Ok(Box::new(Ast::Int { val }))
       }
       Ok(Token::Id(id)) => {
         // eat id
-        let id = id.to_string();
+        let id = *id;
         self.next_token();
         // check if is a function call
         if self.is_token_char('(') {
           self.parse_funcall(id)
+        } else if let Some(val) = self.constant(&id) {
+          Ok(Box::new(Ast::Int { val }))
         } else {
-          Ok(Box::new(Ast::Id { id: id }))
+          Ok(Box::new(Ast::Id { id }))
         }
       }
       Ok(Token::Other(c)) if *c == '(' => {
@@ -257,16 +692,16 @@ impl<T: Read> Parser<T> {
         self.expect_char(')')?;
         Ok(expr)
       }
-      _ => Self::get_error("invalid value"),
+      _ => self.get_error("invalid value"),
     }
   }
 
   /// Parses function calls.
-  fn parse_funcall(&mut self, id: String) -> Result {
+  fn parse_funcall(&mut self, id: Symbol) -> Result {
     // eat '('
     self.next_token();
     // get arguments
-    let mut args = Vec::new();
+    let mut args = ArgList::new();
     if !self.is_token_char(')') {
       loop {
         // get the current argument
@@ -281,58 +716,98 @@ impl<T: Read> Parser<T> {
     // check & eat ')'
     self.expect_char(')')?;
     Ok(Box::new(Ast::FunCall {
-      name: id.to_string(),
-      args: args,
+      name: id,
+      args,
     }))
   }
 
-  /// Parses binary expression.
-  fn parse_binary<F>(&mut self, parser: F, ops: &[Operator]) -> Result
-  where
-    F: Fn(&mut Parser<T>) -> Result,
-  {
+  /// Parses binary expressions by precedence climbing,
+  /// only operators whose left binding power is at least `min_bp` are parsed.
+  ///
+  /// Chained relational operations like `a < b < c` are rejected,
+  /// unless the left-hand side is parenthesized.
+  fn parse_binary(&mut self, min_bp: u8) -> Result {
     // get left-hand side expression
-    let mut lhs = parser(self)?;
+    let mut lhs = self.parse_unary()?;
+    let mut is_rel = false;
     // get the rest things
-    loop {
-      // stop if error
-      let op = match self.is_token_ops(ops) {
-        Some(op) => op,
-        None => break,
-      };
+    while let Some((op, (lbp, rbp))) = self.binary_op() {
+      if lbp < min_bp {
+        break;
+      }
+      let op_is_rel = matches!(op, Operator::Less | Operator::LessEq);
+      if is_rel && op_is_rel {
+        return Err(self.error("chained comparison is not allowed; use && instead"));
+      }
+      is_rel = op_is_rel;
       self.next_token();
       // get right-hand side expression
-      let rhs = parser(self)?;
+      let rhs = self.parse_binary(rbp)?;
       // update lhs
-      lhs = Box::new(Ast::Binary {
-        op: op,
-        lhs: lhs,
-        rhs: rhs,
-      })
+      lhs = Box::new(Ast::Binary { op, lhs, rhs })
     }
     Ok(lhs)
   }
 
-  /// Returns a parser error.
-  fn get_error(message: &str) -> Result {
-    Err(Error::Error(message.to_string()))
+  /// Calls the specific parser function at a deeper nesting level,
+  /// returns an error if reaches the maximum nesting depth.
+  fn nested(&mut self, f: impl FnOnce(&mut Self) -> Result) -> Result {
+    if self.depth >= self.max_depth {
+      return Err(self.error("expression too deeply nested"));
+    }
+    self.depth += 1;
+    let result = f(self);
+    self.depth -= 1;
+    result
+  }
+
+  /// Returns a parser error,
+  /// or the lexer error if the current token is invalid.
+  fn get_error(&self, message: &str) -> Result {
+    Err(self.unexpected(message))
+  }
+
+  /// Creates a new parser error,
+  /// or the lexer error if the current token is invalid.
+  fn error(&self, message: &str) -> Error {
+    match &self.cur_token {
+      Err(err) => err.clone().into(),
+      Ok(_) => Error::Error(message.to_string()),
+    }
+  }
+
+  /// Creates a new parser error of an unexpected token, with the current
+  /// token and its position, like `expected '(', found ':=' at line 3, col 5`,
+  /// or the lexer error if the current token is invalid.
+  fn unexpected(&self, message: &str) -> Error {
+    match &self.cur_token {
+      Err(err) => err.clone().into(),
+      Ok(token) => {
+        let found = match token {
+          Token::End => token.to_string(),
+          _ => format!("'{}'", token),
+        };
+        let (line, col) = self.cur_pos;
+        Error::Error(format!("{}, found {} at line {}, col {}", message, found, line, col))
+      }
+    }
   }
 
   /// Expects an identifier from lexer.
-  fn expect_id(&mut self) -> std::result::Result<String, Error> {
+  fn expect_id(&mut self) -> core::result::Result<Symbol, Error> {
     if let Ok(Token::Id(id)) = &self.cur_token {
-      let id = id.to_string();
+      let id = *id;
       self.next_token();
       Ok(id)
     } else {
-      Err(Error::Error("expected identifier".to_string()))
+      Err(self.unexpected("expected identifier"))
     }
   }
 
   /// Expects the specific character from lexer.
-  fn expect_char(&mut self, c: char) -> std::result::Result<(), Error> {
+  fn expect_char(&mut self, c: char) -> core::result::Result<(), Error> {
     if !self.is_token_char(c) {
-      Err(Error::Error(format!("expected '{}'", c)))
+      Err(self.unexpected(&format!("expected '{}'", c)))
     } else {
       self.next_token();
       Ok(())
@@ -344,7 +819,7 @@ impl<T: Read> Parser<T> {
     self
       .cur_token
       .as_ref()
-      .map_or(false, |t| *t == Token::Other(c))
+      .is_ok_and(|t| *t == Token::Other(c))
   }
 
   /// Checks if the current token is the specific operator.
@@ -352,14 +827,14 @@ impl<T: Read> Parser<T> {
     self
       .cur_token
       .as_ref()
-      .map_or(false, |t| *t == Token::Op(op))
+      .is_ok_and(|t| *t == Token::Op(op))
   }
 
-  /// Checks if the current token is one of the specific operators.
-  /// Returns the operator if matched.
-  fn is_token_ops(&self, ops: &[Operator]) -> Option<Operator> {
+  /// Checks if the current token is a binary operator.
+  /// Returns the operator and its binding powers if matched.
+  fn binary_op(&self) -> Option<(Operator, (u8, u8))> {
     match &self.cur_token {
-      Ok(Token::Op(op)) if ops.iter().find(|&x| *op == *x).is_some() => Some(op.clone()),
+      Ok(Token::Op(op)) => binding_power(op).map(|bp| (op.clone(), bp)),
       _ => None,
     }
   }
@@ -369,20 +844,118 @@ impl<T: Read> Parser<T> {
     self
       .cur_token
       .as_ref()
-      .map_or(false, |t| *t == Token::Key(key))
+      .is_ok_and(|t| *t == Token::Key(key))
+  }
+}
+
+impl<'a> Parser<&'a [u8]> {
+  /// Creates a new `Parser` object from the specific string.
+  // borrows the string, which is not possible for `FromStr`
+  #[allow(clippy::should_implement_trait)]
+  pub fn from_str(source: &'a str) -> Self {
+    Self::new(Lexer::from_str(source))
+  }
+
+  /// Parses the whole program from the specific bytes.
+  ///
+  /// Never panics on any input, including invalid UTF-8, huge integers and
+  /// deeply nested constructs, so it can be used as the entry point of fuzzing.
+  pub fn try_parse_program(bytes: &'a [u8]) -> core::result::Result<Program, Error> {
+    Parser::new(Lexer::new(bytes)).parse_program()
+  }
+}
+
+#[cfg(feature = "std")]
+impl Parser<std::io::BufReader<std::fs::File>> {
+  /// Parses the whole program in the file at the specific path,
+  /// errors of opening the file are reported as parser errors.
+  pub fn parse_file(path: impl AsRef<std::path::Path>) -> core::result::Result<Program, Error> {
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)
+      .map_err(|err| Error::Error(format!("failed to open '{}': {}", path.display(), err)))?;
+    Parser::new(Lexer::new(std::io::BufReader::new(file))).parse_program()
+  }
+}
+
+/// Associativity of binary operators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assoc {
+  /// Left associative, `a - b - c` is `(a - b) - c`.
+  Left,
+  /// Right associative, `a ** b ** c` is `a ** (b ** c)`.
+  Right,
+}
+
+/// Gets the precedence of the specific binary operator, higher precedence
+/// binds tighter, returns 0 if the operator is not a binary operator.
+///
+/// All binary operators bind looser than unary operators. Assignments
+/// are not binary operations, they bind looser than all operators
+/// and are parsed separately, so their precedence is 0.
+pub fn precedence(op: &Operator) -> u8 {
+  match op {
+    Operator::LOr => 1,
+    Operator::LAnd => 2,
+    Operator::Eq | Operator::NotEq => 3,
+    Operator::Less | Operator::LessEq => 4,
+    Operator::Add | Operator::Sub => 5,
+    Operator::Mul | Operator::Div | Operator::Mod => 6,
+    Operator::Pow => 7,
+    _ => 0,
+  }
+}
+
+/// Gets the associativity of the specific binary operator or assignment.
+pub fn associativity(op: &Operator) -> Assoc {
+  match op {
+    Operator::Pow | Operator::Assign | Operator::Define => Assoc::Right,
+    _ => Assoc::Left,
+  }
+}
+
+/// Gets the left and right binding powers of the specific binary operator,
+/// returns `None` if the operator is not a binary operator.
+pub fn binding_power(op: &Operator) -> Option<(u8, u8)> {
+  let prec = precedence(op);
+  if prec == 0 {
+    return None;
+  }
+  Some(match associativity(op) {
+    Assoc::Left => (prec * 2 - 1, prec * 2),
+    Assoc::Right => (prec * 2, prec * 2 - 1),
+  })
+}
+
+impl<T: ByteSource> Iterator for Parser<T> {
+  type Item = core::result::Result<AstBox, Error>;
+
+  /// Parses the next AST, returns `None` if reaches EOF.
+  /// Iteration is stopped after the first error.
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.parse_next() {
+      Err(Error::End) => None,
+      Err(err) => {
+        self.cur_token = Ok(Token::End);
+        Some(Err(err))
+      }
+      ast => Some(ast),
+    }
   }
 }
 
 /// Unit tests for `Parser`.
 #[cfg(test)]
 mod test {
-  use super::{Ast, Lexer, Operator, Parser};
+  use super::{associativity, binding_power, precedence, Assoc};
+  use super::{Ast, AstBox, Error, Lexer, Operator, Parser, Token, Type, DEFAULT_MAX_DEPTH};
+  use crate::define::{AstBuilder, Symbol};
   use crate::unwrap_struct;
   use std::io::Cursor;
+  use std::ops::ControlFlow;
 
   #[test]
   fn test_parser() {
-    let mut parser = Parser::new(Lexer::new(Cursor::new(
+    let mut parser = Parser::from_str(
       r#"
       # test comment
       func(x) {
@@ -392,11 +965,12 @@ mod test {
         }
       }
       "#,
-    )));
+    );
     let fundef = parser.parse_next().unwrap();
-    let (name, args, body) = unwrap_struct!(*fundef, Ast::FunDef, name, args, body);
+    let (name, args, _, _, body) =
+      unwrap_struct!(*fundef, Ast::FunDef, name, args, arg_types, ret_type, body);
     assert_eq!(name, "func");
-    assert_eq!(args, ["x"]);
+    assert_eq!(args.as_slice(), ["x"]);
     let (stmts,) = unwrap_struct!(*body, Ast::Block, stmts);
     assert_eq!(stmts.len(), 1);
     let (cond, then, else_then) = unwrap_struct!(&*stmts[0], Ast::If, cond, then, else_then);
@@ -409,7 +983,7 @@ mod test {
     let (stmts,) = unwrap_struct!(&**then, Ast::Block, stmts);
     assert_eq!(stmts.len(), 1);
     let (expr,) = unwrap_struct!(&*stmts[0], Ast::Return, expr);
-    let (op, lhs, rhs) = unwrap_struct!(&**expr, Ast::Binary, op, lhs, rhs);
+    let (op, lhs, rhs) = unwrap_struct!(&**expr.as_ref().unwrap(), Ast::Binary, op, lhs, rhs);
     assert_eq!(*op, Operator::Add);
     let (id,) = unwrap_struct!(&**lhs, Ast::Id, id);
     assert_eq!(*id, "x");
@@ -417,4 +991,533 @@ mod test {
     assert_eq!(*val, 11);
     assert!(else_then.is_none());
   }
+
+  #[test]
+  fn test_iterator() {
+    let parser = Parser::from_str(
+      r#"
+      f() {
+        return 1
+      }
+      g(x, y) {
+        return x
+      }
+      "#,
+    );
+    let names: Vec<_> = parser
+      .map(|ast| match *ast.unwrap() {
+        Ast::FunDef { name, .. } => name,
+        _ => panic!("expected function definition"),
+      })
+      .collect();
+    assert_eq!(names, ["f", "g"]);
+    let mut parser = Parser::from_str("f() { return 1 } {");
+    assert!(parser.next().unwrap().is_ok());
+    assert!(parser.next().unwrap().is_err());
+    assert!(parser.next().is_none());
+  }
+
+  #[test]
+  fn test_parse_program() {
+    let mut parser = Parser::from_str("f() { return 1 } g() { return 2 }");
+    assert_eq!(parser.parse_program().unwrap().len(), 2);
+    let mut parser = Parser::from_str("f() { return 1 } g(");
+    assert!(parser.parse_program().is_err());
+  }
+
+  #[test]
+  fn test_parse_each() {
+    // the last function is invalid, but never parsed
+    let program = "f() { return 1 } g() { return 2 } h( {";
+    let parser = Parser::from_str(program);
+    let mut names = Vec::new();
+    let result = parser.parse_each(|ast| {
+      if let Ast::FunDef { name, .. } = *ast {
+        names.push(name);
+      }
+      ControlFlow::Break(())
+    });
+    assert!(result.is_ok());
+    assert_eq!(names, [Symbol::new("f")]);
+    // errors are returned after all previous functions are processed
+    let parser = Parser::from_str(program);
+    let mut count = 0;
+    let result = parser.parse_each(|_| {
+      count += 1;
+      ControlFlow::Continue(())
+    });
+    let err = "expected identifier, found '{' at line 1, col 38";
+    assert_eq!(result.err().unwrap().to_string(), err);
+    assert_eq!(count, 2);
+  }
+
+  #[test]
+  fn test_error_display() {
+    assert_eq!(format!("{}", Error::Error("oops".to_string())), "oops");
+    assert_eq!(format!("{}", Error::End), "end of input");
+    let mut parser = Parser::from_str("f(");
+    let err: Box<dyn std::error::Error> = match parser.parse_next() {
+      Err(err) => Box::new(err),
+      Ok(_) => panic!("expected error"),
+    };
+    assert_eq!(err.to_string(), "expected identifier, found end of file at line 1, col 3");
+    let mut parser = Parser::from_str("f() {\n  x := 1 & 2\n}");
+    let err = parser.parse_next().err().unwrap();
+    assert_eq!(err.to_string(), "invalid operator at line 2, col 10");
+  }
+
+  #[test]
+  fn test_unexpected_token() {
+    let error = |program| Parser::from_str(program).parse_program().err().unwrap().to_string();
+    assert_eq!(error("main() {}\n\n  f := 1"), "expected '(', found ':=' at line 3, col 5");
+    assert_eq!(error("f(1) {}"), "expected identifier, found '1' at line 1, col 3");
+    assert_eq!(error("f() { x }"), "expected ':=' or '=', found '}' at line 1, col 9");
+    // positions are tracked after peeking
+    let mut parser = Parser::from_str("f(x) {\n  return x +\n}");
+    assert_eq!(parser.peek(8), &Ok(Token::Other('}')));
+    let err = "invalid value, found '}' at line 3, col 1";
+    assert_eq!(parser.parse_next().err().unwrap().to_string(), err);
+  }
+
+  #[test]
+  fn test_keyword_suggestion() {
+    /// Parses the specific statement and returns the error message.
+    fn error(stmt: &str) -> String {
+      let mut parser = Parser::from_str(stmt);
+      parser.parse_next_stmt().err().unwrap().to_string()
+    }
+    let expected = "expected ':=' or '='";
+    let err = format!("{}, found 'x' at line 1, col 8, did you mean 'return'?", expected);
+    assert_eq!(error("retrun x"), err);
+    let err = format!("{}, found 'x' at line 1, col 5, did you mean 'if'?", expected);
+    assert_eq!(error("iff x { return 1 }"), err);
+    let err = format!("{}, found '{{' at line 1, col 5, did you mean 'else'?", expected);
+    assert_eq!(error("ese { }"), err);
+    assert_eq!(error("count x"), "expected ':=' or '=', found 'x' at line 1, col 7");
+    assert_eq!(error("x y"), "expected ':=' or '=', found 'y' at line 1, col 3");
+  }
+
+  #[test]
+  fn test_try_parse_program() {
+    assert_eq!(Parser::try_parse_program(b"f(x) { return x } g() {}").unwrap().len(), 2);
+    let deep = "f() { return ".to_string() + &"(".repeat(100000) + "1" + &")".repeat(100000);
+    let deep = deep + " }";
+    let err = Parser::try_parse_program(deep.as_bytes()).err().unwrap();
+    assert_eq!(err.to_string(), "expression too deeply nested");
+    let inputs = [
+      "f() { return ".to_string() + &"-".repeat(100000) + "1 }",
+      "f() ".to_string() + &"{ if 1 ".repeat(100000),
+      "f(x) { if x {} ".to_string() + &"else if x {} ".repeat(100000) + "}",
+      "f() { return 99999999999999999999 }".to_string(),
+      "#\n".repeat(100000) + "f(",
+    ];
+    for input in &inputs {
+      assert!(Parser::try_parse_program(input.as_bytes()).is_err());
+    }
+  }
+
+  #[test]
+  fn test_parse_file() {
+    let path = std::env::temp_dir().join(format!("first-step-{}.fstep", std::process::id()));
+    std::fs::write(&path, "f(x) { return x }\nmain() { return f(1) }\n").unwrap();
+    let asts = Parser::parse_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    let expected = Parser::from_str("f(x) { return x } main() { return f(1) }").parse_program();
+    assert_eq!(asts.unwrap(), expected.unwrap());
+    let err = Parser::parse_file(&path).err().unwrap().to_string();
+    assert!(err.starts_with(&format!("failed to open '{}': ", path.display())));
+  }
+
+  #[test]
+  fn test_max_depth() {
+    /// Parses the specific statement with the specific maximum depth.
+    fn parse(stmt: &str, max_depth: usize) -> Result<AstBox, Error> {
+      let mut parser = Parser::from_str(stmt);
+      parser.set_max_depth(max_depth);
+      parser.parse_next_stmt()
+    }
+    let expr = |depth| "x = ".to_string() + &"(".repeat(depth) + "1" + &")".repeat(depth);
+    // the expression itself and each pair of parentheses are one level
+    assert!(parse(&expr(9), 10).is_ok());
+    let err = parse(&expr(10), 10).err().unwrap();
+    assert_eq!(err.to_string(), "expression too deeply nested");
+    assert!(parse("x = - - - 1", 4).is_ok());
+    assert!(parse("x = - - - 1", 3).is_err());
+    // blocks and their conditions
+    let blocks = |depth| "if 1 { ".repeat(depth) + &"}".repeat(depth);
+    assert!(parse(&blocks(8), 8).is_ok());
+    let err = parse(&blocks(9), 8).err().unwrap();
+    assert_eq!(err.to_string(), "expression too deeply nested");
+    // no limit is reached with the default depth
+    assert!(parse(&expr(200), DEFAULT_MAX_DEPTH).is_ok());
+  }
+
+  #[test]
+  fn test_random_bytes() {
+    // xorshift generator, for reproducible pseudo-random inputs
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut next = move || {
+      state ^= state << 13;
+      state ^= state >> 7;
+      state ^= state << 17;
+      state
+    };
+    const TOKENS: &[&str] = &[
+      "f", "x", "(", ")", "{", "}", ",", ":=", "=", "+", "-", "!", "if ", "else ", "return ", "1",
+    ];
+    for _ in 0..1000 {
+      let len = next() as usize % 64;
+      // random bytes
+      let bytes: Vec<_> = (0..len).map(|_| next() as u8).collect();
+      let _ = Parser::try_parse_program(&bytes);
+      // random tokens
+      let source: String = (0..len).map(|_| TOKENS[next() as usize % TOKENS.len()]).collect();
+      let _ = Parser::try_parse_program(source.as_bytes());
+    }
+  }
+
+  /// Dumps the specific expression in prefix notation.
+  fn dump_expr(ast: &AstBox) -> String {
+    match ast.as_ref() {
+      Ast::Binary { op, lhs, rhs } => format!("({:?} {} {})", op, dump_expr(lhs), dump_expr(rhs)),
+      Ast::Unary { op, opr } => format!("({:?} {})", op, dump_expr(opr)),
+      Ast::FunCall { name, args } => {
+        let args: Vec<_> = args.iter().map(dump_expr).collect();
+        format!("({} {})", name, args.join(" "))
+      }
+      Ast::Int { val } => val.to_string(),
+      Ast::Id { id } => id.to_string(),
+      _ => panic!("not an expression"),
+    }
+  }
+
+  #[test]
+  fn test_precedence() {
+    let cases = [
+      ("1 + 2 * 3", "(Add 1 (Mul 2 3))"),
+      ("1 - 2 - 3", "(Sub (Sub 1 2) 3)"),
+      ("8 / 4 % 3 * 2", "(Mul (Mod (Div 8 4) 3) 2)"),
+      ("(1 + 2) * 3", "(Mul (Add 1 2) 3)"),
+      ("a < b == c <= d", "(Eq (Less a b) (LessEq c d))"),
+      ("a || b && c || d", "(LOr (LOr a (LAnd b c)) d)"),
+      (
+        "a || b && c == d < e + f * g",
+        "(LOr a (LAnd b (Eq c (Less d (Add e (Mul f g))))))",
+      ),
+      ("-a * b", "(Mul (Sub a) b)"),
+      ("!a == -f(1, 2)", "(Eq (LNot a) (Sub (f 1 2)))"),
+      ("- -a - b", "(Sub (Sub (Sub a)) b)"),
+      ("2 ** 3 ** 2", "(Pow 2 (Pow 3 2))"),
+      ("a * b ** c * d", "(Mul (Mul a (Pow b c)) d)"),
+      ("-a ** b", "(Pow (Sub a) b)"),
+    ];
+    for (expr, expected) in cases.iter() {
+      let mut parser = Parser::from_str(expr);
+      assert_eq!(dump_expr(&parser.parse_next_expr().unwrap()), *expected);
+      assert!(parser.is_end());
+    }
+  }
+
+  #[test]
+  fn test_precedence_api() {
+    assert!(precedence(&Operator::Mul) > precedence(&Operator::Add));
+    assert!(precedence(&Operator::Pow) > precedence(&Operator::Mul));
+    assert!(precedence(&Operator::LAnd) > precedence(&Operator::LOr));
+    assert_eq!(associativity(&Operator::LOr), Assoc::Left);
+    assert_eq!(associativity(&Operator::Sub), Assoc::Left);
+    assert_eq!(associativity(&Operator::Pow), Assoc::Right);
+    assert_eq!(precedence(&Operator::Assign), 0);
+    assert_eq!(binding_power(&Operator::Assign), None);
+    assert_eq!(binding_power(&Operator::Add), Some((9, 10)));
+    assert_eq!(binding_power(&Operator::Pow), Some((14, 13)));
+  }
+
+  #[test]
+  fn test_chained_comparison() {
+    let parse = |expr| Parser::from_str(expr).parse_next_expr();
+    let err = "chained comparison is not allowed; use && instead";
+    assert_eq!(parse("1 < 2 < 3").err().unwrap().to_string(), err);
+    assert_eq!(parse("a <= b + 1 < c").err().unwrap().to_string(), err);
+    assert!(parse("1 < 2 && 2 < 3").is_ok());
+    assert!(parse("(1 < 2) < 3").is_ok());
+    assert!(parse("a < b == c <= d").is_ok());
+  }
+
+  #[test]
+  fn test_pow_error() {
+    // `*b` is not a valid unary expression
+    let mut parser = Parser::from_str("a * *b");
+    assert!(parser.parse_next_expr().is_err());
+  }
+
+  #[test]
+  fn test_const() {
+    let program = "const N := 2 * 3 + 1 const K := -N f() { return N * K }";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next().unwrap();
+    assert_eq!(parser.constant(&Symbol::new("N")), Some(7));
+    assert_eq!(parser.constant(&Symbol::new("K")), Some(-7));
+    let b = AstBuilder::new();
+    let ret = b.ret(b.binary(Operator::Mul, b.int(7), b.int(-7)));
+    let expected = b.fundef("f", Vec::new(), b.block(vec![ret]));
+    assert!(ast == expected);
+    assert!(matches!(parser.parse_next(), Err(Error::End)));
+    // 64-bit constants
+    let parser64 = |program| {
+      let mut lexer = Lexer::from_str(program);
+      lexer.set_int64(true);
+      Parser::new(lexer)
+    };
+    let mut parser = parser64("const BIG := 3000000000 const M := BIG * 2 f() {}");
+    parser.parse_next().unwrap();
+    assert_eq!(parser.constant(&Symbol::new("M")), Some(6000000000));
+    let mut parser = parser64("const M := 9223372036854775807 + 1");
+    assert_eq!(parser.parse_next().err().unwrap().to_string(), "integer overflow");
+    // errors
+    let error = |program: &str| {
+      let mut parser = Parser::from_str(program);
+      parser.parse_next().err().unwrap().to_string()
+    };
+    let err = "integer literal too large at line 1, col 12";
+    assert_eq!(error("const M := 3000000000"), err);
+    assert_eq!(error("const M := 2147483647 + 1"), "integer overflow");
+    assert_eq!(error("const M := x"), "variables can not be used in constant expressions");
+    assert_eq!(error("const M := 1 / 0"), "division by zero");
+    assert_eq!(error("const M := 1 const M := 2"), "constant has already been defined");
+    assert_eq!(error("const M := 1 f() { M = 2 }"), "can not define or assign constants");
+    assert_eq!(error("const M := 1 f(x, M) { return M }"), "can not define or assign constants");
+  }
+
+  #[test]
+  fn test_else_if() {
+    let program = "if a { x = 1 } else if b { x = 2 } else { x = 3 }";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next_stmt().unwrap();
+    let b = AstBuilder::new();
+    let assign = |val| b.block(vec![b.assign("x", b.int(val))]);
+    let expected = b.if_else(
+      b.id("a"),
+      assign(1),
+      b.if_else(b.id("b"), assign(2), assign(3)),
+    );
+    assert!(ast == expected);
+    // the innermost 'else' is the final block
+    let (_, _, else_then) = unwrap_struct!(*ast, Ast::If, cond, then, else_then);
+    let (_, _, else_then) = unwrap_struct!(*else_then.unwrap(), Ast::If, cond, then, else_then);
+    assert!(else_then == Some(assign(3)));
+    assert!(parser.is_end());
+  }
+
+  #[test]
+  fn test_switch() {
+    let program = "switch x { case 1: y = 1 case -2: y = 2 z = 3 default: y = 0 }";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next_stmt().unwrap();
+    let b = AstBuilder::new();
+    let expected = Box::new(Ast::Switch {
+      scrutinee: b.id("x"),
+      cases: vec![
+        (1, b.block(vec![b.assign("y", b.int(1))])),
+        (-2, b.block(vec![b.assign("y", b.int(2)), b.assign("z", b.int(3))])),
+      ],
+      default: Some(b.block(vec![b.assign("y", b.int(0))])),
+    });
+    assert!(ast == expected);
+    assert!(parser.is_end());
+    // 64-bit case values
+    let mut lexer = Lexer::from_str("switch x { case -3000000000: }");
+    lexer.set_int64(true);
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_next_stmt().unwrap();
+    let (_, cases, _) = unwrap_struct!(*ast, Ast::Switch, scrutinee, cases, default);
+    assert_eq!(cases[0].0, -3000000000);
+    // errors
+    let error = |stmt: &str| {
+      let mut parser = Parser::from_str(stmt);
+      parser.parse_next_stmt().err().unwrap().to_string()
+    };
+    assert_eq!(error("switch x { case 1: case 1: }"), "duplicate case value");
+    let err = "integer literal too large at line 1, col 17";
+    assert_eq!(error("switch x { case 3000000000: }"), err);
+    assert_eq!(error("switch x { default: default: }"), "duplicate default case");
+    let err = "expected integer literal, found 'y' at line 1, col 17";
+    assert_eq!(error("switch x { case y: }"), err);
+    let err = "expected 'case' or 'default', found 'y' at line 1, col 12";
+    assert_eq!(error("switch x { y = 1 }"), err);
+    let err = "expected ':', found 'y' at line 1, col 19";
+    assert_eq!(error("switch x { case 1 y = 1 }"), err);
+  }
+
+  #[test]
+  fn test_type_annotations() {
+    let program = "f(x: int, y, z: bool) -> int { return x } g(x) { return x }";
+    let mut parser = Parser::from_str(program);
+    let ast = parser.parse_next().unwrap();
+    let (name, args, arg_types, ret_type, _) =
+      unwrap_struct!(*ast, Ast::FunDef, name, args, arg_types, ret_type, body);
+    assert_eq!(name, "f");
+    assert_eq!(args.len(), 3);
+    assert_eq!(&arg_types[..], [Some(Type::Int), None, Some(Type::Bool)]);
+    assert_eq!(ret_type, Some(Type::Int));
+    // unannotated functions
+    let b = AstBuilder::new();
+    let expected = b.fundef("g", ["x"], b.block(vec![b.ret(b.id("x"))]));
+    assert!(parser.parse_next().unwrap() == expected);
+    // errors
+    let error = |program: &str| {
+      let mut parser = Parser::from_str(program);
+      parser.parse_next().err().unwrap().to_string()
+    };
+    assert_eq!(error("f(x: y) {}"), "expected type, found 'y' at line 1, col 6");
+    assert_eq!(error("f(x) -> {}"), "expected type, found '{' at line 1, col 9");
+    assert_eq!(error("f(x) int {}"), "expected '{', found 'int' at line 1, col 6");
+  }
+
+  #[test]
+  fn test_void_return() {
+    let b = AstBuilder::new();
+    let parse = |program: &str| {
+      let mut parser = Parser::from_str(program);
+      parser.parse_next().unwrap()
+    };
+    let expected = b.fundef("f", Vec::new(), b.block(vec![b.ret_void()]));
+    assert!(parse("f() { return }") == expected);
+    let expected = b.fundef("f", ["x"], b.block(vec![b.ret(b.id("x"))]));
+    assert!(parse("f(x) { return x }") == expected);
+    // followed by other statements
+    let program = "f(x) { if x { return } return x = 1 return -x }";
+    let body = b.block(vec![
+      b.if_then(b.id("x"), b.block(vec![b.ret_void()])),
+      b.ret_void(),
+      b.assign("x", b.int(1)),
+      b.ret(b.unary(Operator::Sub, b.id("x"))),
+    ]);
+    assert!(parse(program) == b.fundef("f", ["x"], body));
+    // return values must start on the same line
+    let body = b.block(vec![b.ret_void(), b.funcall("print", [b.int(7)])]);
+    assert!(parse("f() {\n  return\n  print(7)\n}") == b.fundef("f", Vec::new(), body));
+    let expected = b.fundef("f", Vec::new(), b.block(vec![b.ret(b.funcall("g", []))]));
+    assert!(parse("f() { return g() }") == expected);
+  }
+
+  #[test]
+  fn test_do_while() {
+    let mut parser = Parser::from_str("do { x = x - 1 } while 0 < x");
+    let ast = parser.parse_next_stmt().unwrap();
+    let b = AstBuilder::new();
+    let expected = Box::new(Ast::DoWhile {
+      body: b.block(vec![b.assign("x", b.binary(Operator::Sub, b.id("x"), b.int(1)))]),
+      cond: b.binary(Operator::Less, b.int(0), b.id("x")),
+    });
+    assert!(ast == expected);
+    assert!(parser.is_end());
+    let mut parser = Parser::from_str("do { } x");
+    let err = "expected 'while', found 'x' at line 1, col 8";
+    assert_eq!(parser.parse_next_stmt().err().unwrap().to_string(), err);
+  }
+
+  #[test]
+  fn test_assign_expr() {
+    let b = AstBuilder::new();
+    let expected = b.assign("a", b.assign("b", b.int(3)));
+    let mut parser = Parser::from_str("a = b = 3");
+    parser.set_assign_expr(true);
+    assert!(parser.parse_next_stmt().unwrap() == expected);
+    assert!(parser.is_end());
+    // right associative
+    let mut parser = Parser::from_str("a = b = c = d x := y = 1");
+    parser.set_assign_expr(true);
+    let expected = b.assign("a", b.assign("b", b.assign("c", b.id("d"))));
+    assert!(parser.parse_next_stmt().unwrap() == expected);
+    let expected = b.define("x", b.assign("y", b.int(1)));
+    assert!(parser.parse_next_stmt().unwrap() == expected);
+    assert_eq!(associativity(&Operator::Assign), Assoc::Right);
+    let mut parser = Parser::from_str("x + (y = 1 + 2) * 3");
+    parser.set_assign_expr(true);
+    let expected = b.binary(
+      Operator::Add,
+      b.id("x"),
+      b.binary(
+        Operator::Mul,
+        b.assign("y", b.binary(Operator::Add, b.int(1), b.int(2))),
+        b.int(3),
+      ),
+    );
+    assert!(parser.parse_next_expr().unwrap() == expected);
+    // disabled by default
+    let mut parser = Parser::from_str("a = b = 3");
+    assert!(parser.parse_next_stmt().is_ok());
+    assert!(!parser.is_end());
+  }
+
+  #[test]
+  fn test_comments() {
+    let program = "# a\nf(x) { # b\n  return x # c\n  # d\n} # e";
+    let mut lexer = Lexer::new(Cursor::new(program));
+    lexer.set_keep_comments(true);
+    let asts = Parser::new(lexer).parse_program().unwrap();
+    let b = AstBuilder::new();
+    let body = b.block([
+      b.comment(" b", true),
+      b.ret(b.id("x")),
+      b.comment(" c", true),
+      b.comment(" d", false),
+    ]);
+    let expected = [b.comment(" a", false), b.fundef("f", ["x"], body), b.comment(" e", true)];
+    assert!(asts == expected);
+  }
+
+  #[test]
+  fn test_from_str() {
+    let mut parser = Parser::from_str("add(a, b) { return a + b }");
+    let b = AstBuilder::new();
+    let body = b.block([b.ret(b.binary(Operator::Add, b.id("a"), b.id("b")))]);
+    assert_eq!(parser.parse_next().unwrap(), b.fundef("add", ["a", "b"], body));
+    assert!(parser.is_end());
+    let tokens: Vec<_> = Lexer::from_str("x := 1").collect();
+    assert_eq!(tokens.len(), 3);
+  }
+
+  #[test]
+  fn test_strict_separators() {
+    let parse = |program, strict| {
+      let mut parser = Parser::from_str(program);
+      parser.set_strict_separators(strict);
+      parser.parse_program().map_err(|err| err.to_string())
+    };
+    // run-on statements are accepted by default
+    let program = "f(x) { x := 1 y := 2 }";
+    assert!(parse(program, false).is_ok());
+    let err = "expected ';' or new line between statements, found 'y' at line 1, col 15";
+    assert_eq!(parse(program, true).err().unwrap(), err);
+    let err = parse("f(x) {\n  if x { x = 1 } x = 2\n}", true).err().unwrap();
+    assert!(err.ends_with("found 'x' at line 2, col 18"));
+    // separated by semicolons or new lines
+    let expected = parse(program, false).unwrap();
+    assert!(parse("f(x) { x := 1; y := 2; }", true).unwrap() == expected);
+    assert!(parse("f(x) {\n  x := 1\n  y := 2 }", true).unwrap() == expected);
+    let program = "f(x) { switch x { case 1: x = 2 case 2: default: x = 3; x = 4 } return x }";
+    assert!(parse(program, true).is_err());
+    let program = "f(x) { switch x { case 1: x = 2 case 2: default: x = 3; x = 4 }\nreturn x }";
+    assert!(parse(program, true).is_ok());
+    // statements parsed one by one
+    let mut parser = Parser::from_str("x := 1; y := 2 z := 3");
+    parser.set_strict_separators(true);
+    assert!(parser.parse_next_stmt().is_ok());
+    assert!(parser.parse_next_stmt().is_err());
+  }
+
+  #[test]
+  fn test_peek() {
+    let mut parser = Parser::from_str("x := y + 1");
+    assert_eq!(parser.peek(0), &Ok(Token::Id(Symbol::new("x"))));
+    assert_eq!(parser.peek(2), &Ok(Token::Id(Symbol::new("y"))));
+    assert_eq!(parser.peek(1), &Ok(Token::Op(Operator::Define)));
+    assert_eq!(parser.peek(5), &Ok(Token::End));
+    let stmt = parser.parse_next_stmt().unwrap();
+    let (name, expr) = unwrap_struct!(*stmt, Ast::Define, name, expr);
+    assert_eq!(name, "x");
+    assert_eq!(dump_expr(&expr), "(Add y 1)");
+    assert!(parser.is_end());
+  }
 }
@@ -0,0 +1,127 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+
+/// Source of bytes read by `Lexer`.
+///
+/// All `std::io::Read` objects are byte sources when the `std` feature
+/// is enabled, otherwise only byte slices are byte sources by default.
+pub trait ByteSource {
+  /// Reads the next byte, returns `None` if reaches the end of source,
+  /// or the error message if failed.
+  fn next_byte(&mut self) -> Result<Option<u8>, String>;
+
+  /// Reads some bytes into the specific buffer, returns the number of bytes
+  /// read, which is zero only if reaches the end of source or the buffer is empty.
+  ///
+  /// Reads at most one byte by default, sources should override this method
+  /// if they can read multiple bytes at once more efficiently.
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, String> {
+    match buf.first_mut() {
+      Some(first) => Ok(self.next_byte()?.map_or(0, |byte| {
+        *first = byte;
+        1
+      })),
+      None => Ok(0),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Read> ByteSource for T {
+  fn next_byte(&mut self) -> Result<Option<u8>, String> {
+    let mut byte = [0];
+    match self.read(&mut byte) {
+      Ok(0) => Ok(None),
+      Ok(_) => Ok(Some(byte[0])),
+      Err(err) => Err(err.to_string()),
+    }
+  }
+
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, String> {
+    loop {
+      match std::io::Read::read(self, buf) {
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+        result => return result.map_err(|err| err.to_string()),
+      }
+    }
+  }
+}
+
+#[cfg(not(feature = "std"))]
+impl ByteSource for &[u8] {
+  fn next_byte(&mut self) -> Result<Option<u8>, String> {
+    Ok(self.split_first().map(|(byte, rest)| {
+      *self = rest;
+      *byte
+    }))
+  }
+
+  fn read(&mut self, buf: &mut [u8]) -> Result<usize, String> {
+    let len = buf.len().min(self.len());
+    let (bytes, rest) = self.split_at(len);
+    buf[..len].copy_from_slice(bytes);
+    *self = rest;
+    Ok(len)
+  }
+}
+
+/// Tests of the front end which only use `core` and `alloc`,
+/// to make sure it still works in `no_std` environments.
+#[cfg(test)]
+mod test {
+  use super::ByteSource;
+  use crate::define::{Ast, Token};
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use alloc::string::String;
+  use alloc::vec::Vec;
+
+  /// Byte source which does not implement `std::io::Read`.
+  struct Bytes {
+    /// All bytes of the source.
+    bytes: Vec<u8>,
+    /// Position of the next byte.
+    pos: usize,
+  }
+
+  impl Bytes {
+    /// Creates a new byte source of the specific string.
+    fn new(s: &str) -> Self {
+      Self {
+        bytes: s.as_bytes().into(),
+        pos: 0,
+      }
+    }
+  }
+
+  impl ByteSource for Bytes {
+    fn next_byte(&mut self) -> Result<Option<u8>, String> {
+      let byte = self.bytes.get(self.pos).copied();
+      self.pos += 1;
+      Ok(byte)
+    }
+  }
+
+  #[test]
+  fn test_alloc_only() {
+    let lexer = Lexer::new(Bytes::new("x := 1 # comment\nreturn x"));
+    let tokens: Result<Vec<_>, _> = lexer.collect();
+    assert_eq!(tokens.unwrap().len(), 5);
+    let mut lexer = Lexer::new(Bytes::new("x"));
+    assert!(matches!(lexer.next_token(), Ok(Token::Id(id)) if id == "x"));
+    let program = "f(x) { return x + 1 } main() { return f(1) }";
+    let mut parser = Parser::new(Lexer::new(Bytes::new(program)));
+    let asts = parser.parse_program().unwrap();
+    assert_eq!(asts.len(), 2);
+    assert!(matches!(asts[1].as_ref(), Ast::FunDef { name, .. } if *name == "main"));
+    // errors of the byte source are reported by the lexer
+    struct Failing;
+    impl ByteSource for Failing {
+      fn next_byte(&mut self) -> Result<Option<u8>, String> {
+        Err("device not ready".into())
+      }
+    }
+    let err = Lexer::new(Failing).next_token().unwrap_err();
+    assert_eq!(err.message, "device not ready");
+  }
+}
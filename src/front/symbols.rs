@@ -0,0 +1,173 @@
+use super::diagnostic::Span;
+use super::lexer::{LexError, Lexer};
+use super::source::ByteSource;
+use crate::define::{Operator, Symbol, Token};
+use alloc::vec::Vec;
+
+/// Function in the symbol table.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionInfo {
+  /// Name of the function.
+  pub name: Symbol,
+  /// Number of parameters.
+  pub arity: usize,
+  /// Span of the name in the definition.
+  pub span: Span,
+}
+
+/// Variable definition (or parameter) in the symbol table.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableInfo {
+  /// Name of the variable.
+  pub name: Symbol,
+  /// Name of the enclosing function.
+  pub func: Symbol,
+  /// Span of the name in the definition.
+  pub span: Span,
+}
+
+/// Flat symbol table of a program, for editor features
+/// like go-to-definition.
+///
+/// Symbols are collected from tokens, so that their spans are known,
+/// and they are listed in the order of their definitions.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolTable {
+  /// All function definitions.
+  pub functions: Vec<FunctionInfo>,
+  /// All parameters and variable definitions (`:=`).
+  pub variables: Vec<VariableInfo>,
+}
+
+impl SymbolTable {
+  /// Builds the symbol table of all tokens of the specific lexer,
+  /// returns the lexer error if failed.
+  pub fn build<T: ByteSource>(mut lexer: Lexer<T>) -> Result<Self, LexError> {
+    // read all tokens and their spans, only identifiers have lengths
+    let mut tokens = Vec::new();
+    loop {
+      let token = lexer.next_token()?;
+      if token == Token::End {
+        break;
+      }
+      let (line, col) = lexer.pos();
+      let len = match &token {
+        Token::Id(id) => id.as_str().chars().count() as u32,
+        _ => 0,
+      };
+      tokens.push((token, Span::new(line, col, len)));
+    }
+    // collect symbols
+    let mut table = Self::default();
+    let (mut depth, mut func) = (0usize, None);
+    let mut i = 0;
+    while i < tokens.len() {
+      match (&tokens[i].0, tokens.get(i + 1).map(|t| &t.0)) {
+        (Token::Other('{'), _) => depth += 1,
+        (Token::Other('}'), _) => depth = depth.saturating_sub(1),
+        // function definitions and their parameters
+        (Token::Id(name), Some(Token::Other('('))) if depth == 0 => {
+          let params: Vec<_> = tokens[i + 2..]
+            .iter()
+            .take_while(|(t, _)| *t != Token::Other(')'))
+            .filter_map(|(t, span)| match t {
+              Token::Id(id) => Some((*id, *span)),
+              _ => None,
+            })
+            .collect();
+          table.functions.push(FunctionInfo {
+            name: *name,
+            arity: params.len(),
+            span: tokens[i].1,
+          });
+          for (param, span) in params {
+            table.variables.push(VariableInfo {
+              name: param,
+              func: *name,
+              span,
+            });
+          }
+          func = Some(*name);
+          i += 1;
+        }
+        // variable definitions
+        (Token::Id(name), Some(Token::Op(Operator::Define))) if depth > 0 => {
+          if let Some(func) = func {
+            table.variables.push(VariableInfo {
+              name: *name,
+              func,
+              span: tokens[i].1,
+            });
+          }
+        }
+        _ => {}
+      }
+      i += 1;
+    }
+    Ok(table)
+  }
+
+  /// Finds the definition of the specific variable in the specific function.
+  pub fn find_variable(&self, func: &Symbol, name: &Symbol) -> Option<&VariableInfo> {
+    self
+      .variables
+      .iter()
+      .find(|var| var.func == *func && var.name == *name)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{FunctionInfo, SymbolTable, VariableInfo};
+  use crate::define::Symbol;
+  use crate::front::{diagnostic::Span, lexer::Lexer};
+  use std::io::Cursor;
+
+  #[test]
+  fn test_symbol_table() {
+    let program = r#"
+const N := 10
+f(x, flag: bool) -> int {
+  y := x
+  if flag { z := y } return N
+}
+main() { return f(1, 1 == 1) }
+"#;
+    let table = SymbolTable::build(Lexer::new(Cursor::new(program))).unwrap();
+    let (f, main) = (Symbol::new("f"), Symbol::new("main"));
+    let function = |name, arity, line, col| FunctionInfo {
+      name,
+      arity,
+      span: Span::new(line, col, name.as_str().len() as u32),
+    };
+    assert_eq!(table.functions, [function(f, 2, 3, 1), function(main, 0, 7, 1)]);
+    let variable = |name, line, col| VariableInfo {
+      name: Symbol::new(name),
+      func: f,
+      span: Span::new(line, col, name.len() as u32),
+    };
+    let expected = [
+      variable("x", 3, 3),
+      variable("flag", 3, 6),
+      variable("y", 4, 3),
+      variable("z", 5, 13),
+    ];
+    assert_eq!(table.variables, expected);
+    assert_eq!(table.find_variable(&f, &Symbol::new("flag")), Some(&expected[1]));
+    assert_eq!(table.find_variable(&main, &Symbol::new("x")), None);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde() {
+    let table = SymbolTable::build(Lexer::new(Cursor::new("f(x) { y := x }"))).unwrap();
+    let json = serde_json::to_string(&table).unwrap();
+    let function = r#"{"name":"f","arity":1,"span":{"line":1,"col":1,"len":1}}"#;
+    assert!(json.contains(function));
+    let restored: SymbolTable = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, table);
+  }
+}
@@ -0,0 +1,127 @@
+use crate::ir::{Function, Label};
+use std::collections::{HashMap, HashSet};
+
+/// Control flow graph of a function.
+pub struct Cfg {
+  /// Labels of all blocks in layout order, the first one is the entry.
+  labels: Vec<Label>,
+  /// Successors of all blocks.
+  succs: HashMap<Label, Vec<Label>>,
+  /// Predecessors of all blocks.
+  preds: HashMap<Label, Vec<Label>>,
+}
+
+impl Cfg {
+  /// Builds the control flow graph of the specific function.
+  pub fn new(func: &Function) -> Self {
+    let labels: Vec<_> = func.blocks.iter().map(|block| block.label).collect();
+    let mut succs = HashMap::new();
+    let mut preds: HashMap<_, _> = labels.iter().map(|label| (*label, Vec::new())).collect();
+    for block in &func.blocks {
+      let block_succs = block.term.succs();
+      for succ in &block_succs {
+        preds.entry(*succ).or_insert_with(Vec::new).push(block.label);
+      }
+      succs.insert(block.label, block_succs);
+    }
+    Self {
+      labels,
+      succs,
+      preds,
+    }
+  }
+
+  /// Gets labels of all blocks in layout order.
+  pub fn labels(&self) -> &[Label] {
+    &self.labels
+  }
+
+  /// Gets the entry block.
+  pub fn entry(&self) -> Option<Label> {
+    self.labels.first().copied()
+  }
+
+  /// Gets successors of the specific block.
+  pub fn succs(&self, label: Label) -> &[Label] {
+    self.succs.get(&label).map_or(&[], |succs| succs)
+  }
+
+  /// Gets predecessors of the specific block.
+  pub fn preds(&self, label: Label) -> &[Label] {
+    self.preds.get(&label).map_or(&[], |preds| preds)
+  }
+
+  /// Gets all blocks reachable from the entry block.
+  pub fn reachable_blocks(&self) -> HashSet<Label> {
+    let mut visited = HashSet::new();
+    let mut stack: Vec<_> = self.entry().into_iter().collect();
+    while let Some(label) = stack.pop() {
+      if visited.insert(label) {
+        stack.extend(self.succs(label).iter().filter(|succ| !visited.contains(succ)));
+      }
+    }
+    visited
+  }
+
+  /// Gets all blocks unreachable from the entry block in layout order.
+  pub fn unreachable_blocks(&self) -> Vec<Label> {
+    let reachable = self.reachable_blocks();
+    let unreachable = self.labels.iter().filter(|label| !reachable.contains(label));
+    unreachable.copied().collect()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Cfg;
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::{BasicBlock, Function, Label, Operand, Terminator};
+
+  /// Generates IR of the specific function definition.
+  fn generate(program: &str) -> Function {
+    let mut parser = Parser::from_str(program);
+    IrGen::new().generate(&parser.parse_next().unwrap()).unwrap()
+  }
+
+  #[test]
+  fn test_cfg() {
+    let cfg = Cfg::new(&generate("f(x) { if x { x = 1 } else { x = 2 } return x }"));
+    assert_eq!(cfg.labels(), [Label(0), Label(1), Label(2), Label(3)]);
+    assert_eq!(cfg.entry(), Some(Label(0)));
+    assert_eq!(cfg.succs(Label(0)), [Label(1), Label(2)]);
+    assert_eq!(cfg.succs(Label(1)), [Label(3)]);
+    assert_eq!(cfg.succs(Label(3)), []);
+    assert_eq!(cfg.preds(Label(0)), []);
+    assert_eq!(cfg.preds(Label(3)), [Label(1), Label(2)]);
+    assert!(cfg.unreachable_blocks().is_empty());
+  }
+
+  #[test]
+  fn test_reachable() {
+    // code after return
+    let cfg = Cfg::new(&generate("f() { return 1 x := 2 return x }"));
+    assert_eq!(cfg.unreachable_blocks(), [Label(1)]);
+    // merge block of branches that both return
+    let cfg = Cfg::new(&generate("f(x) { if x { return 1 } else { return 2 } }"));
+    assert_eq!(cfg.unreachable_blocks(), [Label(3)]);
+    // loops
+    let block = |label, target| BasicBlock {
+      label: Label(label),
+      insts: Vec::new(),
+      term: Terminator::Jump {
+        target: Label(target),
+      },
+    };
+    let mut func = Function::new("f".into());
+    func.blocks = vec![block(0, 1), block(1, 0), block(2, 2)];
+    func.blocks.push(BasicBlock {
+      label: Label(3),
+      insts: Vec::new(),
+      term: Terminator::Ret { val: Operand::Imm(0) },
+    });
+    let cfg = Cfg::new(&func);
+    assert_eq!(cfg.preds(Label(0)), [Label(1)]);
+    assert_eq!(cfg.unreachable_blocks(), [Label(2), Label(3)]);
+  }
+}
@@ -0,0 +1,220 @@
+use crate::define::{eval_binary, eval_unary, OverflowMode};
+use crate::ir::{BasicBlock, Function, Inst, Operand, Reg, Terminator};
+use std::collections::HashMap;
+
+/// Constant propagator, replaces uses of registers holding known constants
+/// with immediates, folds instructions whose operands are all immediates,
+/// and rewrites branches on constant conditions to unconditional jumps.
+///
+/// Constants are only tracked inside basic blocks. Operations that would
+/// fail at runtime (division by zero, overflow in non-wrapping modes)
+/// are left unfolded.
+#[derive(Default)]
+pub struct ConstPropagator {
+  /// Overflow handling mode of integer arithmetic.
+  overflow_mode: OverflowMode,
+}
+
+impl ConstPropagator {
+  /// Creates a new constant propagator.
+  pub fn new() -> Self {
+    Self {
+      overflow_mode: OverflowMode::default(),
+    }
+  }
+
+  /// Sets the overflow handling mode of integer arithmetic.
+  pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+    self.overflow_mode = mode;
+  }
+
+  /// Performs constant propagation on the specific function.
+  pub fn propagate(&self, func: &mut Function) {
+    for block in &mut func.blocks {
+      self.propagate_block(block);
+    }
+  }
+
+  /// Performs constant propagation on the specific block.
+  fn propagate_block(&self, block: &mut BasicBlock) {
+    // values of registers holding known constants
+    let mut consts: HashMap<Reg, i32> = HashMap::new();
+    let replace = |opr: &mut Operand, consts: &HashMap<Reg, i32>| {
+      if let Some(val) = opr.reg().and_then(|reg| consts.get(&reg)) {
+        *opr = Operand::Imm(*val);
+      }
+    };
+    for inst in &mut block.insts {
+      for opr in inst.operands_mut() {
+        replace(opr, &consts);
+      }
+      if let Some(folded) = self.fold(inst) {
+        *inst = folded;
+      }
+      if let Some(def) = inst.def() {
+        match inst {
+          Inst::Copy {
+            src: Operand::Imm(val),
+            ..
+          } => consts.insert(def, *val),
+          _ => consts.remove(&def),
+        };
+      }
+    }
+    for opr in block.term.operands_mut() {
+      replace(opr, &consts);
+    }
+    if let Terminator::Branch {
+      cond: Operand::Imm(cond),
+      then,
+      else_then,
+    } = block.term
+    {
+      let target = if cond != 0 { then } else { else_then };
+      block.term = Terminator::Jump { target };
+    }
+  }
+
+  /// Folds the specific instruction to a copy of an immediate
+  /// if all of its operands are immediates.
+  fn fold(&self, inst: &Inst) -> Option<Inst> {
+    let mode = self.overflow_mode.const_eval_mode();
+    let val = match inst {
+      Inst::Binary {
+        op,
+        lhs: Operand::Imm(lhs),
+        rhs: Operand::Imm(rhs),
+        ..
+      } => eval_binary(op, *lhs, *rhs, mode).ok()?,
+      Inst::Unary {
+        op,
+        opr: Operand::Imm(opr),
+        ..
+      } => eval_unary(op, *opr, mode).ok()?,
+      _ => return None,
+    };
+    Some(Inst::Copy {
+      dest: inst.def()?,
+      src: Operand::Imm(val),
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ConstPropagator;
+  use crate::define::{Operator, OverflowMode};
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+
+  /// Creates a copy instruction.
+  fn copy(dest: u32, src: i32) -> Inst {
+    Inst::Copy {
+      dest: Reg(dest),
+      src: Operand::Imm(src),
+    }
+  }
+
+  /// Creates a binary instruction with register operands.
+  fn binary(dest: u32, op: Operator, lhs: u32, rhs: u32) -> Inst {
+    Inst::Binary {
+      dest: Reg(dest),
+      op,
+      lhs: Operand::Reg(Reg(lhs)),
+      rhs: Operand::Reg(Reg(rhs)),
+    }
+  }
+
+  /// Creates a function with the specific block.
+  fn function(insts: Vec<Inst>, term: Terminator) -> Function {
+    let mut func = Function::new("f".into());
+    func.params = vec![Reg(0)];
+    func.blocks = vec![BasicBlock {
+      label: Label(0),
+      insts,
+      term,
+    }];
+    func.reg_num = 8;
+    func
+  }
+
+  #[test]
+  fn test_const_prop() {
+    let ret = Terminator::Ret {
+      val: Operand::Reg(Reg(3)),
+    };
+    let insts = vec![copy(1, 2), copy(2, 3), binary(3, Operator::Add, 1, 2)];
+    let mut func = function(insts, ret);
+    ConstPropagator::new().propagate(&mut func);
+    assert_eq!(func.blocks[0].insts, [copy(1, 2), copy(2, 3), copy(3, 5)]);
+    assert_eq!(func.blocks[0].term, Terminator::Ret { val: Operand::Imm(5) });
+    // unknown operands and redefined registers
+    let insts = vec![
+      copy(1, 2),
+      binary(2, Operator::Mul, 0, 1),
+      binary(1, Operator::Sub, 1, 2),
+      binary(3, Operator::Add, 1, 1),
+    ];
+    let ret = Terminator::Ret {
+      val: Operand::Reg(Reg(3)),
+    };
+    let mut func = function(insts, ret.clone());
+    ConstPropagator::new().propagate(&mut func);
+    let expected = [
+      copy(1, 2),
+      Inst::Binary {
+        dest: Reg(2),
+        op: Operator::Mul,
+        lhs: Operand::Reg(Reg(0)),
+        rhs: Operand::Imm(2),
+      },
+      Inst::Binary {
+        dest: Reg(1),
+        op: Operator::Sub,
+        lhs: Operand::Imm(2),
+        rhs: Operand::Reg(Reg(2)),
+      },
+      binary(3, Operator::Add, 1, 1),
+    ];
+    assert_eq!(func.blocks[0].insts, expected);
+    assert_eq!(func.blocks[0].term, ret);
+  }
+
+  #[test]
+  fn test_const_prop_errors() {
+    // operations that would fail at runtime are not folded
+    let ret = Terminator::Ret {
+      val: Operand::Reg(Reg(3)),
+    };
+    let insts = vec![copy(1, 1), copy(2, 0), binary(3, Operator::Div, 1, 2)];
+    let mut func = function(insts, ret.clone());
+    ConstPropagator::new().propagate(&mut func);
+    assert_eq!(func.blocks[0].term, ret);
+    let insts = vec![copy(1, i32::MAX), copy(2, 1), binary(3, Operator::Add, 1, 2)];
+    let mut func = function(insts.clone(), ret.clone());
+    ConstPropagator::new().propagate(&mut func);
+    assert_eq!(func.blocks[0].insts[2], copy(3, i32::MIN));
+    let mut func = function(insts, ret.clone());
+    let mut propagator = ConstPropagator::new();
+    propagator.set_overflow_mode(OverflowMode::Panicking);
+    propagator.propagate(&mut func);
+    assert_eq!(func.blocks[0].term, ret);
+  }
+
+  #[test]
+  fn test_const_branch() {
+    let program = "f(x) { y := 2 if y - 2 { x = 1 } else { x = 2 } return x }";
+    let mut parser = Parser::from_str(program);
+    let mut func = IrGen::new().generate(&parser.parse_next().unwrap()).unwrap();
+    ConstPropagator::new().propagate(&mut func);
+    assert_eq!(func.blocks[0].term, Terminator::Jump { target: Label(2) });
+    // constants are not propagated across blocks
+    assert_eq!(
+      func.blocks[3].term,
+      Terminator::Ret {
+        val: Operand::Reg(Reg(0)),
+      },
+    );
+  }
+}
@@ -0,0 +1,198 @@
+use crate::ir::cfg::Cfg;
+use crate::ir::liveness::Liveness;
+use crate::ir::{BasicBlock, Function, Inst, Operand, Reg};
+use std::collections::HashMap;
+
+/// Copy propagator, replaces uses of registers copied from other
+/// registers with the source registers, and removes copies whose
+/// destinations are no longer used, until reaching the fixed point.
+///
+/// Copies are only propagated inside basic blocks, and are invalidated
+/// once their source or destination register is redefined.
+#[derive(Default)]
+pub struct CopyPropagator;
+
+impl CopyPropagator {
+  /// Creates a new copy propagator.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Performs copy propagation on the specific function.
+  pub fn propagate(&self, func: &mut Function) {
+    loop {
+      let mut changed = false;
+      for block in &mut func.blocks {
+        changed |= propagate_block(block);
+      }
+      changed |= remove_dead_copies(func);
+      if !changed {
+        break;
+      }
+    }
+  }
+}
+
+/// Propagates copies inside the specific block,
+/// returns `true` if any operand has been replaced.
+fn propagate_block(block: &mut BasicBlock) -> bool {
+  // source registers of all available copies
+  let mut copies: HashMap<Reg, Reg> = HashMap::new();
+  let mut changed = false;
+  let mut replace = |opr: &mut Operand, copies: &HashMap<Reg, Reg>| {
+    if let Some(src) = opr.reg().and_then(|reg| copies.get(&reg)) {
+      *opr = Operand::Reg(*src);
+      changed = true;
+    }
+  };
+  for inst in &mut block.insts {
+    for opr in inst.operands_mut() {
+      replace(opr, &copies);
+    }
+    if let Some(def) = inst.def() {
+      copies.retain(|dest, src| *dest != def && *src != def);
+      if let Inst::Copy {
+        dest,
+        src: Operand::Reg(src),
+      } = inst
+      {
+        if dest != src {
+          copies.insert(*dest, *src);
+        }
+      }
+    }
+  }
+  for opr in block.term.operands_mut() {
+    replace(opr, &copies);
+  }
+  changed
+}
+
+/// Removes copies whose destinations are dead after them,
+/// returns `true` if any copy has been removed.
+fn remove_dead_copies(func: &mut Function) -> bool {
+  let liveness = Liveness::new(func, &Cfg::new(func));
+  let mut changed = false;
+  for block in &mut func.blocks {
+    let label = block.label;
+    let mut index = 0;
+    block.insts.retain(|inst| {
+      let live = liveness.inst_live_out(label, index).unwrap();
+      index += 1;
+      let dead = match inst {
+        Inst::Copy { dest, src } => !live.contains(dest) || src.reg() == Some(*dest),
+        _ => false,
+      };
+      changed |= dead;
+      !dead
+    });
+  }
+  changed
+}
+
+#[cfg(test)]
+mod test {
+  use super::CopyPropagator;
+  use crate::define::Operator;
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::interp::IrInterpreter;
+  use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+
+  /// Creates a copy instruction.
+  fn copy(dest: u32, src: Operand) -> Inst {
+    Inst::Copy {
+      dest: Reg(dest),
+      src,
+    }
+  }
+
+  /// Creates a function with the specific blocks and one parameter.
+  fn function(blocks: Vec<BasicBlock>) -> Function {
+    let mut func = Function::new("f".into());
+    func.params = vec![Reg(0)];
+    func.blocks = blocks;
+    func.reg_num = 8;
+    func
+  }
+
+  #[test]
+  fn test_copy_chain() {
+    let mut func = function(vec![BasicBlock {
+      label: Label(0),
+      insts: vec![
+        copy(1, Operand::Reg(Reg(0))),
+        copy(2, Operand::Reg(Reg(1))),
+        copy(3, Operand::Reg(Reg(2))),
+        Inst::Binary {
+          dest: Reg(4),
+          op: Operator::Add,
+          lhs: Operand::Reg(Reg(3)),
+          rhs: Operand::Reg(Reg(2)),
+        },
+      ],
+      term: Terminator::Ret {
+        val: Operand::Reg(Reg(4)),
+      },
+    }]);
+    CopyPropagator::new().propagate(&mut func);
+    assert_eq!(
+      func.blocks[0].insts,
+      [Inst::Binary {
+        dest: Reg(4),
+        op: Operator::Add,
+        lhs: Operand::Reg(Reg(0)),
+        rhs: Operand::Reg(Reg(0)),
+      }],
+    );
+  }
+
+  #[test]
+  fn test_copy_redefined() {
+    // `%1` still holds the old value of `%0` after `%0` is redefined
+    let block = BasicBlock {
+      label: Label(0),
+      insts: vec![copy(1, Operand::Reg(Reg(0))), copy(0, Operand::Imm(5))],
+      term: Terminator::Jump { target: Label(1) },
+    };
+    // copies are not propagated across blocks
+    let next = BasicBlock {
+      label: Label(1),
+      insts: vec![Inst::Binary {
+        dest: Reg(2),
+        op: Operator::Sub,
+        lhs: Operand::Reg(Reg(1)),
+        rhs: Operand::Reg(Reg(0)),
+      }],
+      term: Terminator::Ret {
+        val: Operand::Reg(Reg(2)),
+      },
+    };
+    let blocks = vec![block, next];
+    let mut func = function(blocks.clone());
+    CopyPropagator::new().propagate(&mut func);
+    assert_eq!(func.blocks, blocks);
+  }
+
+  #[test]
+  fn test_copy_program() {
+    let program = r#"
+      f(n) { x := n y := x if y { z := y x = z + 1 } else { x = y } return x * y }
+      main() { return f(4) - f(0) }
+    "#;
+    let mut parser = Parser::from_str(program);
+    let mut intp = IrInterpreter::new();
+    let mut copies = 0;
+    for ast in parser.parse_program().unwrap() {
+      let mut func = IrGen::new().generate(&ast).unwrap();
+      CopyPropagator::new().propagate(&mut func);
+      let insts = func.blocks.iter().flat_map(|block| &block.insts);
+      copies += insts.filter(|inst| matches!(inst, Inst::Copy { .. })).count();
+      intp.add_func(func).unwrap();
+    }
+    // `x := n` is removed, while `y := x` is kept since `y` is used in
+    // other blocks, copies to `x` in both branches are kept as well
+    assert_eq!(copies, 3);
+    assert_eq!(intp.eval(), Ok(20));
+  }
+}
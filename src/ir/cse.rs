@@ -0,0 +1,139 @@
+use crate::ir::{BasicBlock, Function, Inst, Operand};
+
+/// Common subexpression eliminator, replaces pure instructions which
+/// compute the same operation on the same operands as previous ones
+/// in the same basic block with copies of the previous results.
+///
+/// Previous results are forgotten once their operands or result registers
+/// are redefined, and at function calls and memory accesses.
+#[derive(Default)]
+pub struct CommonSubexprEliminator;
+
+impl CommonSubexprEliminator {
+  /// Creates a new common subexpression eliminator.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Eliminates common subexpressions in the specific function.
+  pub fn eliminate(&self, func: &mut Function) {
+    for block in &mut func.blocks {
+      eliminate_block(block);
+    }
+  }
+}
+
+/// Eliminates common subexpressions in the specific block.
+fn eliminate_block(block: &mut BasicBlock) {
+  // available pure instructions
+  let mut avail: Vec<Inst> = Vec::new();
+  for inst in &mut block.insts {
+    if let Some(prev) = avail.iter().find(|prev| same_expr(prev, inst)) {
+      *inst = Inst::Copy {
+        dest: inst.def().unwrap(),
+        src: Operand::Reg(prev.def().unwrap()),
+      };
+    }
+    match inst {
+      Inst::Call { .. } | Inst::Load { .. } | Inst::Store { .. } => avail.clear(),
+      _ => (),
+    }
+    if let Some(def) = inst.def() {
+      avail.retain(|prev| prev.def() != Some(def) && !prev.uses().contains(&def));
+      if is_pure(inst) && !inst.uses().contains(&def) {
+        avail.push(inst.clone());
+      }
+    }
+  }
+}
+
+/// Checks if the specific instruction is a pure operation on its operands.
+fn is_pure(inst: &Inst) -> bool {
+  matches!(inst, Inst::Binary { .. } | Inst::Unary { .. })
+}
+
+/// Checks if the specific instructions compute the same operation
+/// on the same operands.
+fn same_expr(lhs: &Inst, rhs: &Inst) -> bool {
+  match (lhs, rhs) {
+    (
+      Inst::Binary {
+        op: op1,
+        lhs: lhs1,
+        rhs: rhs1,
+        ..
+      },
+      Inst::Binary {
+        op: op2,
+        lhs: lhs2,
+        rhs: rhs2,
+        ..
+      },
+    ) => op1 == op2 && lhs1 == lhs2 && rhs1 == rhs2,
+    (
+      Inst::Unary {
+        op: op1, opr: opr1, ..
+      },
+      Inst::Unary {
+        op: op2, opr: opr2, ..
+      },
+    ) => op1 == op2 && opr1 == opr2,
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::CommonSubexprEliminator;
+  use crate::define::Operator;
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::interp::IrInterpreter;
+  use crate::ir::{Function, Inst, Operand, Reg};
+
+  /// Generates IR of the specific function definition,
+  /// and eliminates common subexpressions.
+  fn generate(program: &str) -> Function {
+    let mut parser = Parser::from_str(program);
+    let mut func = IrGen::new().generate(&parser.parse_next().unwrap()).unwrap();
+    CommonSubexprEliminator::new().eliminate(&mut func);
+    func
+  }
+
+  /// Counts binary instructions of the specific operator.
+  fn count(func: &Function, op: Operator) -> usize {
+    let insts = func.blocks.iter().flat_map(|block| &block.insts);
+    insts
+      .filter(|inst| matches!(inst, Inst::Binary { op: o, .. } if *o == op))
+      .count()
+  }
+
+  #[test]
+  fn test_cse() {
+    let func = generate("f(a, b) { x := a + b y := a + b return x * y - -a * -a }");
+    assert_eq!(count(&func, Operator::Add), 1);
+    let copy = Inst::Copy {
+      dest: Reg(4),
+      src: Operand::Reg(Reg(2)),
+    };
+    assert_eq!(func.blocks[0].insts[2], copy);
+    let negs = func.blocks[0].insts.iter();
+    assert_eq!(negs.filter(|inst| matches!(inst, Inst::Unary { .. })).count(), 1);
+    let mut intp = IrInterpreter::new();
+    intp.add_func(func).unwrap();
+    assert_eq!(intp.call(&"f".into(), &[2, 3]), Ok(21));
+  }
+
+  #[test]
+  fn test_cse_boundaries() {
+    // operands are redefined
+    let func = generate("f(a, b) { x := a + b a = 1 y := a + b return x * y }");
+    assert_eq!(count(&func, Operator::Add), 2);
+    // function calls
+    let func = generate("f(a, b) { x := a * b print(x) y := a * b return x + y }");
+    assert_eq!(count(&func, Operator::Mul), 2);
+    // different blocks
+    let func = generate("f(a, b) { x := a * b if x { x = a * b } return x }");
+    assert_eq!(count(&func, Operator::Mul), 2);
+  }
+}
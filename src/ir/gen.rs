@@ -0,0 +1,608 @@
+use crate::define::{AstBox, AstVisitor, NestedMap, Operator, Symbol, Type};
+use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+use std::convert::TryFrom;
+
+/// IR generator, generates three-address-code from ASTs.
+///
+/// Each variable is stored in its own virtual register,
+/// and each intermediate result is stored in a new register.
+///
+/// Integers of the IR are 32-bit. Programs parsed by the default 32-bit
+/// lexer always fit, wide literals and case values accepted by 64-bit
+/// lexers are reported as errors.
+#[derive(Default)]
+pub struct IrGen {
+  /// Function being generated.
+  func: Option<Function>,
+  /// Number of labels in the current function.
+  label_num: u32,
+  /// Label and instructions of the current unterminated block.
+  cur: Option<(Label, Vec<Inst>)>,
+  /// Registers of all variables.
+  vars: NestedMap<Symbol, Reg>,
+}
+
+/// `Result` for `IrGen`.
+pub type Result = std::result::Result<Operand, &'static str>;
+
+impl IrGen {
+  /// Creates a new IR generator.
+  pub fn new() -> Self {
+    Self {
+      func: None,
+      label_num: 0,
+      cur: None,
+      vars: NestedMap::new(),
+    }
+  }
+
+  /// Generates IR of the specific function definition.
+  pub fn generate(&mut self, ast: &AstBox) -> std::result::Result<Function, &'static str> {
+    // drop the state left by the previous failed generation
+    *self = Self::new();
+    self.visit(ast)?;
+    self.func.take().ok_or("expected function definition")
+  }
+
+  /// Gets the current function.
+  fn func(&mut self) -> &mut Function {
+    self.func.as_mut().unwrap()
+  }
+
+  /// Creates a new label.
+  fn new_label(&mut self) -> Label {
+    self.label_num += 1;
+    Label(self.label_num - 1)
+  }
+
+  /// Starts a new block with the specific label,
+  /// the following instructions will be pushed to it.
+  /// The current block falls through to the new block.
+  fn start_block(&mut self, label: Label) {
+    self.jump_to(label);
+    self.cur = Some((label, Vec::new()));
+  }
+
+  /// Gets instructions of the current block.
+  /// Starts a new unreachable block if the current block
+  /// has been terminated (code after return statements).
+  fn cur_insts(&mut self) -> &mut Vec<Inst> {
+    if self.cur.is_none() {
+      let label = self.new_label();
+      self.cur = Some((label, Vec::new()));
+    }
+    &mut self.cur.as_mut().unwrap().1
+  }
+
+  /// Pushes the specific instruction to the current block.
+  fn push_inst(&mut self, inst: Inst) {
+    self.cur_insts().push(inst);
+  }
+
+  /// Terminates the current block with the specific terminator.
+  fn terminate(&mut self, term: Terminator) {
+    self.cur_insts();
+    let (label, insts) = self.cur.take().unwrap();
+    self.func().blocks.push(BasicBlock { label, insts, term });
+  }
+
+  /// Terminates the current block with a jump to the specific label,
+  /// does nothing if the current block has been terminated.
+  fn jump_to(&mut self, target: Label) {
+    if self.cur.is_some() {
+      self.terminate(Terminator::Jump { target });
+    }
+  }
+
+  /// Generates short-circuiting logical operations, the result
+  /// is the value of the last evaluated operand.
+  fn gen_logical(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Result {
+    // evaluate lhs first
+    let lhs = self.visit(lhs)?;
+    let dest = self.func().new_reg();
+    self.push_inst(Inst::Copy { dest, src: lhs });
+    // check if need to evaluate rhs
+    let rhs_label = self.new_label();
+    let end_label = self.new_label();
+    let (then, else_then) = if *op == Operator::LAnd {
+      (rhs_label, end_label)
+    } else {
+      (end_label, rhs_label)
+    };
+    self.terminate(Terminator::Branch {
+      cond: lhs,
+      then,
+      else_then,
+    });
+    // evaluate rhs
+    self.start_block(rhs_label);
+    let src = self.visit(rhs)?;
+    self.push_inst(Inst::Copy { dest, src });
+    self.start_block(end_label);
+    Ok(Operand::Reg(dest))
+  }
+
+  /// Gets the register of the specific variable.
+  fn var(&self, name: &Symbol) -> std::result::Result<Reg, &'static str> {
+    self.vars.get_rec(name).copied().ok_or("symbol has not been defined")
+  }
+}
+
+impl AstVisitor for IrGen {
+  type Result = Result;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.func = Some(Function::new(*name));
+    self.label_num = 0;
+    // allocate registers for parameters
+    self.vars.push();
+    for arg in args {
+      let reg = self.func().new_reg();
+      self.func().params.push(reg);
+      if !self.vars.add(*arg, reg) {
+        return Err("symbol has already been defined");
+      }
+    }
+    // generate body, the default return value is zero
+    let entry = self.new_label();
+    self.start_block(entry);
+    self.visit(body)?;
+    if self.cur.is_some() {
+      self.terminate(Terminator::Ret { val: Operand::Imm(0) });
+    }
+    self.vars.pop();
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    self.vars.push();
+    for stmt in stmts {
+      self.visit(stmt)?;
+    }
+    self.vars.pop();
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    let src = self.visit(expr)?;
+    let dest = self.func().new_reg();
+    if !self.vars.add(*name, dest) {
+      return Err("symbol has already been defined");
+    }
+    self.push_inst(Inst::Copy { dest, src });
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    let src = self.visit(expr)?;
+    let dest = self.var(name)?;
+    self.push_inst(Inst::Copy { dest, src });
+    Ok(Operand::Reg(dest))
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    let cond = self.visit(cond)?;
+    let then_label = self.new_label();
+    let else_label = else_then.as_ref().map(|_| self.new_label());
+    let end_label = self.new_label();
+    self.terminate(Terminator::Branch {
+      cond,
+      then: then_label,
+      else_then: else_label.unwrap_or(end_label),
+    });
+    // generate the then branch
+    self.start_block(then_label);
+    self.visit(then)?;
+    self.jump_to(end_label);
+    // generate the else branch
+    if let (Some(else_then), Some(else_label)) = (else_then, else_label) {
+      self.start_block(else_label);
+      self.visit(else_then)?;
+      self.jump_to(end_label);
+    }
+    self.start_block(end_label);
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    let val = self.visit(scrutinee)?;
+    let end_label = self.new_label();
+    // compare with values of cases one by one
+    for (case, body) in cases {
+      // the IR only supports 32-bit integers
+      let case = i32::try_from(*case).map_err(|_| "integer literal too large")?;
+      let cond = self.func().new_reg();
+      self.push_inst(Inst::Binary {
+        dest: cond,
+        op: Operator::Eq,
+        lhs: val,
+        rhs: Operand::Imm(case),
+      });
+      let body_label = self.new_label();
+      let next_label = self.new_label();
+      self.terminate(Terminator::Branch {
+        cond: Operand::Reg(cond),
+        then: body_label,
+        else_then: next_label,
+      });
+      self.start_block(body_label);
+      self.visit(body)?;
+      self.jump_to(end_label);
+      self.start_block(next_label);
+    }
+    // generate the default case
+    if let Some(default) = default {
+      self.visit(default)?;
+    }
+    self.start_block(end_label);
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    let body_label = self.new_label();
+    let end_label = self.new_label();
+    // generate the body
+    self.start_block(body_label);
+    self.visit(body)?;
+    // generate the condition, and branch back to the body
+    let cond = self.visit(cond)?;
+    self.terminate(Terminator::Branch {
+      cond,
+      then: body_label,
+      else_then: end_label,
+    });
+    self.start_block(end_label);
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    let val = match expr {
+      Some(expr) => self.visit(expr)?,
+      None => Operand::Imm(0),
+    };
+    self.terminate(Terminator::Ret { val });
+    Ok(Operand::Imm(0))
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    if *op == Operator::LAnd || *op == Operator::LOr {
+      return self.gen_logical(op, lhs, rhs);
+    }
+    if *op == Operator::Pow {
+      return Err("power operator is not supported");
+    }
+    let lhs = self.visit(lhs)?;
+    let rhs = self.visit(rhs)?;
+    let dest = self.func().new_reg();
+    self.push_inst(Inst::Binary {
+      dest,
+      op: op.clone(),
+      lhs,
+      rhs,
+    });
+    Ok(Operand::Reg(dest))
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    let opr = self.visit(opr)?;
+    let dest = self.func().new_reg();
+    self.push_inst(Inst::Unary {
+      dest,
+      op: op.clone(),
+      opr,
+    });
+    Ok(Operand::Reg(dest))
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    let args = args
+      .iter()
+      .map(|arg| self.visit(arg))
+      .collect::<std::result::Result<_, _>>()?;
+    let dest = self.func().new_reg();
+    self.push_inst(Inst::Call {
+      dest,
+      func: *name,
+      args,
+    });
+    Ok(Operand::Reg(dest))
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    // the IR only supports 32-bit integers
+    i32::try_from(*val)
+      .map(Operand::Imm)
+      .map_err(|_| "integer literal too large")
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    Ok(Operand::Reg(self.var(val)?))
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {
+    Ok(Operand::Imm(0))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::IrGen;
+  use crate::define::Operator;
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use crate::ir::interp::IrInterpreter;
+  use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+
+  /// Generates IR of the specific function definition.
+  fn generate(program: &str) -> Function {
+    let mut parser = Parser::from_str(program);
+    IrGen::new().generate(&parser.parse_next().unwrap()).unwrap()
+  }
+
+  #[test]
+  fn test_gen() {
+    let func = generate("add(a, b) { return a + b }");
+    assert_eq!(func.name, "add");
+    assert_eq!(func.params, [Reg(0), Reg(1)]);
+    assert_eq!(func.blocks.len(), 1);
+    assert_eq!(func.blocks[0].label, Label(0));
+    assert_eq!(
+      func.blocks[0].insts,
+      [Inst::Binary {
+        dest: Reg(2),
+        op: Operator::Add,
+        lhs: Operand::Reg(Reg(0)),
+        rhs: Operand::Reg(Reg(1)),
+      }],
+    );
+    assert_eq!(
+      func.blocks[0].term,
+      Terminator::Ret {
+        val: Operand::Reg(Reg(2)),
+      },
+    );
+  }
+
+  #[test]
+  fn test_gen_vars() {
+    let func = generate("f(a) { x := 1 x = f(x) + -a return x }");
+    assert_eq!(
+      func.blocks[0].insts,
+      [
+        Inst::Copy {
+          dest: Reg(1),
+          src: Operand::Imm(1),
+        },
+        Inst::Call {
+          dest: Reg(2),
+          func: "f".into(),
+          args: vec![Operand::Reg(Reg(1))],
+        },
+        Inst::Unary {
+          dest: Reg(3),
+          op: Operator::Sub,
+          opr: Operand::Reg(Reg(0)),
+        },
+        Inst::Binary {
+          dest: Reg(4),
+          op: Operator::Add,
+          lhs: Operand::Reg(Reg(2)),
+          rhs: Operand::Reg(Reg(3)),
+        },
+        Inst::Copy {
+          dest: Reg(1),
+          src: Operand::Reg(Reg(4)),
+        },
+      ],
+    );
+    assert_eq!(
+      func.blocks[0].term,
+      Terminator::Ret {
+        val: Operand::Reg(Reg(1)),
+      },
+    );
+    assert_eq!(func.reg_num, 5);
+    // undefined variables
+    let mut parser = Parser::from_str("f() { return x }");
+    let ast = parser.parse_next().unwrap();
+    assert_eq!(IrGen::new().generate(&ast), Err("symbol has not been defined"));
+    // wide literals are rejected by the default lexer with their positions
+    let err = Parser::from_str("f() {\n  return 3000000000\n}").parse_next().err().unwrap();
+    assert_eq!(err.to_string(), "integer literal too large at line 2, col 10");
+    // integers of the IR are 32-bit, even if the lexer accepts 64-bit literals
+    let parse64 = |program| {
+      let mut lexer = Lexer::from_str(program);
+      lexer.set_int64(true);
+      Parser::new(lexer).parse_next().unwrap()
+    };
+    let ast = parse64("f() { return 3000000000 }");
+    assert_eq!(IrGen::new().generate(&ast), Err("integer literal too large"));
+    let ast = parse64("f(x) { switch x { case 3000000000: return 1 } }");
+    assert_eq!(IrGen::new().generate(&ast), Err("integer literal too large"));
+    // generators can be reused after errors
+    let mut parser = Parser::from_str("f(x) { if x { y := 1 return z } } g(a) { return a + 1 }");
+    let (f, g) = (parser.parse_next().unwrap(), parser.parse_next().unwrap());
+    let mut gen = IrGen::new();
+    assert_eq!(gen.generate(&f), Err("symbol has not been defined"));
+    assert_eq!(gen.generate(&g), IrGen::new().generate(&g));
+  }
+
+  /// Creates a basic block with no instructions.
+  fn block(label: u32, term: Terminator) -> BasicBlock {
+    BasicBlock {
+      label: Label(label),
+      insts: Vec::new(),
+      term,
+    }
+  }
+
+  #[test]
+  fn test_gen_if() {
+    let func = generate("f(x) { if x { x = 1 } else { x = 2 } return x }");
+    let copy = |val| Inst::Copy {
+      dest: Reg(0),
+      src: Operand::Imm(val),
+    };
+    let jump = |target| Terminator::Jump {
+      target: Label(target),
+    };
+    assert_eq!(
+      func.blocks,
+      [
+        // condition block
+        block(
+          0,
+          Terminator::Branch {
+            cond: Operand::Reg(Reg(0)),
+            then: Label(1),
+            else_then: Label(2),
+          },
+        ),
+        // then block
+        BasicBlock {
+          insts: vec![copy(1)],
+          ..block(1, jump(3))
+        },
+        // else block
+        BasicBlock {
+          insts: vec![copy(2)],
+          ..block(2, jump(3))
+        },
+        // merge block
+        block(
+          3,
+          Terminator::Ret {
+            val: Operand::Reg(Reg(0)),
+          },
+        ),
+      ],
+    );
+    // without else branch
+    let func = generate("f(x) { if x { return 1 } }");
+    assert_eq!(
+      func.blocks,
+      [
+        block(
+          0,
+          Terminator::Branch {
+            cond: Operand::Reg(Reg(0)),
+            then: Label(1),
+            else_then: Label(2),
+          },
+        ),
+        block(1, Terminator::Ret { val: Operand::Imm(1) }),
+        block(2, Terminator::Ret { val: Operand::Imm(0) }),
+      ],
+    );
+  }
+
+  #[test]
+  fn test_gen_dead_code() {
+    // code after return statements is placed in unreachable blocks
+    let func = generate("f() { return 1 x := 2 return x }");
+    assert_eq!(func.blocks.len(), 2);
+    assert_eq!(func.blocks[0].term, Terminator::Ret { val: Operand::Imm(1) });
+    assert_eq!(func.blocks[1].label, Label(1));
+    assert_eq!(func.blocks[1].insts.len(), 1);
+  }
+
+  #[test]
+  fn test_gen_logical() {
+    let func = generate("f(a, b) { return a && f(b, a) || b }");
+    let cond = |reg, then, else_then| Terminator::Branch {
+      cond: Operand::Reg(Reg(reg)),
+      then: Label(then),
+      else_then: Label(else_then),
+    };
+    let copy = |dest, src| Inst::Copy {
+      dest: Reg(dest),
+      src: Operand::Reg(Reg(src)),
+    };
+    assert_eq!(
+      func.blocks,
+      [
+        // `a && ...`, `f(b, a)` is not evaluated here
+        BasicBlock {
+          insts: vec![copy(2, 0)],
+          ..block(0, cond(0, 1, 2))
+        },
+        BasicBlock {
+          insts: vec![
+            Inst::Call {
+              dest: Reg(3),
+              func: "f".into(),
+              args: vec![Operand::Reg(Reg(1)), Operand::Reg(Reg(0))],
+            },
+            copy(2, 3),
+          ],
+          ..block(1, Terminator::Jump { target: Label(2) })
+        },
+        // `... || b`
+        BasicBlock {
+          insts: vec![copy(4, 2)],
+          ..block(2, cond(2, 4, 3))
+        },
+        BasicBlock {
+          insts: vec![copy(4, 1)],
+          ..block(3, Terminator::Jump { target: Label(4) })
+        },
+        block(
+          4,
+          Terminator::Ret {
+            val: Operand::Reg(Reg(4)),
+          },
+        ),
+      ],
+    );
+  }
+
+  #[test]
+  fn test_gen_switch() {
+    let program = r#"
+      f(x) {
+        switch x {
+          case 1: x = 10
+          case -1: return 20
+          default: x = x * 2
+        }
+        return x
+      }
+    "#;
+    let mut intp = IrInterpreter::new();
+    intp.add_func(generate(program)).unwrap();
+    assert_eq!(intp.call(&"f".into(), &[1]), Ok(10));
+    assert_eq!(intp.call(&"f".into(), &[-1]), Ok(20));
+    assert_eq!(intp.call(&"f".into(), &[3]), Ok(6));
+  }
+
+  #[test]
+  fn test_gen_do_while() {
+    let program = "f(n) { s := 0 do { s = s + n n = n - 1 } while 0 < n return s }";
+    let func = generate(program);
+    // the body branches back to itself
+    assert!(matches!(
+      func.blocks[1].term,
+      Terminator::Branch {
+        then: Label(1),
+        else_then: Label(2),
+        ..
+      }
+    ));
+    let mut intp = IrInterpreter::new();
+    intp.add_func(func).unwrap();
+    assert_eq!(intp.call(&"f".into(), &[4]), Ok(10));
+    assert_eq!(intp.call(&"f".into(), &[-1]), Ok(-1));
+  }
+}
@@ -0,0 +1,280 @@
+use crate::define::{eval_binary, eval_unary, OverflowMode, Symbol};
+use crate::ir::{Function, Inst, Label, Operand, Terminator};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Interpreter that executes the IR directly.
+pub struct IrInterpreter {
+  /// All functions.
+  funcs: HashMap<Symbol, Rc<FuncInfo>>,
+  /// Current depth of function calls.
+  depth: usize,
+  /// Maximum depth of function calls.
+  max_depth: usize,
+  /// Overflow handling mode of integer arithmetic.
+  overflow_mode: OverflowMode,
+}
+
+/// `Result` for `IrInterpreter`.
+pub type Result = std::result::Result<i32, &'static str>;
+
+/// Default maximum depth of function calls.
+///
+/// Calls are executed on the Rust stack, each call takes about 2 KiB
+/// in debug builds, so this fits the 2 MiB stack of spawned threads.
+const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Function and indices of its blocks.
+struct FuncInfo {
+  /// Function.
+  func: Function,
+  /// Indices of blocks in the function.
+  blocks: HashMap<Label, usize>,
+}
+
+/// Stack frame of function calls.
+struct Frame {
+  /// Register file.
+  regs: Vec<i32>,
+  /// Stack slots.
+  slots: Vec<i32>,
+}
+
+impl Frame {
+  /// Gets the value of the specific operand.
+  fn value(&self, opr: &Operand) -> i32 {
+    match opr {
+      Operand::Reg(reg) => self.regs[reg.0 as usize],
+      Operand::Imm(val) => *val,
+    }
+  }
+}
+
+impl IrInterpreter {
+  /// Creates a new IR interpreter.
+  pub fn new() -> Self {
+    Self {
+      funcs: HashMap::new(),
+      depth: 0,
+      max_depth: DEFAULT_MAX_DEPTH,
+      overflow_mode: OverflowMode::default(),
+    }
+  }
+
+  /// Sets the maximum depth of function calls.
+  pub fn set_max_depth(&mut self, max_depth: usize) {
+    self.max_depth = max_depth;
+  }
+
+  /// Sets the overflow handling mode of integer arithmetic.
+  pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+    self.overflow_mode = mode;
+  }
+
+  /// Adds the specific function to the interpreter.
+  pub fn add_func(&mut self, func: Function) -> std::result::Result<(), &'static str> {
+    if self.funcs.contains_key(&func.name) {
+      return Err("function has already been defined");
+    }
+    let blocks = func
+      .blocks
+      .iter()
+      .enumerate()
+      .map(|(i, block)| (block.label, i))
+      .collect();
+    self.funcs.insert(func.name, Rc::new(FuncInfo { func, blocks }));
+    Ok(())
+  }
+
+  /// Evaluates the `main` function.
+  pub fn eval(&mut self) -> Result {
+    self.call(&Symbol::new("main"), &[])
+  }
+
+  /// Calls the specific function with arguments.
+  pub fn call(&mut self, name: &Symbol, args: &[i32]) -> Result {
+    if let Some(ret) = call_lib_func(name, args)? {
+      return Ok(ret);
+    }
+    let info = match self.funcs.get(name) {
+      Some(info) => info.clone(),
+      None if *name == "main" => return Err("'main' function not found"),
+      None => return Err("function not found"),
+    };
+    let func = &info.func;
+    if func.params.len() != args.len() {
+      return Err("argument count mismatch");
+    }
+    if self.depth >= self.max_depth {
+      return Err("maximum recursion depth exceeded");
+    }
+    // set up the stack frame
+    let mut frame = Frame {
+      regs: vec![0; func.reg_num as usize],
+      slots: vec![0; func.slot_num],
+    };
+    for (param, arg) in func.params.iter().zip(args) {
+      frame.regs[param.0 as usize] = *arg;
+    }
+    self.depth += 1;
+    let ret = self.exec(&info, &mut frame);
+    self.depth -= 1;
+    ret
+  }
+
+  /// Executes the specific function in the stack frame.
+  fn exec(&mut self, info: &FuncInfo, frame: &mut Frame) -> Result {
+    let mut block = info.func.blocks.first().ok_or("empty function")?;
+    loop {
+      for inst in &block.insts {
+        self.exec_inst(inst, frame)?;
+      }
+      let target = match &block.term {
+        Terminator::Jump { target } => target,
+        Terminator::Branch {
+          cond,
+          then,
+          else_then,
+        } => {
+          if frame.value(cond) != 0 {
+            then
+          } else {
+            else_then
+          }
+        }
+        Terminator::Ret { val } => return Ok(frame.value(val)),
+      };
+      block = &info.func.blocks[*info.blocks.get(target).ok_or("invalid label")?];
+    }
+  }
+
+  /// Executes the specific instruction in the stack frame.
+  fn exec_inst(&mut self, inst: &Inst, frame: &mut Frame) -> std::result::Result<(), &'static str> {
+    let (dest, val) = match inst {
+      Inst::Copy { dest, src } => (dest, frame.value(src)),
+      Inst::Binary { dest, op, lhs, rhs } => {
+        let val = eval_binary(op, frame.value(lhs), frame.value(rhs), self.overflow_mode)?;
+        (dest, val)
+      }
+      Inst::Unary { dest, op, opr } => {
+        (dest, eval_unary(op, frame.value(opr), self.overflow_mode)?)
+      }
+      Inst::Load { dest, slot } => (dest, frame.slots[*slot]),
+      Inst::Store { src, slot } => {
+        frame.slots[*slot] = frame.value(src);
+        return Ok(());
+      }
+      Inst::Call { dest, func, args } => {
+        let args: Vec<_> = args.iter().map(|arg| frame.value(arg)).collect();
+        (dest, self.call(func, &args)?)
+      }
+    };
+    frame.regs[dest.0 as usize] = val;
+    Ok(())
+  }
+}
+
+impl Default for IrInterpreter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Performs library function call.
+fn call_lib_func(name: &Symbol, args: &[i32]) -> std::result::Result<Option<i32>, &'static str> {
+  match name.as_str() {
+    "input" => {
+      if !args.is_empty() {
+        return Err("argument count mismatch");
+      }
+      // read an integer from stdin
+      let mut line = String::new();
+      std::io::stdin()
+        .read_line(&mut line)
+        .expect("failed to read from stdin");
+      match line.trim().parse::<i32>() {
+        Ok(ret) => Ok(Some(ret)),
+        _ => Err("invalid input, expected integer"),
+      }
+    }
+    "print" => {
+      if args.len() != 1 {
+        return Err("argument count mismatch");
+      }
+      println!("{}", args[0]);
+      Ok(Some(0))
+    }
+    _ => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::IrInterpreter;
+  use crate::back::interpreter::Interpreter;
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+
+  /// Compiles the specific program to IR.
+  fn compile(program: &str) -> IrInterpreter {
+    let mut parser = Parser::from_str(program);
+    let mut intp = IrInterpreter::new();
+    for ast in parser.parse_program().unwrap() {
+      intp.add_func(IrGen::new().generate(&ast).unwrap()).unwrap();
+    }
+    intp
+  }
+
+  #[test]
+  fn test_ir_interp() {
+    let program = r#"
+      fib(n) {
+        if n <= 2 { return 1 } else { return fib(n - 1) + fib(n - 2) }
+      }
+      fact(n) {
+        r := 1
+        if n { r = n * fact(n - 1) }
+        return r
+      }
+      main() {
+        return fib(15) - fact(5) * -(!0 + 1 == 2)
+      }
+    "#;
+    // compare with the tree-walking interpreter
+    let mut parser = Parser::from_str(program);
+    let mut tree_intp = Interpreter::new();
+    for ast in parser.parse_program().unwrap() {
+      tree_intp.add_func_def(ast).unwrap();
+    }
+    let expected = tree_intp.eval();
+    assert_eq!(expected, Ok(610 + 120));
+    let mut intp = compile(program);
+    assert_eq!(intp.eval().map(i64::from), expected);
+    assert_eq!(intp.call(&Symbol::new("fact"), &[6]), Ok(720));
+  }
+
+  #[test]
+  fn test_ir_interp_error() {
+    assert_eq!(compile("main() { return 1 / 0 }").eval(), Err("division by zero"));
+    // logical operators are short-circuiting
+    assert_eq!(compile("main() { return 0 && 1 / 0 }").eval(), Ok(0));
+    assert_eq!(compile("main() { return 2 || 1 / 0 }").eval(), Ok(2));
+    assert_eq!(compile("f() { return 0 }").eval(), Err("'main' function not found"));
+    assert_eq!(compile("main() { return g() }").eval(), Err("function not found"));
+    assert_eq!(
+      compile("f(x) { return x } main() { return f() }").eval(),
+      Err("argument count mismatch"),
+    );
+    let mut intp = compile("f(x) { return f(x) } main() { return f(1) }");
+    intp.set_max_depth(100);
+    assert_eq!(intp.eval(), Err("maximum recursion depth exceeded"));
+  }
+
+  #[test]
+  fn test_default_max_depth() {
+    // test threads have the default stack size of spawned threads
+    let mut intp = compile("f(n) { return 1 + f(n + 1) } main() { return f(0) }");
+    assert_eq!(intp.eval(), Err("maximum recursion depth exceeded"));
+  }
+}
@@ -0,0 +1,174 @@
+use crate::ir::cfg::Cfg;
+use crate::ir::{BasicBlock, Function, Label, Reg};
+use std::collections::{HashMap, HashSet};
+
+/// Set of live registers.
+pub type LiveSet = HashSet<Reg>;
+
+/// Result of liveness analysis of a function.
+pub struct Liveness {
+  /// Registers live at the beginning of each block.
+  live_in: HashMap<Label, LiveSet>,
+  /// Registers live at the end of each block.
+  live_out: HashMap<Label, LiveSet>,
+  /// Registers live after each instruction of each block.
+  inst_live_out: HashMap<Label, Vec<LiveSet>>,
+}
+
+impl Liveness {
+  /// Performs liveness analysis on the specific function.
+  pub fn new(func: &Function, cfg: &Cfg) -> Self {
+    let mut live_in: HashMap<_, LiveSet> = HashMap::new();
+    let mut live_out: HashMap<_, LiveSet> = HashMap::new();
+    // iterate backward until reaching the fixed point
+    let mut changed = true;
+    while changed {
+      changed = false;
+      for block in func.blocks.iter().rev() {
+        let mut out = LiveSet::new();
+        for succ in cfg.succs(block.label) {
+          if let Some(succ_in) = live_in.get(succ) {
+            out.extend(succ_in);
+          }
+        }
+        let (block_in, _) = transfer(block, &out);
+        if live_in.get(&block.label) != Some(&block_in) {
+          live_in.insert(block.label, block_in);
+          changed = true;
+        }
+        live_out.insert(block.label, out);
+      }
+    }
+    // compute live-out sets of instructions
+    let inst_live_out = func
+      .blocks
+      .iter()
+      .map(|block| (block.label, transfer(block, &live_out[&block.label]).1))
+      .collect();
+    Self {
+      live_in,
+      live_out,
+      inst_live_out,
+    }
+  }
+
+  /// Gets registers live at the beginning of the specific block.
+  pub fn live_in(&self, label: Label) -> Option<&LiveSet> {
+    self.live_in.get(&label)
+  }
+
+  /// Gets registers live at the end of the specific block.
+  pub fn live_out(&self, label: Label) -> Option<&LiveSet> {
+    self.live_out.get(&label)
+  }
+
+  /// Gets registers live after the `index`-th instruction of the specific block.
+  pub fn inst_live_out(&self, label: Label, index: usize) -> Option<&LiveSet> {
+    self.inst_live_out.get(&label)?.get(index)
+  }
+}
+
+/// Computes the live-in set of the block from its live-out set,
+/// also returns live-out sets of all instructions.
+fn transfer(block: &BasicBlock, live_out: &LiveSet) -> (LiveSet, Vec<LiveSet>) {
+  let mut live = live_out.clone();
+  live.extend(block.term.uses());
+  let mut inst_live_out = vec![LiveSet::new(); block.insts.len()];
+  for (inst, out) in block.insts.iter().zip(inst_live_out.iter_mut()).rev() {
+    *out = live.clone();
+    if let Some(def) = inst.def() {
+      live.remove(&def);
+    }
+    live.extend(inst.uses());
+  }
+  (live, inst_live_out)
+}
+
+#[cfg(test)]
+mod test {
+  use super::{LiveSet, Liveness};
+  use crate::define::Operator;
+  use crate::ir::cfg::Cfg;
+  use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+
+  /// Creates a live set of the specific registers.
+  fn set(regs: &[u32]) -> LiveSet {
+    regs.iter().map(|reg| Reg(*reg)).collect()
+  }
+
+  #[test]
+  fn test_liveness() {
+    // i := 0, k := 10, then increment i while i < n, finally return i + k
+    let mut func = Function::new("f".into());
+    let n = func.new_reg();
+    let i = func.new_reg();
+    let k = func.new_reg();
+    let c = func.new_reg();
+    let r = func.new_reg();
+    func.params = vec![n];
+    func.blocks = vec![
+      BasicBlock {
+        label: Label(0),
+        insts: vec![
+          Inst::Copy {
+            dest: i,
+            src: Operand::Imm(0),
+          },
+          Inst::Copy {
+            dest: k,
+            src: Operand::Imm(10),
+          },
+        ],
+        term: Terminator::Jump { target: Label(1) },
+      },
+      BasicBlock {
+        label: Label(1),
+        insts: vec![
+          Inst::Binary {
+            dest: i,
+            op: Operator::Add,
+            lhs: Operand::Reg(i),
+            rhs: Operand::Imm(1),
+          },
+          Inst::Binary {
+            dest: c,
+            op: Operator::Less,
+            lhs: Operand::Reg(i),
+            rhs: Operand::Reg(n),
+          },
+        ],
+        term: Terminator::Branch {
+          cond: Operand::Reg(c),
+          then: Label(1),
+          else_then: Label(2),
+        },
+      },
+      BasicBlock {
+        label: Label(2),
+        insts: vec![Inst::Binary {
+          dest: r,
+          op: Operator::Add,
+          lhs: Operand::Reg(i),
+          rhs: Operand::Reg(k),
+        }],
+        term: Terminator::Ret {
+          val: Operand::Reg(r),
+        },
+      },
+    ];
+    let liveness = Liveness::new(&func, &Cfg::new(&func));
+    assert_eq!(liveness.live_in(Label(0)), Some(&set(&[0])));
+    assert_eq!(liveness.live_out(Label(0)), Some(&set(&[0, 1, 2])));
+    // `k` is used after the loop, so it is live across the back edge
+    assert_eq!(liveness.live_in(Label(1)), Some(&set(&[0, 1, 2])));
+    assert_eq!(liveness.live_out(Label(1)), Some(&set(&[0, 1, 2])));
+    assert_eq!(liveness.live_in(Label(2)), Some(&set(&[1, 2])));
+    assert_eq!(liveness.live_out(Label(2)), Some(&set(&[])));
+    // instructions
+    assert_eq!(liveness.inst_live_out(Label(0), 0), Some(&set(&[0, 1])));
+    assert_eq!(liveness.inst_live_out(Label(1), 0), Some(&set(&[0, 1, 2])));
+    assert_eq!(liveness.inst_live_out(Label(1), 1), Some(&set(&[0, 1, 2, 3])));
+    assert_eq!(liveness.inst_live_out(Label(2), 0), Some(&set(&[4])));
+    assert_eq!(liveness.inst_live_out(Label(2), 1), None);
+  }
+}
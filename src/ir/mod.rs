@@ -0,0 +1,203 @@
+pub mod cfg;
+pub mod constprop;
+pub mod copyprop;
+pub mod cse;
+pub mod gen;
+pub mod interp;
+pub mod liveness;
+pub mod peephole;
+pub mod print;
+pub mod prune;
+pub mod regalloc;
+
+use crate::define::{Operator, Symbol};
+
+/// Virtual register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Reg(pub u32);
+
+/// Label of basic blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Label(pub u32);
+
+/// Operand of instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operand {
+  /// Virtual register.
+  Reg(Reg),
+  /// Immediate integer, integers of the IR are 32-bit.
+  Imm(i32),
+}
+
+/// Three-address-code instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inst {
+  /// Copies the operand to the register.
+  Copy { dest: Reg, src: Operand },
+  /// Binary operation.
+  Binary {
+    dest: Reg,
+    op: Operator,
+    lhs: Operand,
+    rhs: Operand,
+  },
+  /// Unary operation.
+  Unary { dest: Reg, op: Operator, opr: Operand },
+  /// Loads the value of the stack slot to the register.
+  Load { dest: Reg, slot: usize },
+  /// Stores the operand to the stack slot.
+  Store { src: Operand, slot: usize },
+  /// Function call.
+  Call {
+    dest: Reg,
+    func: Symbol,
+    args: Vec<Operand>,
+  },
+}
+
+/// Terminator of basic blocks.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Terminator {
+  /// Unconditional jump.
+  Jump { target: Label },
+  /// Jumps to `then` if the condition is non-zero, otherwise `else_then`.
+  Branch {
+    cond: Operand,
+    then: Label,
+    else_then: Label,
+  },
+  /// Function return.
+  Ret { val: Operand },
+}
+
+/// Basic block, a sequence of non-terminator instructions
+/// followed by exactly one terminator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasicBlock {
+  /// Label of the block.
+  pub label: Label,
+  /// Instructions.
+  pub insts: Vec<Inst>,
+  /// Terminator.
+  pub term: Terminator,
+}
+
+/// Function, the first block is the entry block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function {
+  /// Function name.
+  pub name: Symbol,
+  /// Registers of parameters.
+  pub params: Vec<Reg>,
+  /// Basic blocks.
+  pub blocks: Vec<BasicBlock>,
+  /// Number of virtual registers.
+  pub reg_num: u32,
+  /// Number of stack slots.
+  pub slot_num: usize,
+}
+
+impl Operand {
+  /// Gets the register if the operand is a register.
+  pub fn reg(&self) -> Option<Reg> {
+    match self {
+      Operand::Reg(reg) => Some(*reg),
+      Operand::Imm(_) => None,
+    }
+  }
+}
+
+impl Inst {
+  /// Gets the register defined by the instruction.
+  pub fn def(&self) -> Option<Reg> {
+    match self {
+      Inst::Copy { dest, .. }
+      | Inst::Binary { dest, .. }
+      | Inst::Unary { dest, .. }
+      | Inst::Load { dest, .. }
+      | Inst::Call { dest, .. } => Some(*dest),
+      Inst::Store { .. } => None,
+    }
+  }
+
+  /// Gets all registers used by the instruction.
+  pub fn uses(&self) -> Vec<Reg> {
+    let oprs = match self {
+      Inst::Copy { src, .. } | Inst::Store { src, .. } => vec![src],
+      Inst::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+      Inst::Unary { opr, .. } => vec![opr],
+      Inst::Load { .. } => Vec::new(),
+      Inst::Call { args, .. } => args.iter().collect(),
+    };
+    oprs.into_iter().filter_map(|opr| opr.reg()).collect()
+  }
+
+  /// Gets mutable references to all operands used by the instruction.
+  pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+    match self {
+      Inst::Copy { src, .. } | Inst::Store { src, .. } => vec![src],
+      Inst::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+      Inst::Unary { opr, .. } => vec![opr],
+      Inst::Load { .. } => Vec::new(),
+      Inst::Call { args, .. } => args.iter_mut().collect(),
+    }
+  }
+}
+
+impl Terminator {
+  /// Gets all registers used by the terminator.
+  pub fn uses(&self) -> Vec<Reg> {
+    match self {
+      Terminator::Branch { cond, .. } | Terminator::Ret { val: cond } => {
+        cond.reg().into_iter().collect()
+      }
+      Terminator::Jump { .. } => Vec::new(),
+    }
+  }
+
+  /// Gets mutable references to all operands used by the terminator.
+  pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+    match self {
+      Terminator::Branch { cond, .. } | Terminator::Ret { val: cond } => vec![cond],
+      Terminator::Jump { .. } => Vec::new(),
+    }
+  }
+
+  /// Gets labels of all successors.
+  pub fn succs(&self) -> Vec<Label> {
+    match self {
+      Terminator::Jump { target } => vec![*target],
+      Terminator::Branch {
+        then, else_then, ..
+      } if then == else_then => vec![*then],
+      Terminator::Branch {
+        then, else_then, ..
+      } => vec![*then, *else_then],
+      Terminator::Ret { .. } => Vec::new(),
+    }
+  }
+}
+
+impl Function {
+  /// Creates a new function without any blocks.
+  pub fn new(name: Symbol) -> Self {
+    Self {
+      name,
+      params: Vec::new(),
+      blocks: Vec::new(),
+      reg_num: 0,
+      slot_num: 0,
+    }
+  }
+
+  /// Creates a new virtual register.
+  pub fn new_reg(&mut self) -> Reg {
+    self.reg_num += 1;
+    Reg(self.reg_num - 1)
+  }
+
+  /// Gets the block of the specific label.
+  pub fn block(&self, label: Label) -> Option<&BasicBlock> {
+    self.blocks.iter().find(|block| block.label == label)
+  }
+}
@@ -0,0 +1,271 @@
+use crate::define::Operator;
+use crate::ir::cfg::Cfg;
+use crate::ir::{Function, Inst, Operand, Terminator};
+
+/// Peephole optimizer, applies local rewrites to instructions
+/// (and pairs of adjacent instructions), and merges blocks into
+/// their only predecessors if they are jumped to from the previous
+/// block in layout order.
+#[derive(Default)]
+pub struct PeepholeOptimizer;
+
+impl PeepholeOptimizer {
+  /// Creates a new peephole optimizer.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Optimizes the specific function.
+  pub fn optimize(&self, func: &mut Function) {
+    for block in &mut func.blocks {
+      for i in 0..block.insts.len() {
+        let (prev, rest) = block.insts.split_at_mut(i);
+        let inst = &mut rest[0];
+        let rewritten = identity(inst)
+          .or_else(|| strength_reduction(inst))
+          .or_else(|| double_negation(prev.last(), inst));
+        if let Some(rewritten) = rewritten {
+          *inst = rewritten;
+        }
+      }
+    }
+    merge_blocks(func);
+  }
+}
+
+/// Rewrites `add %x, 0`, `sub %x, 0` and `mul %x, 1` (and the swapped
+/// forms of commutative operations) to copies.
+fn identity(inst: &Inst) -> Option<Inst> {
+  match inst {
+    Inst::Binary { dest, op, lhs, rhs } => {
+      let src = match (op, lhs, rhs) {
+        (Operator::Add | Operator::Sub, src, Operand::Imm(0))
+        | (Operator::Add, Operand::Imm(0), src)
+        | (Operator::Mul, src, Operand::Imm(1))
+        | (Operator::Mul, Operand::Imm(1), src) => *src,
+        _ => return None,
+      };
+      Some(Inst::Copy { dest: *dest, src })
+    }
+    _ => None,
+  }
+}
+
+/// Rewrites multiplications by powers of two to left shifts.
+fn strength_reduction(inst: &Inst) -> Option<Inst> {
+  match inst {
+    Inst::Binary {
+      dest,
+      op: Operator::Mul,
+      lhs,
+      rhs,
+    } => {
+      let (opr, pow) = match (lhs, rhs) {
+        (opr @ Operand::Reg(_), Operand::Imm(pow)) | (Operand::Imm(pow), opr @ Operand::Reg(_)) => {
+          (*opr, *pow)
+        }
+        _ => return None,
+      };
+      (pow > 1 && pow.count_ones() == 1).then_some(Inst::Binary {
+        dest: *dest,
+        op: Operator::Shl,
+        lhs: opr,
+        rhs: Operand::Imm(pow.trailing_zeros() as i32),
+      })
+    }
+    _ => None,
+  }
+}
+
+/// Rewrites the second negation of `%y = neg %x; %z = neg %y` to a copy
+/// of `%x`. The first negation is kept since it may overflow.
+fn double_negation(prev: Option<&Inst>, inst: &Inst) -> Option<Inst> {
+  match (prev?, inst) {
+    (
+      Inst::Unary {
+        dest: first,
+        op: Operator::Sub,
+        opr,
+      },
+      Inst::Unary {
+        dest,
+        op: Operator::Sub,
+        opr: Operand::Reg(second),
+      },
+    ) if first == second && opr.reg() != Some(*first) => Some(Inst::Copy {
+      dest: *dest,
+      src: *opr,
+    }),
+    _ => None,
+  }
+}
+
+/// Merges each block into the previous block in layout order, if the
+/// previous block jumps to it and it has no other predecessors.
+fn merge_blocks(func: &mut Function) {
+  let cfg = Cfg::new(func);
+  let mut i = 0;
+  while i + 1 < func.blocks.len() {
+    let next = &func.blocks[i + 1];
+    let mergeable = cfg.preds(next.label).len() == 1
+      && func.blocks[i].term == Terminator::Jump { target: next.label };
+    if mergeable {
+      // edges from the next block now start from the current block,
+      // so numbers of predecessors of all blocks are unchanged
+      let next = func.blocks.remove(i + 1);
+      let block = &mut func.blocks[i];
+      block.insts.extend(next.insts);
+      block.term = next.term;
+    } else {
+      i += 1;
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::PeepholeOptimizer;
+  use crate::define::Operator;
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::interp::IrInterpreter;
+  use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+
+  /// Creates a binary instruction.
+  fn binary(dest: u32, op: Operator, lhs: Operand, rhs: Operand) -> Inst {
+    Inst::Binary {
+      dest: Reg(dest),
+      op,
+      lhs,
+      rhs,
+    }
+  }
+
+  /// Creates a unary negation.
+  fn neg(dest: u32, opr: u32) -> Inst {
+    Inst::Unary {
+      dest: Reg(dest),
+      op: Operator::Sub,
+      opr: Operand::Reg(Reg(opr)),
+    }
+  }
+
+  /// Creates a copy instruction.
+  fn copy(dest: u32, src: u32) -> Inst {
+    Inst::Copy {
+      dest: Reg(dest),
+      src: Operand::Reg(Reg(src)),
+    }
+  }
+
+  /// Optimizes a function with the specific instructions,
+  /// returns the optimized instructions.
+  fn optimize(insts: Vec<Inst>) -> Vec<Inst> {
+    let mut func = Function::new("f".into());
+    func.blocks = vec![BasicBlock {
+      label: Label(0),
+      insts,
+      term: Terminator::Ret { val: Operand::Imm(0) },
+    }];
+    PeepholeOptimizer::new().optimize(&mut func);
+    func.blocks.pop().unwrap().insts
+  }
+
+  #[test]
+  fn test_strength_reduction() {
+    let x = Operand::Reg(Reg(0));
+    let insts = vec![
+      binary(1, Operator::Mul, x, Operand::Imm(8)),
+      binary(2, Operator::Mul, Operand::Imm(2), x),
+      binary(3, Operator::Mul, x, Operand::Imm(6)),
+      binary(4, Operator::Mul, x, Operand::Imm(i32::MIN)),
+    ];
+    let expected = vec![
+      binary(1, Operator::Shl, x, Operand::Imm(3)),
+      binary(2, Operator::Shl, x, Operand::Imm(1)),
+      binary(3, Operator::Mul, x, Operand::Imm(6)),
+      binary(4, Operator::Mul, x, Operand::Imm(i32::MIN)),
+    ];
+    assert_eq!(optimize(insts), expected);
+    // results are the same as multiplications
+    let program = "f(x) { return x * 4 + 1024 * x }";
+    let mut parser = Parser::from_str(program);
+    let mut func = IrGen::new().generate(&parser.parse_next().unwrap()).unwrap();
+    PeepholeOptimizer::new().optimize(&mut func);
+    assert_eq!(func.to_string().matches("shl").count(), 2);
+    let mut intp = IrInterpreter::new();
+    intp.add_func(func).unwrap();
+    assert_eq!(intp.call(&"f".into(), &[-3]), Ok(-3084));
+    assert_eq!(intp.call(&"f".into(), &[i32::MAX]), Ok(i32::MAX.wrapping_mul(1028)));
+  }
+
+  #[test]
+  fn test_identity() {
+    let x = Operand::Reg(Reg(0));
+    let insts = vec![
+      binary(1, Operator::Add, x, Operand::Imm(0)),
+      binary(2, Operator::Add, Operand::Imm(0), x),
+      binary(3, Operator::Sub, Operand::Imm(0), x),
+      binary(4, Operator::Mul, Operand::Imm(1), x),
+      neg(5, 0),
+      neg(6, 5),
+      neg(0, 0),
+      neg(7, 0),
+    ];
+    let expected = vec![
+      copy(1, 0),
+      copy(2, 0),
+      binary(3, Operator::Sub, Operand::Imm(0), x),
+      copy(4, 0),
+      neg(5, 0),
+      copy(6, 0),
+      neg(0, 0),
+      neg(7, 0),
+    ];
+    assert_eq!(optimize(insts), expected);
+  }
+
+  #[test]
+  fn test_redundant_jump() {
+    let block = |label, insts, term| BasicBlock {
+      label: Label(label),
+      insts,
+      term,
+    };
+    let jump = |target| Terminator::Jump {
+      target: Label(target),
+    };
+    let branch = Terminator::Branch {
+      cond: Operand::Reg(Reg(0)),
+      then: Label(1),
+      else_then: Label(3),
+    };
+    let ret = Terminator::Ret {
+      val: Operand::Reg(Reg(1)),
+    };
+    let mut func = Function::new("f".into());
+    func.blocks = vec![
+      block(0, vec![copy(1, 0)], jump(1)),
+      block(1, vec![neg(1, 1)], jump(2)),
+      block(2, vec![neg(1, 1)], branch),
+      block(3, vec![], jump(1)),
+      block(4, vec![], jump(5)),
+      block(5, vec![], ret.clone()),
+    ];
+    PeepholeOptimizer::new().optimize(&mut func);
+    let branch = Terminator::Branch {
+      cond: Operand::Reg(Reg(0)),
+      then: Label(1),
+      else_then: Label(3),
+    };
+    // `bb1` has two predecessors, `bb2` is merged into `bb1`
+    // (without rewriting the double negation across blocks)
+    let expected = vec![
+      block(0, vec![copy(1, 0)], jump(1)),
+      block(1, vec![neg(1, 1), neg(1, 1)], branch),
+      block(3, vec![], jump(1)),
+      block(4, vec![], ret),
+    ];
+    assert_eq!(func.blocks, expected);
+  }
+}
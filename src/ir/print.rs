@@ -0,0 +1,146 @@
+use crate::define::Operator;
+use crate::ir::{BasicBlock, Function, Inst, Label, Operand, Reg, Terminator};
+use std::fmt;
+
+impl fmt::Display for Reg {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "%{}", self.0)
+  }
+}
+
+impl fmt::Display for Label {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "bb{}", self.0)
+  }
+}
+
+impl fmt::Display for Operand {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Operand::Reg(reg) => write!(f, "{}", reg),
+      Operand::Imm(val) => write!(f, "{}", val),
+    }
+  }
+}
+
+impl fmt::Display for Inst {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Inst::Copy { dest, src } => write!(f, "{} = {}", dest, src),
+      Inst::Binary { dest, op, lhs, rhs } => {
+        write!(f, "{} = {} {}, {}", dest, binary_name(op), lhs, rhs)
+      }
+      Inst::Unary { dest, op, opr } => write!(f, "{} = {} {}", dest, unary_name(op), opr),
+      Inst::Load { dest, slot } => write!(f, "{} = load ${}", dest, slot),
+      Inst::Store { src, slot } => write!(f, "store {}, ${}", src, slot),
+      Inst::Call { dest, func, args } => {
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string()).collect();
+        write!(f, "{} = call {}({})", dest, func, args.join(", "))
+      }
+    }
+  }
+}
+
+impl fmt::Display for Terminator {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Terminator::Jump { target } => write!(f, "jump {}", target),
+      Terminator::Branch {
+        cond,
+        then,
+        else_then,
+      } => write!(f, "br {}, {}, {}", cond, then, else_then),
+      Terminator::Ret { val } => write!(f, "ret {}", val),
+    }
+  }
+}
+
+impl fmt::Display for BasicBlock {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "{}:", self.label)?;
+    for inst in &self.insts {
+      writeln!(f, "  {}", inst)?;
+    }
+    writeln!(f, "  {}", self.term)
+  }
+}
+
+impl fmt::Display for Function {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let params: Vec<_> = self.params.iter().map(|param| param.to_string()).collect();
+    writeln!(f, "fn {}({}) {{", self.name, params.join(", "))?;
+    for block in &self.blocks {
+      write!(f, "{}", block)?;
+    }
+    writeln!(f, "}}")
+  }
+}
+
+/// Gets the mnemonic of the specific binary operator.
+fn binary_name(op: &Operator) -> &'static str {
+  match op {
+    Operator::Add => "add",
+    Operator::Sub => "sub",
+    Operator::Mul => "mul",
+    Operator::Div => "div",
+    Operator::Mod => "mod",
+    Operator::Less => "lt",
+    Operator::LessEq => "le",
+    Operator::Eq => "eq",
+    Operator::NotEq => "ne",
+    Operator::LAnd => "and",
+    Operator::LOr => "or",
+    Operator::Shl => "shl",
+    _ => panic!("unknown binary operator"),
+  }
+}
+
+/// Gets the mnemonic of the specific unary operator.
+fn unary_name(op: &Operator) -> &'static str {
+  match op {
+    Operator::Sub => "neg",
+    Operator::LNot => "not",
+    _ => panic!("invalid unary operator"),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::{Inst, Operand, Reg};
+
+  #[test]
+  fn test_print() {
+    let program = "f(a, b) { c := a + b * 2 if !c { c = f(c, -1) } return c }";
+    let mut parser = Parser::from_str(program);
+    let func = IrGen::new().generate(&parser.parse_next().unwrap()).unwrap();
+    let expected = r#"fn f(%0, %1) {
+bb0:
+  %2 = mul %1, 2
+  %3 = add %0, %2
+  %4 = %3
+  %5 = not %4
+  br %5, bb1, bb2
+bb1:
+  %6 = neg 1
+  %7 = call f(%4, %6)
+  %4 = %7
+  jump bb2
+bb2:
+  ret %4
+}
+"#;
+    assert_eq!(func.to_string(), expected);
+    let store = Inst::Store {
+      src: Operand::Reg(Reg(1)),
+      slot: 0,
+    };
+    assert_eq!(store.to_string(), "store %1, $0");
+    let load = Inst::Load {
+      dest: Reg(2),
+      slot: 3,
+    };
+    assert_eq!(load.to_string(), "%2 = load $3");
+  }
+}
@@ -0,0 +1,69 @@
+use crate::ir::cfg::Cfg;
+use crate::ir::Function;
+
+/// Unreachable block pruner, removes blocks that are unreachable
+/// from the entry block, like blocks of branches on constant conditions
+/// which have been rewritten to unconditional jumps.
+///
+/// Predecessors of the remaining blocks are no longer affected by the
+/// removed blocks in control flow graphs built after pruning.
+#[derive(Default)]
+pub struct BlockPruner;
+
+impl BlockPruner {
+  /// Creates a new unreachable block pruner.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Removes unreachable blocks of the specific function,
+  /// returns the number of removed blocks.
+  pub fn prune(&self, func: &mut Function) -> usize {
+    let reachable = Cfg::new(func).reachable_blocks();
+    let len = func.blocks.len();
+    func.blocks.retain(|block| reachable.contains(&block.label));
+    len - func.blocks.len()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::BlockPruner;
+  use crate::front::parser::Parser;
+  use crate::ir::cfg::Cfg;
+  use crate::ir::constprop::ConstPropagator;
+  use crate::ir::gen::IrGen;
+  use crate::ir::interp::IrInterpreter;
+  use crate::ir::{Function, Label};
+
+  /// Generates IR of the specific function definition.
+  fn generate(program: &str) -> Function {
+    let mut parser = Parser::from_str(program);
+    IrGen::new().generate(&parser.parse_next().unwrap()).unwrap()
+  }
+
+  #[test]
+  fn test_prune() {
+    let mut func = generate("f(x) { if 1 { x = x + 1 } else { x = x - 1 } return x }");
+    // nothing is unreachable before constant propagation
+    assert_eq!(BlockPruner::new().prune(&mut func), 0);
+    ConstPropagator::new().propagate(&mut func);
+    assert_eq!(BlockPruner::new().prune(&mut func), 1);
+    let labels: Vec<_> = func.blocks.iter().map(|block| block.label).collect();
+    assert_eq!(labels, [Label(0), Label(1), Label(3)]);
+    let cfg = Cfg::new(&func);
+    assert_eq!(cfg.preds(Label(3)), [Label(1)]);
+    let mut intp = IrInterpreter::new();
+    intp.add_func(func).unwrap();
+    assert_eq!(intp.call(&"f".into(), &[1]), Ok(2));
+  }
+
+  #[test]
+  fn test_prune_dead_code() {
+    // code after return statements, and merge blocks of returning branches
+    let mut func = generate("f(x) { if x { return 1 } else { return 2 } x = 3 return x }");
+    assert_eq!(BlockPruner::new().prune(&mut func), 1);
+    assert_eq!(func.blocks.len(), 3);
+    assert!(Cfg::new(&func).unreachable_blocks().is_empty());
+  }
+}
@@ -0,0 +1,373 @@
+use crate::ir::cfg::Cfg;
+use crate::ir::liveness::Liveness;
+use crate::ir::{BasicBlock, Function, Inst, Operand, Reg, Terminator};
+use std::collections::{BTreeSet, HashMap};
+
+/// Location of a virtual register after register allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+  /// Physical register.
+  Reg(u32),
+  /// Stack slot.
+  Slot(usize),
+}
+
+/// Result of register allocation.
+pub struct Allocation {
+  /// Locations of all virtual registers.
+  pub locations: HashMap<Reg, Location>,
+  /// Rewritten function. Registers `0..reg_num` are the allocated
+  /// physical registers, the rest are scratch registers used to reload
+  /// and spill values in stack slots around each instruction.
+  pub func: Function,
+}
+
+/// Linear scan register allocator.
+pub struct LinearScan {
+  /// Number of allocatable physical registers.
+  reg_num: u32,
+}
+
+/// Live interval of a virtual register.
+struct Interval {
+  /// Virtual register.
+  reg: Reg,
+  /// First position where the register is live.
+  start: usize,
+  /// Last position where the register is live.
+  end: usize,
+}
+
+impl LinearScan {
+  /// Creates a new allocator with the specific number of physical registers.
+  pub fn new(reg_num: u32) -> Self {
+    Self { reg_num }
+  }
+
+  /// Allocates registers for the specific function.
+  pub fn allocate(&self, func: &Function) -> Allocation {
+    let (locations, slot_num) = self.assign(func, live_intervals(func));
+    let mut rewriter = Rewriter {
+      locations: &locations,
+      reg_num: self.reg_num,
+      scratch_num: 0,
+    };
+    let mut func = rewriter.rewrite(func);
+    func.reg_num = self.reg_num + rewriter.scratch_num;
+    func.slot_num = slot_num;
+    Allocation { locations, func }
+  }
+
+  /// Assigns locations to virtual registers,
+  /// returns the locations and the number of stack slots.
+  fn assign(&self, func: &Function, intervals: Vec<Interval>) -> (HashMap<Reg, Location>, usize) {
+    let mut locations = HashMap::new();
+    let mut free: BTreeSet<_> = (0..self.reg_num).collect();
+    let mut active: Vec<Interval> = Vec::new();
+    let mut slot_num = func.slot_num;
+    for cur in intervals {
+      // release registers of expired intervals
+      active.retain(|interval| {
+        let expired = interval.end < cur.start;
+        if expired {
+          if let Some(Location::Reg(reg)) = locations.get(&interval.reg) {
+            free.insert(*reg);
+          }
+        }
+        !expired
+      });
+      if let Some(reg) = free.pop_first() {
+        locations.insert(cur.reg, Location::Reg(reg));
+        active.push(cur);
+        continue;
+      }
+      // spill the interval that ends last
+      let last = active.iter().enumerate().max_by_key(|(_, interval)| interval.end);
+      match last {
+        Some((index, last)) if last.end > cur.end => {
+          let slot = Location::Slot(slot_num);
+          let reg = locations.insert(last.reg, slot).unwrap();
+          locations.insert(cur.reg, reg);
+          active[index] = cur;
+        }
+        _ => {
+          locations.insert(cur.reg, Location::Slot(slot_num));
+        }
+      }
+      slot_num += 1;
+    }
+    (locations, slot_num)
+  }
+}
+
+/// Computes live intervals of all virtual registers, sorted by start position.
+/// Each block occupies one position for its entry, one for each instruction,
+/// and one for its terminator.
+fn live_intervals(func: &Function) -> Vec<Interval> {
+  let liveness = Liveness::new(func, &Cfg::new(func));
+  let mut ranges: HashMap<Reg, (usize, usize)> = HashMap::new();
+  let mut extend = |reg: Reg, pos: usize| {
+    let range = ranges.entry(reg).or_insert((pos, pos));
+    range.0 = range.0.min(pos);
+    range.1 = range.1.max(pos);
+  };
+  func.params.iter().for_each(|param| extend(*param, 0));
+  let mut pos = 0;
+  for block in &func.blocks {
+    let live_in = liveness.live_in(block.label).into_iter().flatten();
+    live_in.for_each(|reg| extend(*reg, pos));
+    for inst in &block.insts {
+      pos += 1;
+      let regs = inst.uses().into_iter().chain(inst.def());
+      regs.for_each(|reg| extend(reg, pos));
+    }
+    pos += 1;
+    block.term.uses().into_iter().for_each(|reg| extend(reg, pos));
+    let live_out = liveness.live_out(block.label).into_iter().flatten();
+    live_out.for_each(|reg| extend(*reg, pos));
+    pos += 1;
+  }
+  let mut intervals: Vec<_> = ranges
+    .into_iter()
+    .map(|(reg, (start, end))| Interval { reg, start, end })
+    .collect();
+  intervals.sort_by_key(|interval| (interval.start, interval.reg));
+  intervals
+}
+
+/// Rewriter that replaces virtual registers with their locations.
+struct Rewriter<'a> {
+  /// Locations of all virtual registers.
+  locations: &'a HashMap<Reg, Location>,
+  /// Number of allocatable physical registers.
+  reg_num: u32,
+  /// Number of scratch registers used so far.
+  scratch_num: u32,
+}
+
+impl Rewriter<'_> {
+  /// Rewrites the specific function.
+  fn rewrite(&mut self, func: &Function) -> Function {
+    let mut params = Vec::new();
+    let mut stores = Vec::new();
+    for (i, param) in func.params.iter().enumerate() {
+      match self.locations[param] {
+        Location::Reg(reg) => params.push(Reg(reg)),
+        Location::Slot(slot) => {
+          // spilled parameters are passed in scratch registers
+          let reg = self.scratch(i as u32);
+          params.push(reg);
+          stores.push(Inst::Store {
+            src: Operand::Reg(reg),
+            slot,
+          });
+        }
+      }
+    }
+    let mut blocks: Vec<_> = func.blocks.iter().map(|block| self.rewrite_block(block)).collect();
+    if let Some(entry) = blocks.first_mut() {
+      entry.insts.splice(0..0, stores);
+    }
+    Function {
+      name: func.name,
+      params,
+      blocks,
+      reg_num: func.reg_num,
+      slot_num: func.slot_num,
+    }
+  }
+
+  /// Rewrites the specific basic block.
+  fn rewrite_block(&mut self, block: &BasicBlock) -> BasicBlock {
+    let mut insts = Vec::new();
+    for inst in &block.insts {
+      self.rewrite_inst(inst, &mut insts);
+    }
+    let mut index = 0;
+    let term = match &block.term {
+      Terminator::Branch {
+        cond,
+        then,
+        else_then,
+      } => Terminator::Branch {
+        cond: self.use_opr(cond, &mut insts, &mut index),
+        then: *then,
+        else_then: *else_then,
+      },
+      Terminator::Ret { val } => Terminator::Ret {
+        val: self.use_opr(val, &mut insts, &mut index),
+      },
+      term => term.clone(),
+    };
+    BasicBlock {
+      label: block.label,
+      insts,
+      term,
+    }
+  }
+
+  /// Rewrites the specific instruction, pushes the result to `insts`.
+  fn rewrite_inst(&mut self, inst: &Inst, insts: &mut Vec<Inst>) {
+    let mut index = 0;
+    let (inst, store) = match inst {
+      Inst::Copy { dest, src } => {
+        let src = self.use_opr(src, insts, &mut index);
+        let (reg, store) = self.def_reg(*dest);
+        // remove copies between the same register
+        if src == Operand::Reg(reg) && store.is_none() {
+          return;
+        }
+        (Inst::Copy { dest: reg, src }, store)
+      }
+      Inst::Binary { dest, op, lhs, rhs } => {
+        let lhs = self.use_opr(lhs, insts, &mut index);
+        let rhs = self.use_opr(rhs, insts, &mut index);
+        let (reg, store) = self.def_reg(*dest);
+        let inst = Inst::Binary {
+          dest: reg,
+          op: op.clone(),
+          lhs,
+          rhs,
+        };
+        (inst, store)
+      }
+      Inst::Unary { dest, op, opr } => {
+        let opr = self.use_opr(opr, insts, &mut index);
+        let (reg, store) = self.def_reg(*dest);
+        let inst = Inst::Unary {
+          dest: reg,
+          op: op.clone(),
+          opr,
+        };
+        (inst, store)
+      }
+      Inst::Load { dest, slot } => {
+        let (reg, store) = self.def_reg(*dest);
+        (Inst::Load { dest: reg, slot: *slot }, store)
+      }
+      Inst::Store { src, slot } => {
+        let src = self.use_opr(src, insts, &mut index);
+        insts.push(Inst::Store { src, slot: *slot });
+        return;
+      }
+      Inst::Call { dest, func, args } => {
+        let args = args
+          .iter()
+          .map(|arg| self.use_opr(arg, insts, &mut index))
+          .collect();
+        let (reg, store) = self.def_reg(*dest);
+        let inst = Inst::Call {
+          dest: reg,
+          func: *func,
+          args,
+        };
+        (inst, store)
+      }
+    };
+    insts.push(inst);
+    insts.extend(store);
+  }
+
+  /// Gets the `index`-th scratch register.
+  fn scratch(&mut self, index: u32) -> Reg {
+    self.scratch_num = self.scratch_num.max(index + 1);
+    Reg(self.reg_num + index)
+  }
+
+  /// Rewrites the specific operand used by an instruction,
+  /// reloads it to a scratch register if spilled.
+  fn use_opr(&mut self, opr: &Operand, insts: &mut Vec<Inst>, index: &mut u32) -> Operand {
+    match opr {
+      Operand::Reg(reg) => match self.locations[reg] {
+        Location::Reg(reg) => Operand::Reg(Reg(reg)),
+        Location::Slot(slot) => {
+          let dest = self.scratch(*index);
+          *index += 1;
+          insts.push(Inst::Load { dest, slot });
+          Operand::Reg(dest)
+        }
+      },
+      Operand::Imm(_) => *opr,
+    }
+  }
+
+  /// Rewrites the specific register defined by an instruction,
+  /// returns the new register and the store if spilled.
+  fn def_reg(&mut self, reg: Reg) -> (Reg, Option<Inst>) {
+    match self.locations[&reg] {
+      Location::Reg(reg) => (Reg(reg), None),
+      Location::Slot(slot) => {
+        let reg = self.scratch(0);
+        let store = Inst::Store {
+          src: Operand::Reg(reg),
+          slot,
+        };
+        (reg, Some(store))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{LinearScan, Location};
+  use crate::front::parser::Parser;
+  use crate::ir::gen::IrGen;
+  use crate::ir::interp::IrInterpreter;
+  use crate::ir::{Function, Inst};
+
+  /// Generates IR of the specific function definition.
+  fn generate(program: &str) -> Function {
+    let mut parser = Parser::from_str(program);
+    IrGen::new().generate(&parser.parse_next().unwrap()).unwrap()
+  }
+
+  /// Calls the specific function with arguments in the IR interpreter.
+  fn call(func: &Function, args: &[i32]) -> Result<i32, &'static str> {
+    let mut intp = IrInterpreter::new();
+    intp.add_func(func.clone())?;
+    intp.call(&func.name, args)
+  }
+
+  #[test]
+  fn test_spill() {
+    let func = generate(
+      r#"
+      f(a, b) {
+        c := a + b d := a * b e := a - b g := c * d
+        return a + b + c + d + e + g
+      }
+    "#,
+    );
+    let alloc = LinearScan::new(2).allocate(&func);
+    assert!(alloc.locations.values().any(|loc| matches!(loc, Location::Slot(_))));
+    let insts = || alloc.func.blocks.iter().flat_map(|block| &block.insts);
+    assert!(insts().any(|inst| matches!(inst, Inst::Load { .. })));
+    assert!(insts().any(|inst| matches!(inst, Inst::Store { .. })));
+    assert!(alloc.func.reg_num <= 4);
+    assert_eq!(call(&func, &[3, 4]), Ok(109));
+    assert_eq!(call(&alloc.func, &[3, 4]), Ok(109));
+    // enough registers
+    let alloc = LinearScan::new(16).allocate(&func);
+    assert!(alloc.locations.values().all(|loc| matches!(loc, Location::Reg(_))));
+    assert_eq!(alloc.func.slot_num, 0);
+    assert_eq!(call(&alloc.func, &[3, 4]), Ok(109));
+  }
+
+  #[test]
+  fn test_spill_params() {
+    let func = generate(
+      r#"
+      fib(n) {
+        r := 1
+        if 2 < n { r = fib(n - 1) + fib(n - 2) }
+        return r
+      }
+    "#,
+    );
+    for reg_num in 0..3 {
+      let alloc = LinearScan::new(reg_num).allocate(&func);
+      assert_eq!(call(&alloc.func, &[15]), Ok(610));
+    }
+  }
+}
@@ -0,0 +1,18 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod back;
+#[cfg(feature = "std")]
+pub mod codegen;
+pub mod define;
+#[cfg(feature = "std")]
+pub mod dump;
+pub mod front;
+#[cfg(feature = "std")]
+pub mod ir;
+#[cfg(feature = "std")]
+pub mod pass;
+#[cfg(feature = "std")]
+pub mod repl;
@@ -1,29 +1,37 @@
-mod back;
-mod define;
-mod front;
-
-use back::{compiler::Compiler, interpreter::Interpreter};
-use define::AstBox;
-use front::{lexer::Lexer, parser::Parser};
+use first_step_rust::back::{compiler::Compiler, interpreter::Interpreter};
+use first_step_rust::define::AstBox;
+use first_step_rust::front::{lexer::Lexer, parser::Parser};
+use first_step_rust::pass::{resolve::Resolver, unused::UnusedChecker};
+use first_step_rust::repl;
+use std::convert::TryFrom;
 use std::env;
 use std::fs;
 use std::io;
 use std::process;
+use std::thread;
+
+/// Stack size of the thread running the interpreter.
+const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Maximum depth of function calls, fits `INTERPRETER_STACK_SIZE`.
+const INTERPRETER_MAX_DEPTH: usize = 10000;
 
-/// Runs parser.
-fn parse<'a, F, T>(file: fs::File, mut action: F) -> Result<(), String>
+/// Runs parser & static checkers.
+fn parse<F, T>(file: fs::File, mut action: F) -> Result<(), String>
 where
   F: FnMut(AstBox) -> Result<T, String>,
 {
-  use front::parser::Error;
-  let mut parser = Parser::new(Lexer::new(file));
   // parse the input file
-  loop {
-    match parser.parse_next() {
-      Ok(ast) => action(ast)?,
-      Err(Error::End) => break,
-      Err(Error::Error(err)) => return Err(err),
-    };
+  let mut resolver = Resolver::new();
+  let mut unused = UnusedChecker::new();
+  for ast in Parser::new(Lexer::new(file)) {
+    let ast = ast.map_err(|err| err.to_string())?;
+    // check variable references
+    resolver.resolve(&ast).map_err(|errs| errs[0].to_string())?;
+    for warning in unused.check(&ast) {
+      eprintln!("warning: {}", warning);
+    }
+    action(ast)?;
   }
   Ok(())
 }
@@ -32,9 +40,26 @@ where
 fn interpret(file: fs::File) -> Result<i32, String> {
   // parse the program
   let mut intp = Interpreter::new();
+  intp.set_max_depth(INTERPRETER_MAX_DEPTH);
+  intp.register_prelude()?;
   parse(file, |ast| Ok(intp.add_func_def(ast)?))?;
-  // evaluate the program
-  Ok(intp.eval()?)
+  // evaluate the program, the result is used as the exit code
+  let val = intp.eval()?;
+  i32::try_from(val).map_err(|_| format!("return value {} is out of range of exit codes", val))
+}
+
+/// Runs the specific function in a thread with a large stack.
+fn with_large_stack<F, T>(f: F) -> T
+where
+  F: FnOnce() -> T + Send + 'static,
+  T: Send + 'static,
+{
+  thread::Builder::new()
+    .stack_size(INTERPRETER_STACK_SIZE)
+    .spawn(f)
+    .expect("failed to spawn the interpreter thread")
+    .join()
+    .expect("the interpreter thread panicked")
 }
 
 /// Runs parser & compiler.
@@ -51,9 +76,15 @@ fn main() -> Result<(), String> {
   // parse command line arguments
   let args: Vec<_> = env::args().collect();
   if args.len() < 2 {
-    println!("usage: {} <INPUT> [-c [-o <OUTPUT>]]", args[0]);
+    println!("usage: {} <INPUT> [-c [-o <OUTPUT>]] | -r", args[0]);
     return Err("invalid command line argument".to_string());
   }
+  if args[1] == "-r" {
+    // run REPL
+    return with_large_stack(|| {
+      repl::run(io::stdin().lock(), &mut io::stdout()).map_err(|err| format!("{}", err))
+    });
+  }
   // open file
   let file = fs::File::open(&args[1]).map_err(|err| format!("{}", err))?;
   // check if need to compile the input file
@@ -67,7 +98,7 @@ fn main() -> Result<(), String> {
     }
   } else {
     // run interpreter
-    let code = interpret(file)?;
+    let code = with_large_stack(move || interpret(file))?;
     process::exit(code);
   }
 }
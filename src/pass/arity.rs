@@ -0,0 +1,235 @@
+use crate::collection;
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Program, Symbol, Type};
+use std::collections::HashMap;
+use std::{error, fmt};
+
+/// Error reported by `ArityChecker`.
+#[derive(Debug, PartialEq)]
+pub enum ArityError {
+  /// Call to an undefined function.
+  Unknown(Symbol),
+  /// Function call with a wrong number of arguments.
+  Mismatch {
+    name: Symbol,
+    expected: usize,
+    found: usize,
+  },
+}
+
+impl fmt::Display for ArityError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ArityError::Unknown(name) => write!(f, "function `{}` has not been defined", name),
+      ArityError::Mismatch {
+        name,
+        expected,
+        found,
+      } => write!(
+        f,
+        "function `{}` takes {} argument(s) but {} were supplied",
+        name, expected, found
+      ),
+    }
+  }
+}
+
+impl error::Error for ArityError {}
+
+/// Arity checker, checks argument counts of function calls
+/// against function definitions in the whole program.
+pub struct ArityChecker {
+  /// Argument counts of all functions.
+  arities: HashMap<Symbol, usize>,
+  /// Errors reported so far.
+  errors: Vec<ArityError>,
+}
+
+impl ArityChecker {
+  /// Creates a new arity checker of the specific program.
+  pub fn new(program: &Program) -> Self {
+    let mut arities: HashMap<_, _> = collection! {
+      Symbol::new("input") => 0,
+      Symbol::new("print") => 1,
+    };
+    for ast in program {
+      if let Ast::FunDef { name, args, .. } = ast.as_ref() {
+        arities.insert(*name, args.len());
+      }
+    }
+    Self {
+      arities,
+      errors: Vec::new(),
+    }
+  }
+
+  /// Checks all function calls in the specific program,
+  /// returns all errors if any.
+  pub fn check(&mut self, program: &Program) -> Result<(), Vec<ArityError>> {
+    for ast in program {
+      self.visit(ast);
+    }
+    if self.errors.is_empty() {
+      Ok(())
+    } else {
+      Err(std::mem::take(&mut self.errors))
+    }
+  }
+}
+
+impl AstVisitor for ArityChecker {
+  type Result = ();
+
+  fn visit_fundef(
+    &mut self,
+    _: &Symbol,
+    _: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.visit(body);
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    for stmt in stmts {
+      self.visit(stmt);
+    }
+  }
+
+  fn visit_define(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+  }
+
+  fn visit_assign(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.visit(cond);
+    self.visit(then);
+    if let Some(else_then) = else_then {
+      self.visit(else_then);
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.visit(scrutinee);
+    for (_, body) in cases {
+      self.visit(body);
+    }
+    if let Some(default) = default {
+      self.visit(default);
+    }
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.visit(body);
+    self.visit(cond);
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    if let Some(expr) = expr {
+      self.visit(expr);
+    }
+  }
+
+  fn visit_binary(&mut self, _: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    self.visit(lhs);
+    self.visit(rhs);
+  }
+
+  fn visit_unary(&mut self, _: &Operator, opr: &AstBox) -> Self::Result {
+    self.visit(opr);
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    match self.arities.get(name) {
+      Some(expected) if *expected != args.len() => self.errors.push(ArityError::Mismatch {
+        name: *name,
+        expected: *expected,
+        found: args.len(),
+      }),
+      Some(_) => (),
+      None => self.errors.push(ArityError::Unknown(*name)),
+    }
+    for arg in args {
+      self.visit(arg);
+    }
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {}
+
+  fn visit_id(&mut self, _: &Symbol) -> Self::Result {}
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {}
+}
+
+#[cfg(test)]
+mod test {
+  use super::{ArityChecker, ArityError};
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+
+  /// Parses and checks the specific program.
+  fn check(program: &str) -> Result<(), Vec<ArityError>> {
+    let mut parser = Parser::from_str(program);
+    let program = parser.parse_program().unwrap();
+    ArityChecker::new(&program).check(&program)
+  }
+
+  #[test]
+  fn test_arity() {
+    // functions can be called before their definitions
+    let program = r#"
+      main() { print(f(input(), 2)) return g() }
+      f(a, b) { return a + b }
+      g() { if f(1, 1) { return 0 } return 1 }
+    "#;
+    assert_eq!(check(program), Ok(()));
+  }
+
+  #[test]
+  fn test_arity_mismatch() {
+    let err = check("f(x) { return x } main() { return f(1, f()) }").unwrap_err();
+    assert_eq!(
+      err,
+      vec![
+        ArityError::Mismatch {
+          name: Symbol::new("f"),
+          expected: 1,
+          found: 2,
+        },
+        ArityError::Mismatch {
+          name: Symbol::new("f"),
+          expected: 1,
+          found: 0,
+        },
+      ],
+    );
+    assert_eq!(
+      err[0].to_string(),
+      "function `f` takes 1 argument(s) but 2 were supplied"
+    );
+    assert_eq!(
+      check("main() { print() return 0 }"),
+      Err(vec![ArityError::Mismatch {
+        name: Symbol::new("print"),
+        expected: 1,
+        found: 0,
+      }]),
+    );
+  }
+
+  #[test]
+  fn test_arity_unknown() {
+    let err = check("main() { x := h(1) return x }").unwrap_err();
+    assert_eq!(err, vec![ArityError::Unknown(Symbol::new("h"))]);
+    assert_eq!(err[0].to_string(), "function `h` has not been defined");
+  }
+}
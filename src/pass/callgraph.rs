@@ -0,0 +1,216 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Program, Symbol, Type};
+use std::collections::{HashMap, HashSet};
+
+/// Call graph of a program, nodes are functions and edges are
+/// function calls from callers to callees.
+pub struct CallGraph {
+  /// Callees of all functions defined in the program.
+  edges: HashMap<Symbol, HashSet<Symbol>>,
+}
+
+impl CallGraph {
+  /// Builds the call graph of the specific program.
+  pub fn new(program: &Program) -> Self {
+    let mut edges = HashMap::new();
+    for ast in program {
+      if let Ast::FunDef { name, body, .. } = ast.as_ref() {
+        let mut collector = CallCollector::default();
+        collector.visit(body);
+        edges.entry(*name).or_insert_with(HashSet::new).extend(collector.callees);
+      }
+    }
+    Self { edges }
+  }
+
+  /// Gets the adjacency map of the call graph.
+  pub fn edges(&self) -> &HashMap<Symbol, HashSet<Symbol>> {
+    &self.edges
+  }
+
+  /// Gets the callees of the specific function,
+  /// returns `None` if the function is not defined in the program.
+  pub fn callees(&self, name: &Symbol) -> Option<&HashSet<Symbol>> {
+    self.edges.get(name)
+  }
+
+  /// Checks if the specific function is recursive, that is, it can
+  /// reach itself by calling itself or through a cycle of calls.
+  pub fn is_recursive(&self, name: &Symbol) -> bool {
+    self
+      .callees(name)
+      .into_iter()
+      .flatten()
+      .any(|callee| self.reachable_from(callee).contains(name))
+  }
+
+  /// Gets all functions transitively called by the specific function,
+  /// including the function itself.
+  pub fn reachable_from(&self, name: &Symbol) -> HashSet<Symbol> {
+    let mut visited = HashSet::new();
+    let mut stack = vec![*name];
+    while let Some(name) = stack.pop() {
+      if visited.insert(name) {
+        stack.extend(self.callees(&name).into_iter().flatten());
+      }
+    }
+    visited
+  }
+
+  /// Gets all recursive functions in the program.
+  pub fn recursive_funcs(&self) -> HashSet<Symbol> {
+    self
+      .edges
+      .keys()
+      .filter(|name| self.is_recursive(name))
+      .copied()
+      .collect()
+  }
+}
+
+/// Call collector, collects all callees of a function body.
+#[derive(Default)]
+struct CallCollector {
+  /// Callees collected so far.
+  callees: HashSet<Symbol>,
+}
+
+impl AstVisitor for CallCollector {
+  type Result = ();
+
+  fn visit_fundef(
+    &mut self,
+    _: &Symbol,
+    _: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.visit(body);
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    for stmt in stmts {
+      self.visit(stmt);
+    }
+  }
+
+  fn visit_define(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+  }
+
+  fn visit_assign(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.visit(cond);
+    self.visit(then);
+    if let Some(else_then) = else_then {
+      self.visit(else_then);
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.visit(scrutinee);
+    for (_, body) in cases {
+      self.visit(body);
+    }
+    if let Some(default) = default {
+      self.visit(default);
+    }
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.visit(body);
+    self.visit(cond);
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    if let Some(expr) = expr {
+      self.visit(expr);
+    }
+  }
+
+  fn visit_binary(&mut self, _: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    self.visit(lhs);
+    self.visit(rhs);
+  }
+
+  fn visit_unary(&mut self, _: &Operator, opr: &AstBox) -> Self::Result {
+    self.visit(opr);
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    self.callees.insert(*name);
+    for arg in args {
+      self.visit(arg);
+    }
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {}
+
+  fn visit_id(&mut self, _: &Symbol) -> Self::Result {}
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {}
+}
+
+#[cfg(test)]
+mod test {
+  use super::CallGraph;
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+  use std::collections::HashSet;
+
+  /// Parses the specific program and builds its call graph.
+  fn build(program: &str) -> CallGraph {
+    let mut parser = Parser::from_str(program);
+    CallGraph::new(&parser.parse_program().unwrap())
+  }
+
+  /// Creates a set of symbols.
+  fn symbols(names: &[&str]) -> HashSet<Symbol> {
+    names.iter().copied().map(Symbol::new).collect()
+  }
+
+  #[test]
+  fn test_call_graph() {
+    let graph = build(
+      r#"
+      f(x) { return x }
+      g(x) { if x { return f(x) } return f(f(print(x))) }
+      main() { return g(input()) }
+    "#,
+    );
+    assert_eq!(graph.edges().len(), 3);
+    assert_eq!(graph.callees(&Symbol::new("f")), Some(&symbols(&[])));
+    assert_eq!(graph.callees(&Symbol::new("g")), Some(&symbols(&["f", "print"])));
+    assert_eq!(graph.callees(&Symbol::new("main")), Some(&symbols(&["g", "input"])));
+    assert_eq!(graph.callees(&Symbol::new("print")), None);
+    let reachable = graph.reachable_from(&Symbol::new("g"));
+    assert_eq!(reachable, symbols(&["g", "f", "print"]));
+    assert!(graph.recursive_funcs().is_empty());
+  }
+
+  #[test]
+  fn test_recursion() {
+    let graph = build(
+      r#"
+      even(n) { if n == 0 { return 1 } return odd(n - 1) }
+      odd(n) { if n == 0 { return 0 } return even(n - 1) }
+      fact(n) { if n { return n * fact(n - 1) } return 1 }
+      main() { return even(fact(3)) }
+    "#,
+    );
+    assert!(graph.is_recursive(&Symbol::new("even")));
+    assert!(graph.is_recursive(&Symbol::new("odd")));
+    assert!(graph.is_recursive(&Symbol::new("fact")));
+    assert!(!graph.is_recursive(&Symbol::new("main")));
+    assert_eq!(graph.recursive_funcs(), symbols(&["even", "odd", "fact"]));
+  }
+}
@@ -0,0 +1,129 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Symbol, Type};
+
+/// Cyclomatic complexity analyzer, computes McCabe complexity of functions,
+/// which is the number of decision points (`if`, `case`, `do-while`,
+/// `&&` and `||`) plus one.
+#[derive(Default)]
+pub struct ComplexityAnalyzer;
+
+impl ComplexityAnalyzer {
+  /// Creates a new cyclomatic complexity analyzer.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Analyzes the specific program,
+  /// returns names and complexities of all functions.
+  pub fn analyze(&mut self, asts: &[AstBox]) -> Vec<(Symbol, usize)> {
+    asts
+      .iter()
+      .filter_map(|ast| match ast.as_ref() {
+        Ast::FunDef { name, .. } => Some((*name, self.visit(ast))),
+        _ => None,
+      })
+      .collect()
+  }
+}
+
+impl AstVisitor for ComplexityAnalyzer {
+  /// Complexity of function definitions,
+  /// or number of decision points of other nodes.
+  type Result = usize;
+
+  fn visit_fundef(
+    &mut self,
+    _: &Symbol,
+    _: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.visit(body) + 1
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    stmts.iter().map(|stmt| self.visit(stmt)).sum()
+  }
+
+  fn visit_define(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr)
+  }
+
+  fn visit_assign(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr)
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    let else_then = else_then.as_ref().map_or(0, |ast| self.visit(ast));
+    self.visit(cond) + self.visit(then) + else_then + 1
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    let default = default.as_ref().map_or(0, |ast| self.visit(ast));
+    let cases: usize = cases.iter().map(|(_, body)| self.visit(body) + 1).sum();
+    self.visit(scrutinee) + cases + default
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.visit(body) + self.visit(cond) + 1
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    expr.as_ref().map_or(0, |expr| self.visit(expr))
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    let decision = matches!(op, Operator::LAnd | Operator::LOr) as usize;
+    self.visit(lhs) + self.visit(rhs) + decision
+  }
+
+  fn visit_unary(&mut self, _: &Operator, opr: &AstBox) -> Self::Result {
+    self.visit(opr)
+  }
+
+  fn visit_funcall(&mut self, _: &Symbol, args: &[AstBox]) -> Self::Result {
+    args.iter().map(|arg| self.visit(arg)).sum()
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {
+    0
+  }
+
+  fn visit_id(&mut self, _: &Symbol) -> Self::Result {
+    0
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {
+    0
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ComplexityAnalyzer;
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+
+  #[test]
+  fn test_complexity() {
+    let program = r#"
+      f(a, b) {
+        if a && b { return 1 }
+        if a < b { return 2 } else if f(!a || b, b) { return 3 }
+        return 0
+      }
+      g(x) { if x && 1 { return x } if x < 0 { return 0 } return 1 }
+      main() { return f(1, 2) }
+    "#;
+    let mut parser = Parser::from_str(program);
+    let asts = parser.parse_program().unwrap();
+    let (f, g, main) = (Symbol::new("f"), Symbol::new("g"), Symbol::new("main"));
+    assert_eq!(ComplexityAnalyzer::new().analyze(&asts), [(f, 6), (g, 4), (main, 1)]);
+  }
+}
@@ -0,0 +1,199 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Symbol, Type};
+
+/// Dead code eliminator, removes statements following
+/// the first return statement in each block.
+#[derive(Default)]
+pub struct DeadCodeEliminator {
+  /// Number of removed statements.
+  removed: usize,
+}
+
+impl DeadCodeEliminator {
+  /// Creates a new dead code eliminator.
+  pub fn new() -> Self {
+    Self { removed: 0 }
+  }
+
+  /// Eliminates dead code in the specific AST, returns the new AST.
+  pub fn eliminate(&mut self, ast: &AstBox) -> AstBox {
+    self.visit(ast)
+  }
+
+  /// Gets the number of statements removed so far.
+  pub fn removed(&self) -> usize {
+    self.removed
+  }
+}
+
+impl AstVisitor for DeadCodeEliminator {
+  type Result = AstBox;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    arg_types: &[Option<Type>],
+    ret_type: &Option<Type>,
+    body: &AstBox,
+    doc: &Option<String>,
+  ) -> Self::Result {
+    Box::new(Ast::FunDef {
+      name: *name,
+      args: args.into(),
+      arg_types: arg_types.into(),
+      ret_type: *ret_type,
+      body: self.visit(body),
+      doc: doc.clone(),
+    })
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    // keep statements until the first return statement
+    let len = stmts
+      .iter()
+      .position(|stmt| matches!(stmt.as_ref(), Ast::Return { .. }))
+      .map_or(stmts.len(), |pos| pos + 1);
+    self.removed += stmts.len() - len;
+    Box::new(Ast::Block {
+      stmts: stmts[..len].iter().map(|stmt| self.visit(stmt)).collect(),
+    })
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    Box::new(Ast::Define {
+      name: *name,
+      expr: expr.clone(),
+    })
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    Box::new(Ast::Assign {
+      name: *name,
+      expr: expr.clone(),
+    })
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    Box::new(Ast::If {
+      cond: cond.clone(),
+      then: self.visit(then),
+      else_then: else_then.as_ref().map(|ast| self.visit(ast)),
+    })
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    Box::new(Ast::Switch {
+      scrutinee: scrutinee.clone(),
+      cases: cases
+        .iter()
+        .map(|(val, body)| (*val, self.visit(body)))
+        .collect(),
+      default: default.as_ref().map(|ast| self.visit(ast)),
+    })
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    Box::new(Ast::DoWhile {
+      body: self.visit(body),
+      cond: cond.clone(),
+    })
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    Box::new(Ast::Return { expr: expr.clone() })
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    Box::new(Ast::Binary {
+      op: op.clone(),
+      lhs: lhs.clone(),
+      rhs: rhs.clone(),
+    })
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    Box::new(Ast::Unary {
+      op: op.clone(),
+      opr: opr.clone(),
+    })
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    Box::new(Ast::FunCall {
+      name: *name,
+      args: args.into(),
+    })
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    Box::new(Ast::Int { val: *val })
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    Box::new(Ast::Id { id: *val })
+  }
+
+  fn visit_comment(&mut self, text: &str, trailing: &bool) -> Self::Result {
+    Box::new(Ast::Comment {
+      text: text.to_string(),
+      trailing: *trailing,
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::DeadCodeEliminator;
+  use crate::define::AstBox;
+  use crate::front::parser::Parser;
+
+  /// Parses the specific function definition.
+  fn parse(program: &str) -> AstBox {
+    let mut parser = Parser::from_str(program);
+    parser.parse_next().unwrap()
+  }
+
+  #[test]
+  fn test_dce() {
+    let mut dce = DeadCodeEliminator::new();
+    let ast = dce.eliminate(&parse(
+      r#"
+      f(x) {
+        if x {
+          return 1
+          x = 2
+          print(x)
+        } else {
+          x = 3
+        }
+        y := x
+        return y
+        y = 4
+        if y { return 5 }
+      }
+      "#,
+    ));
+    assert_eq!(dce.removed(), 4);
+    // returns in branches do not prune the sibling branches
+    // and the statements following the if statement
+    let expected = parse(
+      r#"
+      f(x) {
+        if x { return 1 } else { x = 3 }
+        y := x
+        return y
+      }
+      "#,
+    );
+    assert!(ast == expected);
+    // nothing to remove
+    let ast = dce.eliminate(&expected);
+    assert!(ast == expected);
+    assert_eq!(dce.removed(), 4);
+  }
+}
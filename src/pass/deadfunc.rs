@@ -0,0 +1,87 @@
+use super::callgraph::CallGraph;
+use crate::define::{Ast, Program, Symbol};
+
+/// Dead function eliminator, removes functions that are never called
+/// (transitively) from the `main` function.
+///
+/// Programs without the `main` function are left unchanged,
+/// since they may be called from other programs.
+#[derive(Default)]
+pub struct DeadFuncEliminator {
+  /// Number of removed functions.
+  removed: usize,
+}
+
+impl DeadFuncEliminator {
+  /// Creates a new dead function eliminator.
+  pub fn new() -> Self {
+    Self { removed: 0 }
+  }
+
+  /// Eliminates dead functions in the specific program, returns the new program.
+  pub fn eliminate(&mut self, program: Program) -> Program {
+    let main = Symbol::new("main");
+    let graph = CallGraph::new(&program);
+    if graph.callees(&main).is_none() {
+      return program;
+    }
+    let reachable = graph.reachable_from(&main);
+    let len = program.len();
+    let program: Program = program
+      .into_iter()
+      .filter(|ast| match ast.as_ref() {
+        Ast::FunDef { name, .. } => reachable.contains(name),
+        _ => true,
+      })
+      .collect();
+    self.removed += len - program.len();
+    program
+  }
+
+  /// Gets the number of functions removed so far.
+  pub fn removed(&self) -> usize {
+    self.removed
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::DeadFuncEliminator;
+  use crate::define::{Ast, Program};
+  use crate::front::parser::Parser;
+
+  /// Parses the specific program.
+  fn parse(program: &str) -> Program {
+    Parser::from_str(program).parse_program().unwrap()
+  }
+
+  /// Gets names of all functions in the specific program.
+  fn names(program: &Program) -> Vec<String> {
+    let names = program.iter().filter_map(|ast| match ast.as_ref() {
+      Ast::FunDef { name, .. } => Some(name.to_string()),
+      _ => None,
+    });
+    names.collect()
+  }
+
+  #[test]
+  fn test_dead_func() {
+    let program = parse(
+      r#"
+      unused(x) { return helper(x) + unused(x - 1) }
+      helper(x) { return x * 2 }
+      even(n) { if n { return odd(n - 1) } else { return 1 } }
+      odd(n) { if n { return even(n - 1) } else { return 0 } }
+      main() { return helper(even(4)) }
+    "#,
+    );
+    let mut eliminator = DeadFuncEliminator::new();
+    let program = eliminator.eliminate(program);
+    assert_eq!(names(&program), ["helper", "even", "odd", "main"]);
+    assert_eq!(eliminator.removed(), 1);
+    // programs without `main`
+    let program = eliminator.eliminate(parse("f() { return 0 } g() { return 1 }"));
+    assert_eq!(names(&program), ["f", "g"]);
+    assert_eq!(eliminator.removed(), 1);
+  }
+}
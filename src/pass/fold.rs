@@ -0,0 +1,171 @@
+use crate::define::{eval_binary, eval_unary, OverflowMode};
+use crate::define::{Ast, AstBox, AstTransformer, Operator};
+use std::convert::TryFrom;
+
+/// Constant folder, replaces expressions whose operands are
+/// integer literals with the folded integer literals.
+///
+/// Overflow is handled in the same way as the interpreter with the same
+/// overflow handling mode. Operations that would fail at runtime
+/// (division by zero, overflow in non-wrapping modes) are left unfolded.
+/// Integers are folded as 32-bit integers by default, like the default mode
+/// of the interpreter, so literals that do not fit in 32 bits are left
+/// unfolded. The 64-bit mode folds integers as 64-bit integers.
+#[derive(Default)]
+pub struct ConstFolder {
+  /// Overflow handling mode of integer arithmetic.
+  overflow_mode: OverflowMode,
+  /// Folds integers as 64-bit integers.
+  int64: bool,
+}
+
+impl ConstFolder {
+  /// Creates a new constant folder.
+  pub fn new() -> Self {
+    Self {
+      overflow_mode: OverflowMode::default(),
+      int64: false,
+    }
+  }
+
+  /// Sets the overflow handling mode of integer arithmetic.
+  pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+    self.overflow_mode = mode;
+  }
+
+  /// Enables or disables the 64-bit mode,
+  /// should match the mode of the interpreter.
+  pub fn set_int64(&mut self, int64: bool) {
+    self.int64 = int64;
+  }
+
+  /// Folds the specific AST, returns the folded AST.
+  pub fn fold(&mut self, ast: AstBox) -> AstBox {
+    self.transform(ast)
+  }
+
+  /// Gets the value of the specific integer literal, returns `None`
+  /// if it is not an integer literal of the current integer type.
+  fn int_val(&self, ast: &AstBox) -> Option<i64> {
+    match ast.as_ref() {
+      Ast::Int { val } if self.int64 || i32::try_from(*val).is_ok() => Some(*val),
+      _ => None,
+    }
+  }
+}
+
+impl AstTransformer for ConstFolder {
+  fn transform_binary(&mut self, op: Operator, lhs: AstBox, rhs: AstBox) -> AstBox {
+    let lhs = self.transform(lhs);
+    let rhs = self.transform(rhs);
+    if let (Some(l), Some(r)) = (self.int_val(&lhs), self.int_val(&rhs)) {
+      let mode = self.overflow_mode.const_eval_mode();
+      let val = if self.int64 {
+        eval_binary(&op, l, r, mode)
+      } else {
+        eval_binary(&op, l as i32, r as i32, mode).map(i64::from)
+      };
+      if let Ok(val) = val {
+        return Box::new(Ast::Int { val });
+      }
+    }
+    Box::new(Ast::Binary { op, lhs, rhs })
+  }
+
+  fn transform_unary(&mut self, op: Operator, opr: AstBox) -> AstBox {
+    let opr = self.transform(opr);
+    if let Some(val) = self.int_val(&opr) {
+      let mode = self.overflow_mode.const_eval_mode();
+      let val = if self.int64 {
+        eval_unary(&op, val, mode)
+      } else {
+        eval_unary(&op, val as i32, mode).map(i64::from)
+      };
+      if let Ok(val) = val {
+        return Box::new(Ast::Int { val });
+      }
+    }
+    Box::new(Ast::Unary { op, opr })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ConstFolder;
+  use crate::define::{Ast, AstBox, AstBuilder, AstVisitor, Operator, OverflowMode};
+  use crate::dump::pretty::Printer;
+  use crate::front::{lexer::Lexer, parser::Parser};
+  use std::io::Cursor;
+
+  /// Parses the specific expression, integer literals can be 64-bit.
+  fn parse(expr: &str) -> AstBox {
+    let mut lexer = Lexer::new(Cursor::new(expr));
+    lexer.set_int64(true);
+    Parser::new(lexer).parse_next_expr().unwrap()
+  }
+
+  /// Folds the specific expression.
+  fn fold(expr: &str) -> AstBox {
+    ConstFolder::new().fold(parse(expr))
+  }
+
+  #[test]
+  fn test_fold() {
+    assert!(fold("2 + 3 * 4") == Box::new(Ast::Int { val: 14 }));
+    assert!(fold("-(1 - 3) * (10 % 4) == 4 && !0") == Box::new(Ast::Int { val: 1 }));
+    // partially constant expressions
+    assert!(fold("x * (1 + 2) - f(4 / 2, y)") == parse("x * 3 - f(2, y)"));
+    assert!(fold("(x + 1) + 2") == parse("x + 1 + 2"));
+    // statements
+    let program = "f(x) { y := 1 + 1 if x < 3 * 3 { return y } else { return 0 - 1 } }";
+    let mut parser = Parser::from_str(program);
+    let folded = ConstFolder::new().fold(parser.parse_next().unwrap());
+    let expected = "f(x) {\n  y := 2\n  if x < 9 {\n    return y\n  } else {\n    return -1\n  }\n}";
+    assert_eq!(Printer::new().visit(&folded), expected);
+  }
+
+  #[test]
+  fn test_fold_div_by_zero() {
+    assert!(fold("1 / 0") == parse("1 / 0"));
+    assert!(fold("x + 7 % (2 - 2)") == parse("x + 7 % 0"));
+  }
+
+  /// Folds the specific expression under the specific overflow mode.
+  fn fold_overflow(mode: OverflowMode, expr: &str) -> AstBox {
+    let mut folder = ConstFolder::new();
+    folder.set_overflow_mode(mode);
+    folder.fold(parse(expr))
+  }
+
+  #[test]
+  fn test_fold_overflow() {
+    let (add, neg) = ("2147483647 + 1", "-(0 - 2147483647 - 1)");
+    let min = Box::new(Ast::Int { val: i32::MIN.into() });
+    assert!(fold(add) == min);
+    assert!(fold_overflow(OverflowMode::Wrapping, add) == min);
+    assert!(fold_overflow(OverflowMode::Wrapping, neg) == min);
+    // overflow is left to the runtime
+    assert!(fold_overflow(OverflowMode::Checked, add) == parse(add));
+    let b = AstBuilder::new();
+    let partial = b.unary(Operator::Sub, b.int(i32::MIN.into()));
+    assert!(fold_overflow(OverflowMode::Checked, neg) == partial);
+    assert!(fold_overflow(OverflowMode::Panicking, add) == parse(add));
+    let no_overflow = Box::new(Ast::Int { val: i32::MAX.into() });
+    assert!(fold_overflow(OverflowMode::Checked, "2147483646 + 1") == no_overflow);
+    // division by zero is never folded
+    assert!(fold_overflow(OverflowMode::Wrapping, "1 / 0") == parse("1 / 0"));
+  }
+
+  #[test]
+  fn test_fold_int64() {
+    let mut folder = ConstFolder::new();
+    folder.set_int64(true);
+    let big = Box::new(Ast::Int { val: 2147483648 });
+    assert!(folder.fold(parse("2147483647 + 1")) == big);
+    assert!(folder.fold(parse("3000000000 * 2")) == Box::new(Ast::Int { val: 6000000000 }));
+    let min = Box::new(Ast::Int { val: i64::MIN });
+    assert!(folder.fold(parse("9223372036854775807 + 1")) == min);
+    // literals beyond 32 bits are left unfolded in the 32-bit mode
+    assert!(fold("3000000000 * 2") == parse("3000000000 * 2"));
+  }
+}
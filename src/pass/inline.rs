@@ -0,0 +1,305 @@
+use super::{callgraph::CallGraph, metrics::NodeCounter};
+use crate::define::{ArgList, Ast, AstBox, AstTransformer, Program, Symbol};
+use std::collections::{HashMap, HashSet};
+
+/// Default maximum size (number of nodes) of expressions to be inlined.
+const DEFAULT_MAX_SIZE: usize = 8;
+
+/// Function inliner, inlines calls to small non-recursive functions
+/// whose bodies consist of a single return statement.
+///
+/// Integer literals and identifiers are substituted for parameters
+/// directly. Other arguments are bound to temporaries defined before
+/// the statement, so calls with such arguments are only inlined if they
+/// are the whole expression of a statement, which preserves the
+/// evaluation order.
+pub struct Inliner {
+  /// Maximum size of expressions to be inlined.
+  max_size: usize,
+  /// Parameters and returned expressions of inlinable functions.
+  funcs: HashMap<Symbol, (ArgList<Symbol>, AstBox)>,
+  /// Number of temporaries defined so far.
+  temps: usize,
+  /// Number of inlined calls.
+  inlined: usize,
+}
+
+impl Inliner {
+  /// Creates a new inliner.
+  pub fn new() -> Self {
+    Self {
+      max_size: DEFAULT_MAX_SIZE,
+      funcs: HashMap::new(),
+      temps: 0,
+      inlined: 0,
+    }
+  }
+
+  /// Sets the maximum size (number of nodes) of expressions to be inlined.
+  pub fn set_max_size(&mut self, max_size: usize) {
+    self.max_size = max_size;
+  }
+
+  /// Inlines function calls in the specific program, returns the new program.
+  pub fn inline(&mut self, program: Program) -> Program {
+    let graph = CallGraph::new(&program);
+    self.funcs.clear();
+    for ast in &program {
+      if let Ast::FunDef { name, args, body, .. } = ast.as_ref() {
+        if let Some(expr) = self.inlinable_expr(body) {
+          let distinct = args.iter().collect::<HashSet<_>>().len() == args.len();
+          if distinct && !graph.is_recursive(name) {
+            self.funcs.insert(*name, (args.clone(), expr.clone()));
+          }
+        }
+      }
+    }
+    program.into_iter().map(|ast| self.transform(ast)).collect()
+  }
+
+  /// Gets the number of calls inlined so far.
+  pub fn inlined(&self) -> usize {
+    self.inlined
+  }
+
+  /// Gets the returned expression of the specific function body
+  /// if the function can be inlined.
+  fn inlinable_expr<'a>(&self, body: &'a AstBox) -> Option<&'a AstBox> {
+    match body.as_ref() {
+      Ast::Block { stmts } => match stmts.as_slice() {
+        [stmt] => match stmt.as_ref() {
+          Ast::Return { expr: Some(expr) } => {
+            let size = NodeCounter::new().count(std::slice::from_ref(expr)).total();
+            (size <= self.max_size).then_some(expr)
+          }
+          _ => None,
+        },
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// Transforms the whole expression of a statement, arguments of
+  /// inlined calls are bound to temporaries defined in `stmts`.
+  fn transform_root(&mut self, expr: Ast, stmts: &mut Vec<AstBox>) -> AstBox {
+    match expr {
+      Ast::FunCall { name, args } if self.can_inline(&name, &args) => {
+        let args = args
+          .into_iter()
+          .map(|arg| {
+            let arg = self.transform(arg);
+            if is_trivial(&arg) {
+              return arg;
+            }
+            let name = Symbol::new(&format!("$inline{}", self.temps));
+            self.temps += 1;
+            stmts.push(Box::new(Ast::Define { name, expr: arg }));
+            Box::new(Ast::Id { id: name })
+          })
+          .collect();
+        self.inline_call(&name, args)
+      }
+      expr => self.transform(Box::new(expr)),
+    }
+  }
+
+  /// Checks if the specific function call can be inlined.
+  fn can_inline(&self, name: &Symbol, args: &[AstBox]) -> bool {
+    self
+      .funcs
+      .get(name)
+      .is_some_and(|(params, _)| params.len() == args.len())
+  }
+
+  /// Inlines the call to the specific function with trivial arguments.
+  fn inline_call(&mut self, name: &Symbol, args: Vec<AstBox>) -> AstBox {
+    let (params, expr) = &self.funcs[name];
+    let mut substituter = Substituter {
+      args: params.iter().copied().zip(args).collect(),
+    };
+    let expr = substituter.transform(expr.clone());
+    self.inlined += 1;
+    // the inlined expression may contain calls to other small functions
+    self.transform(expr)
+  }
+}
+
+impl Default for Inliner {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl AstTransformer for Inliner {
+  fn transform_block(&mut self, stmts: Vec<AstBox>) -> AstBox {
+    let mut new_stmts = Vec::new();
+    for stmt in stmts {
+      let stmt = match *stmt {
+        Ast::Define { name, expr } => Ast::Define {
+          name,
+          expr: self.transform_root(*expr, &mut new_stmts),
+        },
+        Ast::Assign { name, expr } => Ast::Assign {
+          name,
+          expr: self.transform_root(*expr, &mut new_stmts),
+        },
+        Ast::Return { expr: Some(expr) } => Ast::Return {
+          expr: Some(self.transform_root(*expr, &mut new_stmts)),
+        },
+        Ast::If {
+          cond,
+          then,
+          else_then,
+        } => Ast::If {
+          cond: self.transform_root(*cond, &mut new_stmts),
+          then: self.transform(then),
+          else_then: else_then.map(|ast| self.transform(ast)),
+        },
+        stmt => *self.transform(Box::new(stmt)),
+      };
+      new_stmts.push(Box::new(stmt));
+    }
+    Box::new(Ast::Block { stmts: new_stmts })
+  }
+
+  fn transform_funcall(&mut self, name: Symbol, args: ArgList<AstBox>) -> AstBox {
+    let args: Vec<_> = args.into_iter().map(|arg| self.transform(arg)).collect();
+    if self.can_inline(&name, &args) && args.iter().all(is_trivial) {
+      self.inline_call(&name, args)
+    } else {
+      Box::new(Ast::FunCall {
+        name,
+        args: args.into_iter().collect(),
+      })
+    }
+  }
+}
+
+/// Substituter, replaces parameters with arguments.
+struct Substituter {
+  /// Arguments of all parameters.
+  args: HashMap<Symbol, AstBox>,
+}
+
+impl AstTransformer for Substituter {
+  fn transform_id(&mut self, id: Symbol) -> AstBox {
+    match self.args.get(&id) {
+      Some(arg) => arg.clone(),
+      None => Box::new(Ast::Id { id }),
+    }
+  }
+}
+
+/// Checks if the specific argument can be substituted for parameters
+/// directly, that is, evaluating it any number of times has no effect.
+fn is_trivial(arg: &AstBox) -> bool {
+  matches!(arg.as_ref(), Ast::Int { .. } | Ast::Id { .. })
+}
+
+#[cfg(test)]
+mod test {
+  use super::Inliner;
+  use crate::back::interpreter::Interpreter;
+  use crate::define::{AstBuilder, Operator::*, Program};
+  use crate::front::parser::Parser;
+
+  /// Parses the specific program.
+  fn parse(program: &str) -> Program {
+    Parser::from_str(program).parse_program().unwrap()
+  }
+
+  /// Evaluates the specific program.
+  fn eval(program: Program) -> i64 {
+    let mut intp = Interpreter::new();
+    for ast in program {
+      intp.add_func_def(ast).unwrap();
+    }
+    intp.eval().unwrap()
+  }
+
+  #[test]
+  fn test_inline() {
+    let program = parse(
+      r#"
+      sq(x) { return x * x }
+      main() { y := 3 return sq(y) + sq(2) }
+    "#,
+    );
+    let mut inliner = Inliner::new();
+    let inlined = inliner.inline(program.clone());
+    assert_eq!(inliner.inlined(), 2);
+    let b = AstBuilder::new();
+    let main = b.fundef(
+      "main",
+      [],
+      b.block([
+        b.define("y", b.int(3)),
+        b.ret(b.binary(
+          Add,
+          b.binary(Mul, b.id("y"), b.id("y")),
+          b.binary(Mul, b.int(2), b.int(2)),
+        )),
+      ]),
+    );
+    assert!(inlined[0] == program[0]);
+    assert!(inlined[1] == main);
+    assert_eq!(eval(inlined), 13);
+  }
+
+  #[test]
+  fn test_inline_temps() {
+    // non-trivial arguments are evaluated once, before the inlined body
+    let program = parse(
+      r#"
+      add(x, y) { return y + x }
+      sq(x) { return x * x }
+      main() { x := 1 x = sq(add(x, 2)) return sq(x + 1) - sq(x + 1) + x }
+    "#,
+    );
+    let mut inliner = Inliner::new();
+    let inlined = inliner.inline(program);
+    assert_eq!(inliner.inlined(), 2);
+    let b = AstBuilder::new();
+    let main = b.fundef(
+      "main",
+      [],
+      b.block([
+        b.define("x", b.int(1)),
+        b.define("$inline0", b.binary(Add, b.int(2), b.id("x"))),
+        b.assign("x", b.binary(Mul, b.id("$inline0"), b.id("$inline0"))),
+        b.ret(b.binary(
+          Add,
+          b.binary(
+            Sub,
+            b.funcall("sq", [b.binary(Add, b.id("x"), b.int(1))]),
+            b.funcall("sq", [b.binary(Add, b.id("x"), b.int(1))]),
+          ),
+          b.id("x"),
+        )),
+      ]),
+    );
+    assert!(inlined[2] == main);
+    assert_eq!(eval(inlined), 9);
+  }
+
+  #[test]
+  fn test_inline_limits() {
+    let source = r#"
+      fact(n) { if n { return n * fact(n - 1) } else { return 1 } }
+      big(x) { return x + x + x + x + x }
+      two(x) { y := x return y }
+      main() { return fact(3) + big(1) + two(2) }
+    "#;
+    let mut inliner = Inliner::new();
+    let program = parse(source);
+    assert!(inliner.inline(program.clone()) == program);
+    assert_eq!(inliner.inlined(), 0);
+    // size of `big(x)` is 9
+    let mut inliner = Inliner::new();
+    inliner.set_max_size(9);
+    let inlined = inliner.inline(parse(source));
+    assert_eq!(inliner.inlined(), 1);
+    assert_eq!(eval(inlined), 13);
+  }
+}
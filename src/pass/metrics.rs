@@ -0,0 +1,215 @@
+use crate::define::{AstBox, AstVisitor, Operator, Symbol, Type};
+
+/// Numbers of AST nodes of each variant.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeCounts {
+  /// Number of function definitions.
+  pub fundef: usize,
+  /// Number of statement blocks.
+  pub block: usize,
+  /// Number of define statements.
+  pub define: usize,
+  /// Number of assign statements.
+  pub assign: usize,
+  /// Number of if-else statements.
+  pub if_else: usize,
+  /// Number of switch statements.
+  pub switch: usize,
+  /// Number of do-while statements.
+  pub do_while: usize,
+  /// Number of return statements.
+  pub ret: usize,
+  /// Number of binary expressions.
+  pub binary: usize,
+  /// Number of unary expressions.
+  pub unary: usize,
+  /// Number of function calls.
+  pub funcall: usize,
+  /// Number of integer literals.
+  pub int: usize,
+  /// Number of identifiers.
+  pub id: usize,
+}
+
+impl NodeCounts {
+  /// Gets the total number of nodes.
+  pub fn total(&self) -> usize {
+    self.fundef
+      + self.block
+      + self.define
+      + self.assign
+      + self.if_else
+      + self.switch
+      + self.do_while
+      + self.ret
+      + self.binary
+      + self.unary
+      + self.funcall
+      + self.int
+      + self.id
+  }
+}
+
+/// Node counter, counts AST nodes by variant.
+#[derive(Default)]
+pub struct NodeCounter {
+  /// Numbers of nodes counted so far.
+  counts: NodeCounts,
+}
+
+impl NodeCounter {
+  /// Creates a new node counter.
+  pub fn new() -> Self {
+    Self {
+      counts: NodeCounts::default(),
+    }
+  }
+
+  /// Counts nodes of the specific ASTs.
+  pub fn count(&mut self, asts: &[AstBox]) -> NodeCounts {
+    for ast in asts {
+      self.visit(ast);
+    }
+    std::mem::take(&mut self.counts)
+  }
+}
+
+impl AstVisitor for NodeCounter {
+  type Result = ();
+
+  fn visit_fundef(
+    &mut self,
+    _: &Symbol,
+    _: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.counts.fundef += 1;
+    self.visit(body);
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    self.counts.block += 1;
+    for stmt in stmts {
+      self.visit(stmt);
+    }
+  }
+
+  fn visit_define(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.counts.define += 1;
+    self.visit(expr);
+  }
+
+  fn visit_assign(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.counts.assign += 1;
+    self.visit(expr);
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.counts.if_else += 1;
+    self.visit(cond);
+    self.visit(then);
+    if let Some(else_then) = else_then {
+      self.visit(else_then);
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.counts.switch += 1;
+    self.visit(scrutinee);
+    for (_, body) in cases {
+      self.visit(body);
+    }
+    if let Some(default) = default {
+      self.visit(default);
+    }
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.counts.do_while += 1;
+    self.visit(body);
+    self.visit(cond);
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    self.counts.ret += 1;
+    if let Some(expr) = expr {
+      self.visit(expr);
+    }
+  }
+
+  fn visit_binary(&mut self, _: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    self.counts.binary += 1;
+    self.visit(lhs);
+    self.visit(rhs);
+  }
+
+  fn visit_unary(&mut self, _: &Operator, opr: &AstBox) -> Self::Result {
+    self.counts.unary += 1;
+    self.visit(opr);
+  }
+
+  fn visit_funcall(&mut self, _: &Symbol, args: &[AstBox]) -> Self::Result {
+    self.counts.funcall += 1;
+    for arg in args {
+      self.visit(arg);
+    }
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {
+    self.counts.int += 1;
+  }
+
+  fn visit_id(&mut self, _: &Symbol) -> Self::Result {
+    self.counts.id += 1;
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {}
+}
+
+#[cfg(test)]
+mod test {
+  use super::{NodeCounter, NodeCounts};
+  use crate::front::parser::Parser;
+
+  #[test]
+  fn test_count() {
+    let program = r#"
+      fib(n) {
+        if n <= 2 { return 1 } else { return fib(n - 1) + fib(n - 2) }
+      }
+      main() { x := -input() x = fib(x) return x }
+    "#;
+    let mut parser = Parser::from_str(program);
+    let counts = NodeCounter::new().count(&parser.parse_program().unwrap());
+    assert_eq!(counts.int, 4);
+    assert_eq!(counts.binary, 4);
+    assert_eq!(
+      counts,
+      NodeCounts {
+        fundef: 2,
+        block: 4,
+        define: 1,
+        assign: 1,
+        if_else: 1,
+        switch: 0,
+        do_while: 0,
+        ret: 3,
+        binary: 4,
+        unary: 1,
+        funcall: 4,
+        int: 4,
+        id: 5,
+      },
+    );
+    assert_eq!(counts.total(), 30);
+    assert_eq!(NodeCounter::new().count(&[]).total(), 0);
+  }
+}
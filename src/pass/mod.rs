@@ -0,0 +1,14 @@
+pub mod arity;
+pub mod callgraph;
+pub mod complexity;
+pub mod dce;
+pub mod deadfunc;
+pub mod fold;
+pub mod inline;
+pub mod metrics;
+pub mod purity;
+pub mod resolve;
+pub mod returns;
+pub mod simplify;
+pub mod typeck;
+pub mod unused;
@@ -0,0 +1,71 @@
+use super::callgraph::CallGraph;
+use crate::define::{Program, Symbol};
+use std::collections::HashSet;
+
+/// Purity analyzer, finds functions without side effects, that is,
+/// functions which do not call `input`, `print`, native functions and
+/// undefined functions, directly or transitively.
+///
+/// Pure functions may still fail at runtime (like division by zero).
+#[derive(Default)]
+pub struct PurityAnalyzer;
+
+impl PurityAnalyzer {
+  /// Creates a new purity analyzer.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Analyzes the specific program, returns names of all pure functions.
+  pub fn analyze(&self, program: &Program) -> HashSet<Symbol> {
+    let graph = CallGraph::new(program);
+    // assume all functions are pure, and remove impure functions until
+    // reaching the fixed point, so that recursive functions can be pure
+    let mut pure: HashSet<_> = graph.edges().keys().copied().collect();
+    loop {
+      let impure: Vec<_> = pure
+        .iter()
+        .filter(|name| !graph.edges()[name].iter().all(|callee| pure.contains(callee)))
+        .copied()
+        .collect();
+      if impure.is_empty() {
+        return pure;
+      }
+      for name in impure {
+        pure.remove(&name);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::PurityAnalyzer;
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+  use std::collections::HashSet;
+
+  /// Parses and analyzes the specific program.
+  fn analyze(program: &str) -> HashSet<Symbol> {
+    let mut parser = Parser::from_str(program);
+    PurityAnalyzer::new().analyze(&parser.parse_program().unwrap())
+  }
+
+  #[test]
+  fn test_purity() {
+    let pure = analyze(
+      r#"
+      sq(x) { return x * x }
+      fib(n) { if n < 2 { return n } else { return fib(n - 1) + fib(n - 2) } }
+      log(x) { print(x) return sq(x) }
+      read() { return input() }
+      caller(x) { return sq(log(x)) }
+      even(n) { if n { return odd(n - 1) } else { return native(n) } }
+      odd(n) { if n { return even(n - 1) } else { return 0 } }
+      main() { return fib(sq(5)) }
+    "#,
+    );
+    let expected: HashSet<_> = ["sq", "fib", "main"].iter().copied().map(Symbol::new).collect();
+    assert_eq!(pure, expected);
+  }
+}
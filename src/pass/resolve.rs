@@ -0,0 +1,245 @@
+use crate::define::{AstBox, AstVisitor, NestedMap, Operator, Symbol, Type, closest};
+use std::{error, fmt};
+
+/// Error reported by `Resolver`.
+#[derive(Debug, PartialEq)]
+pub enum ResolveError {
+  /// Use of an undefined variable,
+  /// with the closest name in scope as a suggestion.
+  Undefined(Symbol, Option<Symbol>),
+  /// Redefinition of a variable in the same scope.
+  Redefined(Symbol),
+}
+
+impl fmt::Display for ResolveError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ResolveError::Undefined(name, None) => write!(f, "symbol `{}` has not been defined", name),
+      ResolveError::Undefined(name, Some(sugg)) => write!(
+        f,
+        "symbol `{}` has not been defined, did you mean `{}`?",
+        name, sugg
+      ),
+      ResolveError::Redefined(name) => write!(f, "symbol `{}` has already been defined", name),
+    }
+  }
+}
+
+impl error::Error for ResolveError {}
+
+/// Name resolver, checks variable references and definitions statically.
+///
+/// Each block introduces a new scope, and function
+/// parameters are defined in the scope of the function.
+/// Variables can only be shadowed in nested scopes.
+#[derive(Default)]
+pub struct Resolver {
+  /// Variables defined in all enclosing scopes.
+  scopes: NestedMap<Symbol, ()>,
+  /// Errors reported so far.
+  errors: Vec<ResolveError>,
+}
+
+impl Resolver {
+  /// Creates a new resolver.
+  pub fn new() -> Self {
+    Self {
+      scopes: NestedMap::new(),
+      errors: Vec::new(),
+    }
+  }
+
+  /// Resolves the specific AST, returns all errors if any.
+  pub fn resolve(&mut self, ast: &AstBox) -> Result<(), Vec<ResolveError>> {
+    self.visit(ast);
+    if self.errors.is_empty() {
+      Ok(())
+    } else {
+      Err(std::mem::take(&mut self.errors))
+    }
+  }
+
+  /// Checks if the specific variable has been defined.
+  fn check_defined(&mut self, name: &Symbol) {
+    if self.scopes.get_rec(name).is_none() {
+      let names = self.scopes.keys().map(|name| name.as_str());
+      let sugg = closest(name.as_str(), names, MAX_SUGGEST_DISTANCE).map(Symbol::new);
+      self.errors.push(ResolveError::Undefined(*name, sugg));
+    }
+  }
+}
+
+impl AstVisitor for Resolver {
+  type Result = ();
+
+  fn visit_fundef(
+    &mut self,
+    _: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.scopes.push();
+    for arg in args {
+      self.scopes.add(*arg, ());
+    }
+    self.visit(body);
+    self.scopes.pop();
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    self.scopes.push();
+    for stmt in stmts {
+      self.visit(stmt);
+    }
+    self.scopes.pop();
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    // the expression is evaluated before the definition
+    self.visit(expr);
+    if !self.scopes.add(*name, ()) {
+      self.errors.push(ResolveError::Redefined(*name));
+    }
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+    self.check_defined(name);
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.visit(cond);
+    self.visit(then);
+    if let Some(else_then) = else_then {
+      self.visit(else_then);
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.visit(scrutinee);
+    for (_, body) in cases {
+      self.visit(body);
+    }
+    if let Some(default) = default {
+      self.visit(default);
+    }
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.visit(body);
+    self.visit(cond);
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    if let Some(expr) = expr {
+      self.visit(expr);
+    }
+  }
+
+  fn visit_binary(&mut self, _: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    self.visit(lhs);
+    self.visit(rhs);
+  }
+
+  fn visit_unary(&mut self, _: &Operator, opr: &AstBox) -> Self::Result {
+    self.visit(opr);
+  }
+
+  fn visit_funcall(&mut self, _: &Symbol, args: &[AstBox]) -> Self::Result {
+    for arg in args {
+      self.visit(arg);
+    }
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {}
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    self.check_defined(val);
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {}
+}
+
+/// Maximum edit distance between an undefined name and its suggestion.
+const MAX_SUGGEST_DISTANCE: usize = 2;
+
+#[cfg(test)]
+mod test {
+  use super::{ResolveError, Resolver};
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+
+  /// Parses and resolves the specific function definition.
+  fn resolve(program: &str) -> Result<(), Vec<ResolveError>> {
+    let mut parser = Parser::from_str(program);
+    Resolver::new().resolve(&parser.parse_next().unwrap())
+  }
+
+  /// Creates undefined errors of the specific names.
+  fn undefined(names: &[&str]) -> Result<(), Vec<ResolveError>> {
+    Err(names.iter().map(|name| ResolveError::Undefined(Symbol::new(name), None)).collect())
+  }
+
+  #[test]
+  fn test_resolve() {
+    let program = r#"
+      f(a, b) {
+        x := a + b
+        if x { y := x x = y } else { x = f(x, a) }
+        return x
+      }
+    "#;
+    assert_eq!(resolve(program), Ok(()));
+  }
+
+  #[test]
+  fn test_undefined() {
+    assert_eq!(resolve("f(x) { return x + y }"), undefined(&["y"]));
+    let err = resolve("f() { return y }").unwrap_err();
+    assert_eq!(err[0].to_string(), "symbol `y` has not been defined");
+    // assign targets
+    assert_eq!(resolve("f(x) { y = x }"), undefined(&["y"]));
+    // variables are not visible outside their blocks
+    assert_eq!(
+      resolve("f(x) { if x { y := 1 } else { z := y } return y + z }"),
+      undefined(&["y", "y", "z"]),
+    );
+    // variables are not visible in their initializers
+    assert_eq!(resolve("f() { x := x }"), undefined(&["x"]));
+  }
+
+  #[test]
+  fn test_suggest() {
+    let err = resolve("f(total) { count := 0 count = cont + total return count }").unwrap_err();
+    let (cont, count) = (Symbol::new("cont"), Symbol::new("count"));
+    assert_eq!(err, vec![ResolveError::Undefined(cont, Some(count))]);
+    assert_eq!(err[0].to_string(), "symbol `cont` has not been defined, did you mean `count`?");
+    // names in outer scopes are suggested, names out of scope are not
+    let err = resolve("f(index) { if 1 { return indx } x := 1 if x { value := 1 } return valeu }");
+    let err = err.unwrap_err();
+    assert_eq!(err[0], ResolveError::Undefined(Symbol::new("indx"), Some(Symbol::new("index"))));
+    assert_eq!(err[1], ResolveError::Undefined(Symbol::new("valeu"), None));
+  }
+
+  #[test]
+  fn test_redefined() {
+    let err = resolve("f() { x := 1 x := 2 return x }").unwrap_err();
+    assert_eq!(err, vec![ResolveError::Redefined(Symbol::new("x"))]);
+    assert_eq!(err[0].to_string(), "symbol `x` has already been defined");
+    assert_eq!(
+      resolve("f(x) { if x { y := 1 y := y } else { y := 2 } return x }"),
+      Err(vec![ResolveError::Redefined(Symbol::new("y"))]),
+    );
+    // shadowing in nested blocks
+    assert_eq!(resolve("f() { x := 1 if x { x := 2 x = x } return x }"), Ok(()));
+    assert_eq!(resolve("f(x) { x := x + 1 return x }"), Ok(()));
+  }
+}
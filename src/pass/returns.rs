@@ -0,0 +1,151 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, Program, Symbol, Type};
+
+/// Return checker, checks if function bodies return on all paths.
+///
+/// A statement returns if it is a return statement, a block containing
+/// a statement that returns, an if-else statement whose branches
+/// both return, or a switch statement with a default case whose cases
+/// all return, or a do-while statement whose body returns.
+#[derive(Default)]
+pub struct ReturnChecker;
+
+impl ReturnChecker {
+  /// Creates a new return checker.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Checks all functions in the specific program,
+  /// returns names of functions that may fall through if any.
+  pub fn check(&mut self, program: &Program) -> Result<(), Vec<Symbol>> {
+    let funcs: Vec<_> = program
+      .iter()
+      .filter_map(|ast| match ast.as_ref() {
+        Ast::FunDef { name, body, .. } if !self.visit(body) => Some(*name),
+        _ => None,
+      })
+      .collect();
+    if funcs.is_empty() {
+      Ok(())
+    } else {
+      Err(funcs)
+    }
+  }
+}
+
+impl AstVisitor for ReturnChecker {
+  type Result = bool;
+
+  fn visit_fundef(
+    &mut self,
+    _: &Symbol,
+    _: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.visit(body)
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    stmts.iter().any(|stmt| self.visit(stmt))
+  }
+
+  fn visit_define(&mut self, _: &Symbol, _: &AstBox) -> Self::Result {
+    false
+  }
+
+  fn visit_assign(&mut self, _: &Symbol, _: &AstBox) -> Self::Result {
+    false
+  }
+
+  fn visit_if(&mut self, _: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    else_then
+      .as_ref()
+      .is_some_and(|else_then| self.visit(then) && self.visit(else_then))
+  }
+
+  fn visit_switch(
+    &mut self,
+    _: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    default.as_ref().is_some_and(|default| {
+      cases.iter().all(|(_, body)| self.visit(body)) && self.visit(default)
+    })
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, _: &AstBox) -> Self::Result {
+    // the body is evaluated at least once
+    self.visit(body)
+  }
+
+  fn visit_return(&mut self, _: &Option<AstBox>) -> Self::Result {
+    true
+  }
+
+  fn visit_binary(&mut self, _: &Operator, _: &AstBox, _: &AstBox) -> Self::Result {
+    false
+  }
+
+  fn visit_unary(&mut self, _: &Operator, _: &AstBox) -> Self::Result {
+    false
+  }
+
+  fn visit_funcall(&mut self, _: &Symbol, _: &[AstBox]) -> Self::Result {
+    false
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {
+    false
+  }
+
+  fn visit_id(&mut self, _: &Symbol) -> Self::Result {
+    false
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {
+    false
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::ReturnChecker;
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+
+  /// Parses and checks the specific program.
+  fn check(program: &str) -> Result<(), Vec<Symbol>> {
+    let mut parser = Parser::from_str(program);
+    ReturnChecker::new().check(&parser.parse_program().unwrap())
+  }
+
+  #[test]
+  fn test_always_return() {
+    assert_eq!(check("f() { return 1 }"), Ok(()));
+    assert_eq!(check("f(x) { x = 1 return x print(x) }"), Ok(()));
+    assert_eq!(check("f(x) { if x { return 1 } else { return 2 } }"), Ok(()));
+    assert_eq!(
+      check("f(x) { if x { return 1 } else if x - 1 { return 2 } else { return 3 } }"),
+      Ok(()),
+    );
+  }
+
+  #[test]
+  fn test_fall_through() {
+    // only the then-branch returns
+    assert_eq!(check("f(x) { if x { return 1 } }"), Err(vec![Symbol::new("f")]));
+    assert_eq!(
+      check("f(x) { if x { return 1 } else if x - 1 { return 2 } }"),
+      Err(vec![Symbol::new("f")]),
+    );
+    // no return at all
+    assert_eq!(
+      check("f(x) { print(x) } g() { return 0 } h() {}"),
+      Err(vec![Symbol::new("f"), Symbol::new("h")]),
+    );
+  }
+}
@@ -0,0 +1,270 @@
+use crate::define::{Ast, AstBox, AstVisitor, Operator, OverflowMode, Symbol, Type};
+
+/// Algebraic simplifier, rewrites arithmetic identities like
+/// `x + 0`, `x * 1` and `x * 0`.
+///
+/// Operands are only dropped if they are pure, so side effects and
+/// runtime errors are preserved. The overflow handling mode should match
+/// the interpreter's, arithmetic that may overflow is only dropped
+/// in the wrapping mode.
+#[derive(Default)]
+pub struct Simplifier {
+  /// Overflow handling mode of integer arithmetic.
+  overflow_mode: OverflowMode,
+}
+
+impl Simplifier {
+  /// Creates a new simplifier.
+  pub fn new() -> Self {
+    Self {
+      overflow_mode: OverflowMode::default(),
+    }
+  }
+
+  /// Sets the overflow handling mode of integer arithmetic.
+  pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+    self.overflow_mode = mode;
+  }
+
+  /// Simplifies the specific AST, returns the simplified AST.
+  pub fn simplify(&mut self, ast: &AstBox) -> AstBox {
+    self.visit(ast)
+  }
+}
+
+impl AstVisitor for Simplifier {
+  type Result = AstBox;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    arg_types: &[Option<Type>],
+    ret_type: &Option<Type>,
+    body: &AstBox,
+    doc: &Option<String>,
+  ) -> Self::Result {
+    Box::new(Ast::FunDef {
+      name: *name,
+      args: args.into(),
+      arg_types: arg_types.into(),
+      ret_type: *ret_type,
+      body: self.visit(body),
+      doc: doc.clone(),
+    })
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    Box::new(Ast::Block {
+      stmts: stmts.iter().map(|stmt| self.visit(stmt)).collect(),
+    })
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    Box::new(Ast::Define {
+      name: *name,
+      expr: self.visit(expr),
+    })
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    Box::new(Ast::Assign {
+      name: *name,
+      expr: self.visit(expr),
+    })
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    Box::new(Ast::If {
+      cond: self.visit(cond),
+      then: self.visit(then),
+      else_then: else_then.as_ref().map(|ast| self.visit(ast)),
+    })
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    Box::new(Ast::Switch {
+      scrutinee: self.visit(scrutinee),
+      cases: cases
+        .iter()
+        .map(|(val, body)| (*val, self.visit(body)))
+        .collect(),
+      default: default.as_ref().map(|ast| self.visit(ast)),
+    })
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    Box::new(Ast::DoWhile {
+      body: self.visit(body),
+      cond: self.visit(cond),
+    })
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    Box::new(Ast::Return {
+      expr: expr.as_ref().map(|expr| self.visit(expr)),
+    })
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    let lhs = self.visit(lhs);
+    let rhs = self.visit(rhs);
+    match (op, int_val(&lhs), int_val(&rhs)) {
+      // `x + 0`, `x - 0`, `x * 1`
+      (Operator::Add, _, Some(0)) | (Operator::Sub, _, Some(0)) | (Operator::Mul, _, Some(1)) => lhs,
+      // `0 + x`, `1 * x`
+      (Operator::Add, Some(0), _) | (Operator::Mul, Some(1), _) => rhs,
+      // `x * 0`
+      (Operator::Mul, _, Some(0)) if is_pure(&lhs, self.overflow_mode) => rhs,
+      // `0 * x`
+      (Operator::Mul, Some(0), _) if is_pure(&rhs, self.overflow_mode) => lhs,
+      // `x - x`
+      (Operator::Sub, _, _) if lhs == rhs && is_pure(&lhs, self.overflow_mode) => {
+        Box::new(Ast::Int { val: 0 })
+      }
+      _ => Box::new(Ast::Binary {
+        op: op.clone(),
+        lhs,
+        rhs,
+      }),
+    }
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    Box::new(Ast::Unary {
+      op: op.clone(),
+      opr: self.visit(opr),
+    })
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    Box::new(Ast::FunCall {
+      name: *name,
+      args: args.iter().map(|arg| self.visit(arg)).collect(),
+    })
+  }
+
+  fn visit_int(&mut self, val: &i64) -> Self::Result {
+    Box::new(Ast::Int { val: *val })
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    Box::new(Ast::Id { id: *val })
+  }
+
+  fn visit_comment(&mut self, text: &str, trailing: &bool) -> Self::Result {
+    Box::new(Ast::Comment {
+      text: text.to_string(),
+      trailing: *trailing,
+    })
+  }
+}
+
+/// Gets the value of the specific integer literal.
+fn int_val(ast: &AstBox) -> Option<i64> {
+  match ast.as_ref() {
+    Ast::Int { val } => Some(*val),
+    _ => None,
+  }
+}
+
+/// Checks if the specific expression is pure, which means evaluating
+/// it has no side effects (function calls) and it can not fail
+/// at runtime (division by zero, negative exponent, or overflow
+/// in non-wrapping modes).
+fn is_pure(ast: &AstBox, mode: OverflowMode) -> bool {
+  let wrapping = mode == OverflowMode::Wrapping;
+  match ast.as_ref() {
+    Ast::Binary {
+      op: Operator::Div | Operator::Mod | Operator::Pow,
+      ..
+    } => false,
+    Ast::Binary {
+      op: Operator::Add | Operator::Sub | Operator::Mul,
+      ..
+    } if !wrapping => false,
+    Ast::Binary { lhs, rhs, .. } => is_pure(lhs, mode) && is_pure(rhs, mode),
+    Ast::Unary {
+      op: Operator::Sub,
+      ..
+    } if !wrapping => false,
+    Ast::Unary { opr, .. } => is_pure(opr, mode),
+    Ast::Int { .. } | Ast::Id { .. } => true,
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Simplifier;
+  use crate::define::{AstBox, OverflowMode};
+  use crate::front::parser::Parser;
+
+  /// Parses the specific expression.
+  fn parse(expr: &str) -> AstBox {
+    let mut parser = Parser::from_str(expr);
+    parser.parse_next_expr().unwrap()
+  }
+
+  /// Checks if the specific expression is simplified to the expected one.
+  fn check(expr: &str, expected: &str) {
+    assert!(Simplifier::new().simplify(&parse(expr)) == parse(expected), "{}", expr);
+  }
+
+  #[test]
+  fn test_simplify() {
+    check("x + 0", "x");
+    check("0 + x", "x");
+    check("x - 0", "x");
+    check("x * 1", "x");
+    check("1 * x", "x");
+    check("x * 0", "0");
+    check("0 * x", "0");
+    check("x - x", "0");
+    check("(a + b * 2) - (a + b * 2)", "0");
+    // nested identities
+    check("(x * 1 + 0) * (y - 0)", "x * y");
+    check("f(a * 0, b + 0) * 1", "f(0, b)");
+    // no identities
+    check("x - y", "x - y");
+    check("0 - x", "0 - x");
+    check("x / 1 + 1", "x / 1 + 1");
+  }
+
+  #[test]
+  fn test_simplify_impure() {
+    // function calls must still be evaluated
+    check("f() * 0", "f() * 0");
+    check("0 * input()", "0 * input()");
+    check("f(x) - f(x)", "f(x) - f(x)");
+    check("f() + 0", "f()");
+    check("1 * input()", "input()");
+    // possible division by zero
+    check("x / y * 0", "x / y * 0");
+    check("x % y - x % y", "x % y - x % y");
+  }
+
+  #[test]
+  fn test_simplify_overflow() {
+    let check = |mode, expr, expected| {
+      let mut simplifier = Simplifier::new();
+      simplifier.set_overflow_mode(mode);
+      assert!(simplifier.simplify(&parse(expr)) == parse(expected), "{}", expr);
+    };
+    // overflow is an error in non-wrapping modes
+    check(OverflowMode::Checked, "(a * b) * 0", "(a * b) * 0");
+    check(OverflowMode::Checked, "(a * b) - (a * b)", "(a * b) - (a * b)");
+    check(OverflowMode::Panicking, "0 * -a", "0 * -a");
+    check(OverflowMode::Checked, "x * 0", "0");
+    check(OverflowMode::Checked, "(a < b) - (a < b)", "0");
+    check(OverflowMode::Checked, "(a * b) + 0", "a * b");
+    // overflow wraps around in the default mode
+    check(OverflowMode::Wrapping, "(a * b) * 0", "0");
+    check(OverflowMode::Wrapping, "(a * b) - (a * b)", "0");
+  }
+}
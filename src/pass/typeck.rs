@@ -0,0 +1,390 @@
+use crate::define::{Ast, AstBox, AstVisitor, NestedMap, Operator, Program, Symbol};
+use crate::define::TryAstVisitor;
+use crate::dump::pretty::Printer;
+use std::collections::HashMap;
+use std::{error, fmt};
+
+pub use crate::define::Type;
+
+/// Type error.
+#[derive(Debug, PartialEq)]
+pub struct TypeError {
+  /// Error message.
+  pub message: &'static str,
+  /// Source code of the offending node.
+  pub node: String,
+}
+
+impl fmt::Display for TypeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}: `{}`", self.message, self.node)
+  }
+}
+
+impl error::Error for TypeError {}
+
+/// `Result` for `TypeChecker`.
+pub type Result = std::result::Result<Type, TypeError>;
+
+/// Static type checker of `int`s and `bool`s.
+///
+/// Relational and equality operations yield booleans, logical operations
+/// require booleans, and arithmetic operations require integers.
+/// Function parameters and return values are integers,
+/// unless they are annotated with other types. Function calls are
+/// checked against the signatures of the functions that have been
+/// checked or declared, calls to other functions (like library functions)
+/// take and return integers.
+#[derive(Default)]
+pub struct TypeChecker {
+  vars: NestedMap<Symbol, Type>,
+  /// Signatures of functions, argument types and return types.
+  funcs: HashMap<Symbol, (Vec<Type>, Type)>,
+  /// Return type of the current function.
+  ret_type: Option<Type>,
+}
+
+impl TypeChecker {
+  /// Creates a new type checker.
+  pub fn new() -> Self {
+    Self {
+      vars: NestedMap::new(),
+      funcs: HashMap::new(),
+      ret_type: None,
+    }
+  }
+
+  /// Declares signatures of all functions in the specific program,
+  /// so functions can be called before their definitions.
+  pub fn declare(&mut self, program: &Program) {
+    for ast in program {
+      if let Ast::FunDef {
+        name,
+        arg_types,
+        ret_type,
+        ..
+      } = ast.as_ref()
+      {
+        self.declare_func(name, arg_types, ret_type);
+      }
+    }
+  }
+
+  /// Checks the specific AST, returns its type.
+  pub fn check(&mut self, ast: &AstBox) -> Result {
+    self.visit(ast)
+  }
+
+  /// Adds the signature of the specific function,
+  /// unannotated types are integers.
+  fn declare_func(&mut self, name: &Symbol, arg_types: &[Option<Type>], ret_type: &Option<Type>) {
+    let arg_types = arg_types.iter().map(|ty| ty.unwrap_or(Type::Int)).collect();
+    self.funcs.insert(*name, (arg_types, ret_type.unwrap_or(Type::Int)));
+  }
+
+  /// Checks if the specific expression has the expected type.
+  fn expect(&mut self, ast: &AstBox, expected: Type, message: &'static str) -> Result {
+    if self.visit(ast)? == expected {
+      Ok(expected)
+    } else {
+      Err(error(message, ast))
+    }
+  }
+}
+
+/// Creates a type error of the specific node.
+fn error(message: &'static str, ast: &AstBox) -> TypeError {
+  TypeError {
+    message,
+    node: Printer::new().visit(ast),
+  }
+}
+
+impl AstVisitor for TypeChecker {
+  type Result = Result;
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    arg_types: &[Option<Type>],
+    ret_type: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    // declare the function first, for recursive calls
+    self.declare_func(name, arg_types, ret_type);
+    self.vars.push();
+    for (arg, ty) in args.iter().zip(arg_types) {
+      self.vars.add(*arg, ty.unwrap_or(Type::Int));
+    }
+    self.ret_type = *ret_type;
+    let ret = self.visit(body);
+    self.ret_type = None;
+    self.vars.pop();
+    ret
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    self.vars.push();
+    let ret = self.try_visit(stmts);
+    self.vars.pop();
+    ret.map(|_| Type::Unit)
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    let ty = self.visit(expr)?;
+    if !self.vars.add(*name, ty) {
+      self.vars.update(name, ty, false);
+    }
+    Ok(Type::Unit)
+  }
+
+  fn visit_assign(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    let ty = self.visit(expr)?;
+    match self.vars.get_rec(name) {
+      // yields the assigned type if used as an expression
+      Some(var_ty) if *var_ty == ty => Ok(ty),
+      Some(_) => Err(error("type mismatch in assignment", expr)),
+      None => Err(error("symbol has not been defined", &Box::new(Ast::Id { id: *name }))),
+    }
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.expect(cond, Type::Bool, "condition must be a boolean")?;
+    self.visit(then)?;
+    self.try_visit_opt(else_then)?;
+    Ok(Type::Unit)
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.expect(scrutinee, Type::Int, "switch value must be an integer")?;
+    for (_, body) in cases {
+      self.visit(body)?;
+    }
+    self.try_visit_opt(default)?;
+    Ok(Type::Unit)
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.visit(body)?;
+    self.expect(cond, Type::Bool, "condition must be a boolean")?;
+    Ok(Type::Unit)
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    let (expected, message) = match self.ret_type {
+      Some(Type::Bool) => (Type::Bool, "return value must be a boolean"),
+      _ => (Type::Int, "return value must be an integer"),
+    };
+    match expr {
+      Some(expr) => self.expect(expr, expected, message)?,
+      // functions with annotated return types must return values
+      None if self.ret_type.is_some() => {
+        return Err(error("missing return value", &Box::new(Ast::Return { expr: None })));
+      }
+      None => Type::Unit,
+    };
+    Ok(Type::Unit)
+  }
+
+  fn visit_binary(&mut self, op: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    match op {
+      Operator::Eq | Operator::NotEq => {
+        let ty = self.visit(lhs)?;
+        self.expect(rhs, ty, "operands must have the same type")?;
+        Ok(Type::Bool)
+      }
+      Operator::Less | Operator::LessEq => {
+        self.expect(lhs, Type::Int, "operand must be an integer")?;
+        self.expect(rhs, Type::Int, "operand must be an integer")?;
+        Ok(Type::Bool)
+      }
+      Operator::LAnd | Operator::LOr => {
+        self.expect(lhs, Type::Bool, "operand must be a boolean")?;
+        self.expect(rhs, Type::Bool, "operand must be a boolean")
+      }
+      _ => {
+        self.expect(lhs, Type::Int, "operand must be an integer")?;
+        self.expect(rhs, Type::Int, "operand must be an integer")
+      }
+    }
+  }
+
+  fn visit_unary(&mut self, op: &Operator, opr: &AstBox) -> Self::Result {
+    match op {
+      Operator::LNot => self.expect(opr, Type::Bool, "operand must be a boolean"),
+      _ => self.expect(opr, Type::Int, "operand must be an integer"),
+    }
+  }
+
+  fn visit_funcall(&mut self, name: &Symbol, args: &[AstBox]) -> Self::Result {
+    let (arg_types, ret_type) = match self.funcs.get(name) {
+      Some(sig) => sig.clone(),
+      None => (vec![Type::Int; args.len()], Type::Int),
+    };
+    // argument counts are checked by `ArityChecker`
+    for (arg, ty) in args.iter().zip(arg_types) {
+      let message = match ty {
+        Type::Bool => "argument must be a boolean",
+        _ => "argument must be an integer",
+      };
+      self.expect(arg, ty, message)?;
+    }
+    Ok(ret_type)
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {
+    Ok(Type::Int)
+  }
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    match self.vars.get_rec(val) {
+      Some(ty) => Ok(*ty),
+      None => Err(error("symbol has not been defined", &Box::new(Ast::Id { id: *val }))),
+    }
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {
+    Ok(Type::Unit)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{Type, TypeChecker, TypeError};
+  use crate::define::{Symbol, TryAstVisitor};
+  use crate::front::parser::Parser;
+
+  /// Parses and checks the specific function definition.
+  fn check(program: &str) -> super::Result {
+    let mut parser = Parser::from_str(program);
+    TypeChecker::new().check(&parser.parse_next().unwrap())
+  }
+
+  /// Parses and checks all function definitions in the specific program.
+  fn check_program(program: &str) -> super::Result {
+    let program = Parser::from_str(program).parse_program().unwrap();
+    let mut checker = TypeChecker::new();
+    checker.declare(&program);
+    for ast in &program {
+      checker.check(ast)?;
+    }
+    Ok(Type::Unit)
+  }
+
+  /// Creates a type error.
+  fn error(message: &'static str, node: &str) -> super::Result {
+    Err(TypeError {
+      message,
+      node: node.to_string(),
+    })
+  }
+
+  #[test]
+  fn test_typeck() {
+    let program = r#"
+      f(a, b) {
+        c := a < b && !(a == 0)
+        if c || a == b {
+          c = b <= a
+          return f(a - 1, b) * 2
+        }
+        return -a
+      }
+    "#;
+    assert_eq!(check(program), Ok(Type::Unit));
+  }
+
+  #[test]
+  fn test_typeck_annotations() {
+    assert_eq!(check("f(a: bool, b: int) -> bool { return a && b < 1 }"), Ok(Type::Unit));
+    assert_eq!(
+      check("f(a: bool) -> int { return a }"),
+      error("return value must be an integer", "a"),
+    );
+    assert_eq!(
+      check("f(a) -> bool { return a }"),
+      error("return value must be a boolean", "a"),
+    );
+    assert_eq!(check("f(a: bool) { return -a }"), error("operand must be an integer", "a"));
+  }
+
+  #[test]
+  fn test_typeck_funcall() {
+    let program = "g(a: bool) -> bool { return a } f() { if g(1 < 2) { return 0 } return 1 }";
+    assert_eq!(check_program(program), Ok(Type::Unit));
+    // functions can be called before their definitions
+    let program = r#"
+      f() { x := g(1) && h() return 0 }
+      g(a) -> bool { return a < 0 }
+      h() -> bool { return 1 == 1 }
+    "#;
+    assert_eq!(check_program(program), Ok(Type::Unit));
+    // recursive calls
+    assert_eq!(check("f(a: bool) -> bool { return f(!a) }"), Ok(Type::Unit));
+    assert_eq!(
+      check_program("g(a: bool) { return 0 } f() { return g(1) }"),
+      error("argument must be a boolean", "1"),
+    );
+    assert_eq!(
+      check_program("g() -> bool { return 1 == 1 } f() { return g() + 1 }"),
+      error("operand must be an integer", "g()"),
+    );
+    // library functions take and return integers
+    assert_eq!(check("f() { return print(input()) }"), Ok(Type::Unit));
+  }
+
+  #[test]
+  fn test_typeck_error() {
+    assert_eq!(
+      check("f() { if 1 + 2 { return 0 } }"),
+      error("condition must be a boolean", "1 + 2"),
+    );
+    assert_eq!(
+      check("f(a) { return a < 1 }"),
+      error("return value must be an integer", "a < 1"),
+    );
+    assert_eq!(
+      check("f(a) { return (a < 1) + 1 }"),
+      error("operand must be an integer", "a < 1"),
+    );
+    assert_eq!(
+      check("f(a) { if a && 0 < a { return 0 } }"),
+      error("operand must be a boolean", "a"),
+    );
+    assert_eq!(
+      check("f(a) { x := a == 1 x = a return 0 }"),
+      error("type mismatch in assignment", "a"),
+    );
+    assert_eq!(
+      check("f(a) { return f(a != 1) }"),
+      error("argument must be an integer", "a != 1"),
+    );
+    assert_eq!(
+      check("f() { if 1 == 1 { x := 1 } return x }"),
+      error("symbol has not been defined", "x"),
+    );
+  }
+
+  #[test]
+  fn test_typeck_short_circuit() {
+    let mut parser = Parser::from_str("x := 1 y := x && 1 z := 2");
+    let stmts: Vec<_> = (0..3).map(|_| parser.parse_next_stmt().unwrap()).collect();
+    let mut checker = TypeChecker::new();
+    let err = TypeError {
+      message: "operand must be a boolean",
+      node: "x".to_string(),
+    };
+    assert_eq!(checker.try_visit(&stmts), Err(err));
+    // the third statement has not been visited
+    assert_eq!(checker.vars.get_rec(&Symbol::new("x")), Some(&Type::Int));
+    assert_eq!(checker.vars.get_rec(&Symbol::new("z")), None);
+    assert_eq!(checker.try_visit(&stmts[..1]), Ok(vec![Type::Unit]));
+  }
+}
@@ -0,0 +1,214 @@
+use crate::define::{AstBox, AstVisitor, NestedMap, Operator, Symbol, Type};
+use std::fmt;
+
+/// Warning of a variable that is defined but never read.
+#[derive(Debug, PartialEq)]
+pub struct UnusedVar {
+  /// Name of the function containing the variable.
+  pub func: Symbol,
+  /// Name of the variable.
+  pub name: Symbol,
+}
+
+impl fmt::Display for UnusedVar {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "unused variable `{}` in function `{}`", self.name, self.func)
+  }
+}
+
+/// Unused variable checker, finds local variables that are never read.
+/// Assignments do not count as reads.
+#[derive(Default)]
+pub struct UnusedChecker {
+  /// Whether to check function parameters.
+  check_params: bool,
+  /// Indices of the visible variables in `vars`.
+  scopes: NestedMap<Symbol, usize>,
+  /// All variables of the current function and whether they have been read.
+  vars: Vec<(Symbol, bool)>,
+  /// Warnings reported so far.
+  warnings: Vec<UnusedVar>,
+}
+
+impl UnusedChecker {
+  /// Creates a new unused variable checker.
+  pub fn new() -> Self {
+    Self {
+      check_params: false,
+      scopes: NestedMap::new(),
+      vars: Vec::new(),
+      warnings: Vec::new(),
+    }
+  }
+
+  /// Sets whether to report unused function parameters (default `false`).
+  pub fn set_check_params(&mut self, check_params: bool) {
+    self.check_params = check_params;
+  }
+
+  /// Checks the specific function definition, returns all warnings.
+  pub fn check(&mut self, ast: &AstBox) -> Vec<UnusedVar> {
+    self.visit(ast);
+    std::mem::take(&mut self.warnings)
+  }
+
+  /// Defines a new variable in the current scope.
+  fn define(&mut self, name: &Symbol) {
+    self.vars.push((*name, false));
+    let index = self.vars.len() - 1;
+    if !self.scopes.add(*name, index) {
+      self.scopes.update(name, index, false);
+    }
+  }
+}
+
+impl AstVisitor for UnusedChecker {
+  type Result = ();
+
+  fn visit_fundef(
+    &mut self,
+    name: &Symbol,
+    args: &[Symbol],
+    _: &[Option<Type>],
+    _: &Option<Type>,
+    body: &AstBox,
+    _: &Option<String>,
+  ) -> Self::Result {
+    self.scopes.push();
+    for arg in args {
+      self.define(arg);
+    }
+    // parameters are treated as used if they need not be checked
+    if !self.check_params {
+      self.vars.iter_mut().for_each(|(_, used)| *used = true);
+    }
+    self.visit(body);
+    self.scopes.pop();
+    // collect warnings
+    let unused = self.vars.drain(..).filter(|(_, used)| !used);
+    self.warnings.extend(unused.map(|(var, _)| UnusedVar {
+      func: *name,
+      name: var,
+    }));
+  }
+
+  fn visit_block(&mut self, stmts: &[AstBox]) -> Self::Result {
+    self.scopes.push();
+    for stmt in stmts {
+      self.visit(stmt);
+    }
+    self.scopes.pop();
+  }
+
+  fn visit_define(&mut self, name: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+    self.define(name);
+  }
+
+  fn visit_assign(&mut self, _: &Symbol, expr: &AstBox) -> Self::Result {
+    self.visit(expr);
+  }
+
+  fn visit_if(&mut self, cond: &AstBox, then: &AstBox, else_then: &Option<AstBox>) -> Self::Result {
+    self.visit(cond);
+    self.visit(then);
+    if let Some(else_then) = else_then {
+      self.visit(else_then);
+    }
+  }
+
+  fn visit_switch(
+    &mut self,
+    scrutinee: &AstBox,
+    cases: &[(i64, AstBox)],
+    default: &Option<AstBox>,
+  ) -> Self::Result {
+    self.visit(scrutinee);
+    for (_, body) in cases {
+      self.visit(body);
+    }
+    if let Some(default) = default {
+      self.visit(default);
+    }
+  }
+
+  fn visit_do_while(&mut self, body: &AstBox, cond: &AstBox) -> Self::Result {
+    self.visit(body);
+    self.visit(cond);
+  }
+
+  fn visit_return(&mut self, expr: &Option<AstBox>) -> Self::Result {
+    if let Some(expr) = expr {
+      self.visit(expr);
+    }
+  }
+
+  fn visit_binary(&mut self, _: &Operator, lhs: &AstBox, rhs: &AstBox) -> Self::Result {
+    self.visit(lhs);
+    self.visit(rhs);
+  }
+
+  fn visit_unary(&mut self, _: &Operator, opr: &AstBox) -> Self::Result {
+    self.visit(opr);
+  }
+
+  fn visit_funcall(&mut self, _: &Symbol, args: &[AstBox]) -> Self::Result {
+    for arg in args {
+      self.visit(arg);
+    }
+  }
+
+  fn visit_int(&mut self, _: &i64) -> Self::Result {}
+
+  fn visit_id(&mut self, val: &Symbol) -> Self::Result {
+    if let Some(index) = self.scopes.get_rec(val) {
+      self.vars[*index].1 = true;
+    }
+  }
+
+  fn visit_comment(&mut self, _: &str, _: &bool) -> Self::Result {}
+}
+
+#[cfg(test)]
+mod test {
+  use super::{UnusedChecker, UnusedVar};
+  use crate::define::Symbol;
+  use crate::front::parser::Parser;
+
+  /// Parses and checks the specific function definition.
+  fn check(program: &str, check_params: bool) -> Vec<String> {
+    let mut parser = Parser::from_str(program);
+    let mut checker = UnusedChecker::new();
+    checker.set_check_params(check_params);
+    let warnings = checker.check(&parser.parse_next().unwrap());
+    warnings.iter().map(|warning| warning.name.to_string()).collect()
+  }
+
+  #[test]
+  fn test_unused() {
+    let program = "f(a, b) { t := 5 x := a x = 1 if x { y := 1 } return x }";
+    let mut parser = Parser::from_str(program);
+    let warnings = UnusedChecker::new().check(&parser.parse_next().unwrap());
+    assert_eq!(
+      warnings,
+      vec![
+        UnusedVar {
+          func: Symbol::new("f"),
+          name: Symbol::new("t"),
+        },
+        UnusedVar {
+          func: Symbol::new("f"),
+          name: Symbol::new("y"),
+        },
+      ],
+    );
+    assert_eq!(warnings[0].to_string(), "unused variable `t` in function `f`");
+    // parameters
+    assert_eq!(check(program, true), ["b", "t", "y"]);
+    // assignments are not reads
+    assert_eq!(check("f() { x := 1 x = 2 return 0 }", false), ["x"]);
+    // shadowed variables are tracked separately
+    assert_eq!(check("f() { x := 1 if 1 { x := 2 } return x }", false), ["x"]);
+    assert_eq!(check("f() { x := 1 if 1 { x := x } return 0 }", false), ["x"]);
+  }
+}
@@ -0,0 +1,139 @@
+use crate::back::interpreter::Interpreter;
+use crate::define::{Keyword, Operator, Token};
+use crate::front::parser::{Error, Parser};
+use std::io::{self, BufRead, Read, Write};
+
+/// Prompt for a new input.
+const PROMPT: &str = "> ";
+
+/// Prompt for continuing an unfinished input.
+const CONT_PROMPT: &str = "... ";
+
+/// Maximum depth of function calls.
+const MAX_DEPTH: usize = 10000;
+
+/// Kind of the REPL input.
+enum InputKind {
+  /// Empty input (spaces or comments).
+  Empty,
+  /// Function definition.
+  FunDef,
+  /// Statement.
+  Statement,
+  /// Expression.
+  Expression,
+}
+
+/// Runs the read-eval-print loop.
+///
+/// Reads inputs line by line from `input`, evaluates them and writes
+/// the results to `output`. Definitions persist across inputs.
+///
+/// Function calls can be nested up to 10000 levels, like the CLI does,
+/// so this should be run on a thread with a large stack (256 MiB).
+pub fn run(input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+  let mut intp = Interpreter::new();
+  intp.set_max_depth(MAX_DEPTH);
+  intp
+    .register_prelude()
+    .expect("failed to register the prelude");
+  let mut lines = input.lines();
+  loop {
+    // read the first line of the input
+    write!(output, "{}", PROMPT)?;
+    output.flush()?;
+    let mut source = match lines.next() {
+      Some(line) => line?,
+      None => break,
+    };
+    // keep reading until all braces are closed
+    while brace_depth(&source) > 0 {
+      write!(output, "{}", CONT_PROMPT)?;
+      output.flush()?;
+      match lines.next() {
+        Some(line) => {
+          source.push('\n');
+          source.push_str(&line?);
+        }
+        None => break,
+      }
+    }
+    // evaluate the input and print the result
+    match eval(&mut intp, &source) {
+      Ok(Some(val)) => writeln!(output, "{}", val)?,
+      Ok(None) => (),
+      Err(err) => writeln!(output, "error: {}", err)?,
+    }
+  }
+  writeln!(output)
+}
+
+/// Evaluates the specific input,
+/// returns the value if the input is an expression.
+fn eval(intp: &mut Interpreter, source: &str) -> Result<Option<i64>, String> {
+  let mut parser = Parser::from_str(source);
+  // parse the input
+  let ast = match input_kind(&mut parser) {
+    InputKind::Empty => return Ok(None),
+    InputKind::FunDef => parser.parse_next(),
+    InputKind::Statement => parser.parse_next_stmt(),
+    InputKind::Expression => parser.parse_next_expr(),
+  };
+  let ast = match ast {
+    Ok(ast) => ast,
+    Err(Error::End) => return Ok(None),
+    Err(Error::Error(err)) => return Err(err),
+  };
+  if !parser.is_end() {
+    return Err("unexpected tokens after the input".to_string());
+  }
+  // evaluate the input
+  Ok(intp.eval_stmt(ast)?)
+}
+
+/// Gets the kind of the specific input by peeking its leading tokens.
+fn input_kind<T: Read>(parser: &mut Parser<T>) -> InputKind {
+  match parser.peek(0) {
+    Ok(Token::End) => InputKind::Empty,
+    Ok(Token::Key(Keyword::If))
+    | Ok(Token::Key(Keyword::Switch))
+    | Ok(Token::Key(Keyword::Do))
+    | Ok(Token::Key(Keyword::Return)) => InputKind::Statement,
+    Ok(Token::Id(_)) => match parser.peek(1) {
+      Ok(Token::Op(Operator::Define)) | Ok(Token::Op(Operator::Assign)) => InputKind::Statement,
+      Ok(Token::Other('(')) => {
+        // skip to the matching ')', function definitions are followed by '{'
+        let mut depth = 1;
+        let mut n = 2;
+        while depth > 0 {
+          match parser.peek(n) {
+            Ok(Token::Other('(')) => depth += 1,
+            Ok(Token::Other(')')) => depth -= 1,
+            Ok(Token::End) | Err(_) => break,
+            _ => (),
+          }
+          n += 1;
+        }
+        if parser.peek(n) == &Ok(Token::Other('{')) {
+          InputKind::FunDef
+        } else {
+          InputKind::Expression
+        }
+      }
+      _ => InputKind::Expression,
+    },
+    _ => InputKind::Expression,
+  }
+}
+
+/// Counts the unclosed braces in the specific input, skipping comments.
+fn brace_depth(source: &str) -> i32 {
+  source
+    .lines()
+    .flat_map(|line| line.split('#').next().unwrap_or("").chars())
+    .fold(0, |depth, c| match c {
+      '{' => depth + 1,
+      '}' => depth - 1,
+      _ => depth,
+    })
+}
@@ -0,0 +1,57 @@
+use first_step_rust::repl;
+use std::io::Cursor;
+
+#[test]
+fn test_repl() {
+  let input = Cursor::new(
+    r#"x := 10
+x + 1
+y = 1
+add(a, b) {
+  return a + b
+}
+add(x, 5)
+x = x * 2
+# comment
+x
+"#,
+  );
+  let mut output = Vec::new();
+  repl::run(input, &mut output).unwrap();
+  assert_eq!(
+    String::from_utf8(output).unwrap(),
+    "> > 11\n> error: symbol has not been defined\n> ... ... > 15\n> > > 20\n> \n"
+  );
+}
+
+#[test]
+fn test_repl_comment_braces() {
+  let input = Cursor::new(
+    r#"f() { # }
+  return 1
+}
+f() # {
+"#,
+  );
+  let mut output = Vec::new();
+  repl::run(input, &mut output).unwrap();
+  assert_eq!(String::from_utf8(output).unwrap(), "> ... ... > 1\n> \n");
+}
+
+#[test]
+fn test_repl_error_in_call() {
+  let input = Cursor::new(
+    r#"x := 1
+f() { y := 1 return q }
+f()
+x = 2
+x
+"#,
+  );
+  let mut output = Vec::new();
+  repl::run(input, &mut output).unwrap();
+  assert_eq!(
+    String::from_utf8(output).unwrap(),
+    "> > > error: symbol has not been defined\n> > 2\n> \n"
+  );
+}